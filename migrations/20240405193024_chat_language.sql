@@ -0,0 +1,3 @@
+-- Add migration script here
+CREATE TYPE public.chatlanguageenum AS ENUM ('RU', 'EN');
+ALTER TABLE chats ADD COLUMN language public.chatlanguageenum NOT NULL DEFAULT 'RU';
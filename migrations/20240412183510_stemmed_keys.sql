@@ -0,0 +1,17 @@
+-- Add migration script here
+CREATE TYPE public.keynormalizationenum AS ENUM ('LOWERCASE', 'STEM');
+ALTER TABLE chats ADD COLUMN key_normalization public.keynormalizationenum NOT NULL DEFAULT 'LOWERCASE';
+ALTER TABLE answer_entities ADD COLUMN stemmed_key character varying(255) NULL;
+
+-- Snowball dictionary without stop words gives the same stems as the bot stemmer
+CREATE TEXT SEARCH DICTIONARY public.russian_stem_without_stopwords (TEMPLATE = snowball, LANGUAGE = russian);
+UPDATE answer_entities
+SET stemmed_key = coalesce(
+        (SELECT string_agg(coalesce((ts_lexize('public.russian_stem_without_stopwords', word))[1], word), ' '
+                           ORDER BY position)
+         FROM regexp_split_to_table(key, '[^0-9a-zа-яё_]+') WITH ORDINALITY AS words(word, position)
+         WHERE word <> ''),
+        key);
+DROP TEXT SEARCH DICTIONARY public.russian_stem_without_stopwords;
+
+CREATE INDEX answer_entities_chat_id_stemmed_key_idx ON answer_entities (chat_id, stemmed_key);
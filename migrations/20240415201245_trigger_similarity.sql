@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE chats ADD COLUMN trigger_similarity SMALLINT NOT NULL DEFAULT 100;
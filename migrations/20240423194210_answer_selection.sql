@@ -0,0 +1,15 @@
+-- Add migration script here
+ALTER TABLE answer_entities ADD COLUMN weight SMALLINT NOT NULL DEFAULT 1;
+
+CREATE TYPE public.selectionmodeenum AS ENUM ('WEIGHTED', 'CYCLE', 'STORY');
+-- Selection mode of key variants and number of hits, which is the position in cycle or story
+CREATE TABLE public.answer_key_selections
+(
+    id            SERIAL PRIMARY KEY,
+    chat_id       INT                          NOT NULL REFERENCES chats (id) ON DELETE CASCADE,
+    key           character varying(255)       NOT NULL,
+    reaction_type public.answerentitytypesenum NOT NULL,
+    mode          public.selectionmodeenum     NOT NULL DEFAULT 'WEIGHTED',
+    position      INT                          NOT NULL DEFAULT 0,
+    UNIQUE (chat_id, key, reaction_type)
+);
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE answer_entities ADD COLUMN deleted_at timestamp without time zone NULL;
+ALTER TABLE answer_entities ADD COLUMN deleted_by INT NULL REFERENCES members (id) ON DELETE SET NULL;
+
+CREATE INDEX answer_entities_chat_id_deleted_at_idx ON answer_entities (chat_id, deleted_at) WHERE NOT is_active;
@@ -0,0 +1,17 @@
+-- Add migration script here
+ALTER TABLE answer_entities ADD COLUMN author_id INT NULL REFERENCES members (id) ON DELETE SET NULL;
+ALTER TABLE dictionary_entities ADD COLUMN author_id INT NULL REFERENCES members (id) ON DELETE SET NULL;
+
+CREATE TYPE public.auditactionenum AS ENUM ('REMEMBER', 'DELETE', 'RESTORE', 'PURGE', 'ADD', 'SETTING');
+-- Append-only log of content and settings changes in chat
+CREATE TABLE public.audit_log
+(
+    id         SERIAL PRIMARY KEY,
+    chat_id    INT                         NOT NULL REFERENCES chats (id) ON DELETE CASCADE,
+    member_id  INT                         NULL REFERENCES members (id) ON DELETE SET NULL,
+    action     public.auditactionenum      NOT NULL,
+    details    text                        NOT NULL,
+    created_at timestamp without time zone NOT NULL
+);
+
+CREATE INDEX audit_log_chat_id_created_at_idx ON audit_log (chat_id, created_at);
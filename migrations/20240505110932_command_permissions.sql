@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TYPE public.memberroleenum AS ENUM ('MEMBER', 'AUTHOR', 'ADMIN', 'OWNER');
+-- Chat overrides of roles required by commands
+CREATE TABLE public.command_permissions
+(
+    id      SERIAL PRIMARY KEY,
+    chat_id INT                    NOT NULL REFERENCES chats (id) ON DELETE CASCADE,
+    command character varying(255) NOT NULL,
+    role    public.memberroleenum  NOT NULL,
+    UNIQUE (chat_id, command)
+);
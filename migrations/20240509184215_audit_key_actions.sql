@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TYPE public.auditactionenum ADD VALUE 'UNLINK';
+ALTER TYPE public.auditactionenum ADD VALUE 'RENAME';
+ALTER TYPE public.auditactionenum ADD VALUE 'MOVE';
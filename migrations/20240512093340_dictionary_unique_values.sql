@@ -0,0 +1,12 @@
+-- Add migration script here
+DELETE FROM dictionary_entities d
+    USING dictionary_entities o
+WHERE d.chat_id = o.chat_id
+  AND d.value = o.value
+  AND d.id > o.id;
+
+ALTER TABLE dictionary_entities
+    ADD CONSTRAINT dictionary_entities_chat_id_value_key UNIQUE (chat_id, value);
+
+ALTER TYPE public.auditactionenum ADD VALUE 'IMPORT';
+ALTER TYPE public.auditactionenum ADD VALUE 'DELETE_WORDS';
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TYPE public.auditactionenum ADD VALUE 'IMPORT_ARCHIVE';
@@ -0,0 +1,20 @@
+-- Add migration script here
+-- Pack content is the content of its source chat, so packs are curated with usual commands
+CREATE TABLE public.answer_packs
+(
+    id            SERIAL PRIMARY KEY,
+    chat_id       INT                         NOT NULL UNIQUE REFERENCES chats (id) ON DELETE CASCADE,
+    name          character varying(255)      NOT NULL UNIQUE,
+    author_id     INT                         NULL REFERENCES members (id) ON DELETE SET NULL,
+    is_private    boolean                     NOT NULL DEFAULT false,
+    answer_chance smallint                    NOT NULL DEFAULT 100,
+    created_at    timestamp without time zone NOT NULL DEFAULT now()
+);
+
+CREATE TABLE public.answer_packs_to_chats
+(
+    pack_id    INT                         NOT NULL REFERENCES answer_packs (id) ON DELETE CASCADE,
+    chat_id    INT                         NOT NULL REFERENCES chats (id) ON DELETE CASCADE,
+    created_at timestamp without time zone NOT NULL DEFAULT now(),
+    PRIMARY KEY (pack_id, chat_id)
+);
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- Key of media reaction is unique id of sticker, gif or photo file or emoji of sticker
+ALTER TYPE public.answerentitytypesenum ADD VALUE 'MEDIA';
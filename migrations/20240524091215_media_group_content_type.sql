@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TYPE public.answerentitycontenttypesenum ADD VALUE 'MEDIA_GROUP';
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TYPE public.answerentitycontenttypesenum ADD VALUE 'FORWARD';
+ALTER TYPE public.answerentitycontenttypesenum ADD VALUE 'COPY';
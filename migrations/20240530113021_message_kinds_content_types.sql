@@ -0,0 +1,6 @@
+-- Add migration script here
+ALTER TYPE public.answerentitycontenttypesenum ADD VALUE 'DICE';
+ALTER TYPE public.answerentitycontenttypesenum ADD VALUE 'POLL';
+ALTER TYPE public.answerentitycontenttypesenum ADD VALUE 'LOCATION';
+ALTER TYPE public.answerentitycontenttypesenum ADD VALUE 'CONTACT';
+ALTER TYPE public.answerentitycontenttypesenum ADD VALUE 'VENUE';
@@ -0,0 +1,15 @@
+-- Add migration script here
+ALTER TABLE chats ADD COLUMN welcome_delete_after SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE chats ADD COLUMN is_welcome_for_bots BOOLEAN NOT NULL DEFAULT false;
+
+-- Welcome is stored as answer content, so new members can be greeted with any content type
+CREATE TABLE public.chat_welcomes
+(
+    chat_id        INT                                 NOT NULL PRIMARY KEY REFERENCES chats (id) ON DELETE CASCADE,
+    content_type   public.answerentitycontenttypesenum NOT NULL,
+    value          text                                NOT NULL,
+    description    text                                NULL,
+    file_unique_id character varying(255)              NULL,
+    author_id      INT                                 NULL REFERENCES members (id) ON DELETE SET NULL,
+    updated_at     timestamp without time zone         NOT NULL DEFAULT now()
+);
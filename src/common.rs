@@ -1,13 +1,23 @@
+pub mod album_service;
+pub mod archive_service;
 pub mod command_parser;
 pub mod command_service;
 pub mod db;
 pub mod error;
 pub mod http;
 pub mod lexer;
+pub mod locale;
 pub mod message_service;
+pub mod permission_service;
 pub mod request;
 pub mod user_service;
 pub mod response;
 pub mod telegram_client;
+pub mod template;
+pub mod welcome_service;
 mod answer_entity_service;
 mod callback_service;
+mod chat_cache;
+mod regex_index;
+mod substring_index;
+mod trigger_index;
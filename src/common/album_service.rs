@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+
+use crate::common::chat_cache::{cache_key, CacheKey};
+use crate::common::db::ChatId;
+use crate::common::request::{MessageBody, MessageExt};
+use crate::common::response::{InputMedia, InputMediaKind};
+
+// Parts of album are kept for this time after the last received part
+const ALBUM_TTL: Duration = Duration::from_secs(10 * 60);
+// Telegram doesn't send albums with more parts
+const ALBUM_MAX_PARTS: usize = 10;
+
+struct AlbumPart {
+    message_id: i64,
+    file_unique_id: String,
+    media: InputMedia,
+}
+
+struct BufferedAlbum {
+    updated_at: Instant,
+    parts: Vec<AlbumPart>,
+}
+
+static ALBUM_BUFFER: Lazy<RwLock<HashMap<(CacheKey, String), BufferedAlbum>>> =
+    Lazy::new(Default::default);
+
+fn album_part(message_body: &MessageBody) -> Option<AlbumPart> {
+    let (kind, content, caption) = match &message_body.ext {
+        MessageExt::Photo { photo, caption, .. } => {
+            (InputMediaKind::Photo, photo.first()?, caption)
+        }
+        MessageExt::Video { video, caption, .. } => (InputMediaKind::Video, video, caption),
+        MessageExt::Document {
+            document, caption, ..
+        } => (InputMediaKind::Document, document, caption),
+        MessageExt::Audio { audio, caption, .. } => (InputMediaKind::Audio, audio, caption),
+        _ => return None,
+    };
+    Some(AlbumPart {
+        message_id: message_body.base.message_id,
+        file_unique_id: content.file_unique_id.clone(),
+        media: InputMedia {
+            kind,
+            media: content.file_id.clone(),
+            caption: caption.clone(),
+        },
+    })
+}
+
+// Every part of album arrives as a separate message, so parts are assembled here
+pub fn buffer_album_part(pool: &PgPool, chat_db_id: &ChatId, message_body: &MessageBody) {
+    let Some(media_group_id) = &message_body.base.media_group_id else {
+        return;
+    };
+    let Some(part) = album_part(message_body) else {
+        return;
+    };
+    let mut buffer = ALBUM_BUFFER.write().unwrap();
+    buffer.retain(|_, album| album.updated_at.elapsed() < ALBUM_TTL);
+    let album = buffer
+        .entry((cache_key(pool, chat_db_id), media_group_id.clone()))
+        .or_insert_with(|| BufferedAlbum {
+            updated_at: Instant::now(),
+            parts: vec![],
+        });
+    album.updated_at = Instant::now();
+    if album.parts.len() < ALBUM_MAX_PARTS
+        && album.parts.iter().all(|x| x.message_id != part.message_id)
+    {
+        album.parts.push(part);
+        album.parts.sort_by_key(|x| x.message_id);
+    }
+}
+
+// Content of the whole album of the message as value, file unique id and description.
+// None if the message is not a part of album or only this part is received
+pub fn album_content(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    message_body: &MessageBody,
+) -> Option<(String, Option<String>, Option<String>)> {
+    let media_group_id = message_body.base.media_group_id.as_ref()?;
+    let buffer = ALBUM_BUFFER.read().unwrap();
+    let album = buffer
+        .get(&(cache_key(pool, chat_db_id), media_group_id.clone()))
+        .filter(|x| x.updated_at.elapsed() < ALBUM_TTL && x.parts.len() > 1)?;
+    let media = album
+        .parts
+        .iter()
+        .map(|x| x.media.clone())
+        .collect::<Vec<InputMedia>>();
+    let file_unique_ids = album
+        .parts
+        .iter()
+        .map(|x| x.file_unique_id.as_str())
+        .collect::<Vec<&str>>()
+        .join(",");
+    Some((
+        serde_json::to_string(&media).ok()?,
+        Some(file_unique_ids),
+        None,
+    ))
+}
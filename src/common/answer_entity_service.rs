@@ -1,45 +1,247 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
 use sqlx::PgPool;
 
-use crate::common::db::{AnswerEntity, ChatId, EntityReactionType};
-use crate::common::lexer::{normalize_text, tokens_to_string, Token};
+use crate::common::db::{
+    AnswerEntity, AnswerKeySelection, AnswerPack, Chat as ChatDB, ChatId, EntityContentType,
+    EntityReactionType, KeyNormalization, MemberId, SelectionMode,
+};
+use crate::common::lexer::{normalize_text, stem_text, tokens_to_string, Token};
+use crate::common::regex_index::{expand_captures, invalidate_regex_index, regex_index};
+use crate::common::substring_index::{invalidate_substring_index, substring_index};
+use crate::common::trigger_index::{invalidate_trigger_index, trigger_index};
+
+// Indexes of keys are cached, so they must be invalidated after every change of answers
+// or packs of the chat. Indexes of chats subscribed to the pack of the chat are invalidated too
+pub fn invalidate_indexes(pool: &PgPool, chat_db_id: &ChatId) {
+    invalidate_substring_index(pool, chat_db_id);
+    invalidate_regex_index(pool, chat_db_id);
+    invalidate_trigger_index(pool, chat_db_id);
+}
+
+async fn find_values_by_keys(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    keys: &[String],
+    entity_reaction_type: &EntityReactionType,
+) -> Vec<AnswerEntity> {
+    match ChatDB::key_normalization(pool, chat_db_id)
+        .await
+        .unwrap_or_default()
+    {
+        KeyNormalization::Lowercase => {
+            AnswerEntity::find_values_by_keys(pool, chat_db_id, keys, entity_reaction_type).await
+        }
+        KeyNormalization::Stem => {
+            let stemmed_keys = keys.iter().map(|x| stem_text(x)).collect::<Vec<String>>();
+            AnswerEntity::find_values_by_stemmed_keys(
+                pool,
+                chat_db_id,
+                &stemmed_keys,
+                entity_reaction_type,
+            )
+            .await
+        }
+    }
+}
+
+// Content of every subscribed pack is used with the answer chance of the pack. The chance is
+// rolled once per pack on selection, so the found content of skipped packs is kept
+async fn skipped_packs(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    answer_entities: &[AnswerEntity],
+) -> Vec<ChatId> {
+    if answer_entities.iter().all(|x| &x.chat_id == chat_db_id) {
+        return vec![];
+    }
+    AnswerPack::subscribed(pool, chat_db_id)
+        .await
+        .into_iter()
+        .filter(|x| rand::thread_rng().gen_range(0..100) >= x.answer_chance)
+        .map(|x| x.chat_id)
+        .collect()
+}
 
 pub async fn substrings<'a>(
     pool: &PgPool,
     tokens: &'a [Token<'a>],
     chat_db_id: &ChatId,
 ) -> Vec<AnswerEntity> {
-    let mut keys: Vec<String> = vec![];
-    if tokens.len() > 1 {
-        keys.push(normalize_text(tokens_to_string(tokens, false)));
+    let index = substring_index(pool, chat_db_id).await;
+    let keys = index.find_keys(&normalize_text(tokens_to_string(tokens, false)));
+    if keys.is_empty() {
+        return vec![];
     }
-    tokens.iter().for_each(|x| {
-        if let Token::Word(word) = x {
-            keys.push(normalize_text(word.to_string()))
+    match index.key_normalization {
+        KeyNormalization::Lowercase => {
+            AnswerEntity::find_values_by_keys(
+                pool,
+                chat_db_id,
+                &keys,
+                &EntityReactionType::Substring,
+            )
+            .await
+        }
+        KeyNormalization::Stem => {
+            AnswerEntity::find_values_by_stemmed_keys(
+                pool,
+                chat_db_id,
+                &keys,
+                &EntityReactionType::Substring,
+            )
+            .await
         }
-    });
-    AnswerEntity::find_values_by_keys(pool, chat_db_id, &keys, &EntityReactionType::Substring).await
+    }
 }
 
+// Values of trigger equal to text or, if chat allows typos, of the most similar trigger.
+// Similarity of found trigger is returned in percent
 pub async fn triggers<'a>(
     pool: &PgPool,
     tokens: &'a [Token<'a>],
     chat_db_id: &ChatId,
-) -> Vec<AnswerEntity> {
-    AnswerEntity::find_values_by_keys(
+) -> (Vec<AnswerEntity>, i16) {
+    let text = normalize_text(tokens_to_string(tokens, false));
+    let answer_entities = find_values_by_keys(
         pool,
         chat_db_id,
-        &[normalize_text(tokens_to_string(tokens, false))],
+        std::slice::from_ref(&text),
         &EntityReactionType::Trigger,
     )
-    .await
+    .await;
+    if !answer_entities.is_empty() {
+        return (answer_entities, 100);
+    }
+    let min_similarity = ChatDB::trigger_similarity(pool, chat_db_id)
+        .await
+        .unwrap_or(100);
+    if min_similarity >= 100 {
+        return (vec![], 0);
+    }
+    let index = trigger_index(pool, chat_db_id).await;
+    let Some((key, similarity)) = index.most_similar(&text, min_similarity) else {
+        return (vec![], 0);
+    };
+    let answer_entities = match index.key_normalization {
+        KeyNormalization::Lowercase => {
+            AnswerEntity::find_values_by_keys(
+                pool,
+                chat_db_id,
+                &[key],
+                &EntityReactionType::Trigger,
+            )
+            .await
+        }
+        KeyNormalization::Stem => {
+            AnswerEntity::find_values_by_stemmed_keys(
+                pool,
+                chat_db_id,
+                &[key],
+                &EntityReactionType::Trigger,
+            )
+            .await
+        }
+    };
+    (answer_entities, similarity)
+}
+
+// Values of patterns matching text, "$1" in text values is replaced with captures of pattern
+pub async fn regexes(pool: &PgPool, text: &str, chat_db_id: &ChatId) -> Vec<AnswerEntity> {
+    let index = regex_index(pool, chat_db_id).await;
+    let matches = index.find_matches(text);
+    if matches.is_empty() {
+        return vec![];
+    }
+    let keys = matches
+        .iter()
+        .map(|(pattern, _, _)| pattern.to_string())
+        .collect::<Vec<String>>();
+    let answer_entities =
+        AnswerEntity::find_values_by_keys(pool, chat_db_id, &keys, &EntityReactionType::Regex)
+            .await;
+    answer_entities
+        .into_iter()
+        .map(|mut answer_entity| {
+            if answer_entity.content_type != EntityContentType::Text {
+                return answer_entity;
+            }
+            if let Some((_, regex, captures)) =
+                matches.iter().find(|(x, _, _)| *x == answer_entity.key)
+            {
+                answer_entity.value = expand_captures(regex, captures, &answer_entity.value);
+            }
+            answer_entity
+        })
+        .collect()
+}
+
+// Values of media reactions on sticker, gif or photo by keys of the media
+pub async fn media(pool: &PgPool, keys: &[String], chat_db_id: &ChatId) -> Vec<AnswerEntity> {
+    AnswerEntity::find_values_by_keys(pool, chat_db_id, keys, &EntityReactionType::Media).await
+}
+
+// Variant of random key from found ones, chosen by selection mode of the key. Positions of
+// cycle and story index all variants of the key, so a variant of a skipped pack passes its turn
+pub async fn select_answer_entity(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    answer_entities: Vec<AnswerEntity>,
+) -> Option<AnswerEntity> {
+    let skipped = skipped_packs(pool, chat_db_id, &answer_entities).await;
+    let is_answering = |x: &AnswerEntity| !skipped.contains(&x.chat_id);
+    let mut keys = answer_entities
+        .iter()
+        .filter(|x| is_answering(x))
+        .map(|x| x.key.to_string())
+        .collect::<Vec<String>>();
+    keys.sort();
+    keys.dedup();
+    if keys.is_empty() {
+        return None;
+    }
+    let key = &keys[rand::thread_rng().gen_range(0..keys.len())];
+    let variants = answer_entities
+        .iter()
+        .filter(|x| &x.key == key)
+        .collect::<Vec<&AnswerEntity>>();
+    let reaction_type = &variants[0].reaction_type;
+    let mode =
+        AnswerKeySelection::modes(pool, chat_db_id, std::slice::from_ref(key), reaction_type)
+            .await
+            .pop()
+            .map(|(_, mode)| mode)
+            .unwrap_or_default();
+    let index = match mode {
+        SelectionMode::Weighted => WeightedIndex::new(variants.iter().map(|x| {
+            if is_answering(x) {
+                x.weight.max(1)
+            } else {
+                0
+            }
+        }))
+        .ok()
+        .map(|x| x.sample(&mut rand::thread_rng())),
+        SelectionMode::Cycle => AnswerKeySelection::hit(pool, chat_db_id, key, reaction_type)
+            .await
+            .ok()
+            .map(|x| x as usize % variants.len()),
+        SelectionMode::Story => AnswerKeySelection::hit(pool, chat_db_id, key, reaction_type)
+            .await
+            .ok()
+            .map(|x| (x as usize).min(variants.len() - 1)),
+    };
+    index
+        .filter(|x| is_answering(variants[*x]))
+        .map(|x| variants[x].clone())
 }
 
-pub async fn all_keys<'a>(
+pub async fn all_keys(
     pool: &PgPool,
     value: &String,
     chat_db_id: &ChatId,
     is_media: bool,
-) -> Vec<String> {
+) -> Vec<(String, Option<MemberId>)> {
     if is_media {
         AnswerEntity::find_keys_by_file_unique_id(pool, chat_db_id, value).await
     } else {
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgPool};
+
+use crate::common::answer_entity_service::invalidate_indexes;
+use crate::common::db::{
+    AnswerEntity, ArchivedAnswerEntity, Chat as ChatDB, ChatId, DictionaryEntity,
+    EntityReactionType, KeyNormalization, Language, MemberId,
+};
+use crate::common::locale::{translate, Phrase};
+use crate::common::regex_index::compile_pattern;
+use crate::config::BOT_ID;
+
+// Archives of greater versions are not supported
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct ArchivedSettings {
+    pub language: Option<Language>,
+    pub substring_answer_chance: Option<i16>,
+    pub morph_answer_chance: Option<i16>,
+    pub key_normalization: Option<KeyNormalization>,
+    pub trigger_similarity: Option<i16>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ChatArchive {
+    pub version: u32,
+    // Bot, which exported the archive
+    pub bot_id: String,
+    #[serde(default)]
+    pub settings: ArchivedSettings,
+    #[serde(default)]
+    pub answers: Vec<ArchivedAnswerEntity>,
+    #[serde(default)]
+    pub dictionary: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportReport {
+    pub added_answers: u64,
+    pub existed_answers: u64,
+    pub added_words: u64,
+    pub existed_words: u64,
+    // Media of other bots can't be sent by file id
+    pub skipped_media: u64,
+    pub invalid_keys: u64,
+    // Settings of the chat are kept, the differing ones are reported
+    pub conflicting_settings: Vec<Phrase>,
+}
+
+impl ImportReport {
+    pub fn text(&self, language: &Language) -> String {
+        let mut lines = vec![
+            format!(
+                "{}: {}",
+                translate(language, Phrase::AddedAnswers),
+                self.added_answers
+            ),
+            format!(
+                "{}: {}",
+                translate(language, Phrase::AddedWords),
+                self.added_words
+            ),
+            format!(
+                "{}: {}",
+                translate(language, Phrase::ExistedAnswers),
+                self.existed_answers
+            ),
+            format!(
+                "{}: {}",
+                translate(language, Phrase::ExistedWords),
+                self.existed_words
+            ),
+        ];
+        if self.skipped_media > 0 {
+            lines.push(format!(
+                "{}: {}",
+                translate(language, Phrase::SkippedMedia),
+                self.skipped_media
+            ));
+        }
+        if self.invalid_keys > 0 {
+            lines.push(format!(
+                "{}: {}",
+                translate(language, Phrase::SkippedInvalidKeys),
+                self.invalid_keys
+            ));
+        }
+        if !self.conflicting_settings.is_empty() {
+            lines.push(format!(
+                "{}: {}",
+                translate(language, Phrase::SettingsNotChanged),
+                self.conflicting_settings
+                    .iter()
+                    .map(|x| translate(language, *x))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+async fn chat_settings(pool: &PgPool, chat_db_id: &ChatId) -> ArchivedSettings {
+    ArchivedSettings {
+        language: ChatDB::language(pool, chat_db_id).await,
+        substring_answer_chance: ChatDB::substring_answer_chance(pool, chat_db_id).await,
+        morph_answer_chance: ChatDB::morph_answer_chance(pool, chat_db_id).await,
+        key_normalization: ChatDB::key_normalization(pool, chat_db_id).await,
+        trigger_similarity: ChatDB::trigger_similarity(pool, chat_db_id).await,
+    }
+}
+
+pub async fn export_archive(pool: &PgPool, chat_db_id: &ChatId) -> ChatArchive {
+    ChatArchive {
+        version: ARCHIVE_VERSION,
+        bot_id: BOT_ID.to_string(),
+        settings: chat_settings(pool, chat_db_id).await,
+        answers: AnswerEntity::archived(pool, chat_db_id).await,
+        dictionary: DictionaryEntity::all_values(pool, chat_db_id).await,
+    }
+}
+
+fn conflicting_settings(current: &ArchivedSettings, archived: &ArchivedSettings) -> Vec<Phrase> {
+    [
+        (
+            archived.language.is_some() && archived.language != current.language,
+            Phrase::ChatLanguage,
+        ),
+        (
+            archived.substring_answer_chance.is_some()
+                && archived.substring_answer_chance != current.substring_answer_chance,
+            Phrase::SubstringChance,
+        ),
+        (
+            archived.morph_answer_chance.is_some()
+                && archived.morph_answer_chance != current.morph_answer_chance,
+            Phrase::MorphChance,
+        ),
+        (
+            archived.key_normalization.is_some()
+                && archived.key_normalization != current.key_normalization,
+            Phrase::WordForms,
+        ),
+        (
+            archived.trigger_similarity.is_some()
+                && archived.trigger_similarity != current.trigger_similarity,
+            Phrase::TriggerSimilarity,
+        ),
+    ]
+    .into_iter()
+    .filter(|(is_conflicting, _)| *is_conflicting)
+    .map(|(_, name)| name)
+    .collect()
+}
+
+// Content is merged into the chat, existing content and settings of the chat are kept
+pub async fn import_archive(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    archive: &ChatArchive,
+) -> Result<ImportReport, Error> {
+    let mut report = ImportReport {
+        conflicting_settings: conflicting_settings(
+            &chat_settings(pool, chat_db_id).await,
+            &archive.settings,
+        ),
+        ..Default::default()
+    };
+    let mut seen = HashSet::new();
+    let mut answers = vec![];
+    for answer in &archive.answers {
+        if answer.bot_specific && archive.bot_id != *BOT_ID {
+            report.skipped_media += 1;
+        } else if answer.reaction_type == EntityReactionType::Regex
+            && compile_pattern(&answer.key).is_err()
+        {
+            report.invalid_keys += 1;
+        } else if seen.insert((
+            &answer.key,
+            &answer.reaction_type,
+            &answer.content_type,
+            &answer.value,
+        )) {
+            answers.push(answer.clone());
+        }
+    }
+    report.added_answers =
+        AnswerEntity::bulk_import(pool, chat_db_id, &answers, member_db_id).await?;
+    if report.added_answers > 0 {
+        invalidate_indexes(pool, chat_db_id);
+    }
+    report.existed_answers = answers.len() as u64 - report.added_answers;
+    let words = archive
+        .dictionary
+        .iter()
+        .filter(|x| !x.is_empty())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    for chunk in words.chunks(5000) {
+        report.added_words +=
+            DictionaryEntity::bulk_add_items(pool, chunk.to_vec(), chat_db_id, member_db_id)
+                .await?
+                .len() as u64;
+    }
+    report.existed_words = words.len() as u64 - report.added_words;
+    Ok(report)
+}
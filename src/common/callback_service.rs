@@ -1,22 +1,57 @@
-use crate::common::command_service::process_command;
-use crate::common::db::{ChatId, ChatToMemberId, MemberId};
+use crate::common::command_service::{
+    dictionary_page_callback, key_values_callback, keys_page_callback, process_command,
+    DICTIONARY_PAGE_CALLBACK, KEYS_PAGE_CALLBACK, KEY_VALUES_CALLBACK,
+};
+use crate::common::db::{AnswerEntityId, ChatId, ChatToMemberId, MemberId};
 use crate::common::error::ProcessError;
-use crate::common::lexer::tokenize;
+use crate::common::lexer::tokenize_with_entities;
 use crate::common::request::{ReplyMarkup, ReplyMarkupButton, RequestPayload};
 use crate::common::response::ResponseMessage;
 use log::{info, warn};
 use sqlx::PgPool;
 
-pub async fn process_callback<'a>(
-    request_payload: &'a RequestPayload,
+pub async fn process_callback(
+    request_payload: &RequestPayload,
     pool: &PgPool,
     member_db_id: &MemberId,
     chat_db_id: &ChatId,
     chat_to_member_db_id: &ChatToMemberId,
-) -> Result<ResponseMessage, ProcessError<'a>> {
+) -> Result<ResponseMessage, ProcessError> {
     if request_payload.any_message().reply().is_none() {
         return Err(ProcessError::Next);
     };
+    let callback_data = request_payload.callback_data().unwrap_or_default();
+    if let Some(page) = callback_data.strip_prefix(KEYS_PAGE_CALLBACK) {
+        return match page.parse::<i64>() {
+            Ok(page) => {
+                keys_page_callback(pool, request_payload.any_message(), chat_db_id, page).await
+            }
+            Err(_) => Err(ProcessError::Stop),
+        };
+    }
+    if let Some(page) = callback_data.strip_prefix(DICTIONARY_PAGE_CALLBACK) {
+        return match page.parse::<i64>() {
+            Ok(page) => {
+                dictionary_page_callback(pool, request_payload.any_message(), chat_db_id, page)
+                    .await
+            }
+            Err(_) => Err(ProcessError::Stop),
+        };
+    }
+    if let Some(id) = callback_data.strip_prefix(KEY_VALUES_CALLBACK) {
+        return match id.parse::<i32>() {
+            Ok(id) => {
+                key_values_callback(
+                    pool,
+                    request_payload.any_message(),
+                    chat_db_id,
+                    &AnswerEntityId::new(id),
+                )
+                .await
+            }
+            Err(_) => Err(ProcessError::Stop),
+        };
+    }
     match request_payload.any_message().reply_markup() {
         None => {
             info!("Обработка Callback без reply_markup невозможна");
@@ -24,13 +59,10 @@ pub async fn process_callback<'a>(
         }
         Some(ReplyMarkup { inline_keyboard: x }) => match &x[0][0] {
             ReplyMarkupButton { text, .. } if text == "Roll" => {
-                let tokens = request_payload
-                    .any_message()
-                    .reply()
-                    .unwrap()
-                    .ext
+                let message_ext = &request_payload.any_message().reply().unwrap().ext;
+                let tokens = message_ext
                     .raw_text()
-                    .map(tokenize);
+                    .map(|text| tokenize_with_entities(text, message_ext.entities()));
                 match process_command(
                     &tokens,
                     request_payload.any_message(),
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+
+use sqlx::PgPool;
+
+use crate::common::db::ChatId;
+
+// Caches are separated by database name, because tests use a database per test
+pub type CacheKey = (String, ChatId);
+
+pub fn cache_key(pool: &PgPool, chat_id: &ChatId) -> CacheKey {
+    (
+        pool.connect_options()
+            .get_database()
+            .unwrap_or_default()
+            .to_string(),
+        chat_id.clone(),
+    )
+}
+
+// Value built from data of several chats, so it's outdated on change of any of them
+pub trait ChatSources {
+    fn sources(&self) -> &[ChatId];
+}
+
+struct ChatCacheState<T> {
+    values: HashMap<CacheKey, Arc<T>>,
+    // Incremented on every invalidation to skip caching of values built from outdated data
+    versions: HashMap<CacheKey, u64>,
+}
+
+pub struct ChatCache<T> {
+    state: RwLock<ChatCacheState<T>>,
+}
+
+impl<T> Default for ChatCache<T> {
+    fn default() -> Self {
+        ChatCache {
+            state: RwLock::new(ChatCacheState {
+                values: HashMap::new(),
+                versions: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl<T: ChatSources> ChatCache<T> {
+    pub async fn get_or_build<F>(&self, pool: &PgPool, chat_id: &ChatId, build: F) -> Arc<T>
+    where
+        F: Future<Output = T>,
+    {
+        let cache_key = cache_key(pool, chat_id);
+        let version = {
+            let state = self.state.read().unwrap();
+            if let Some(value) = state.values.get(&cache_key) {
+                return value.clone();
+            }
+            state.versions.get(&cache_key).copied().unwrap_or_default()
+        };
+        let value = Arc::new(build.await);
+        let mut state = self.state.write().unwrap();
+        if state.versions.get(&cache_key).copied().unwrap_or_default() == version {
+            state.values.insert(cache_key, value.clone());
+        }
+        value
+    }
+
+    // Values of chats, which are built from data of the chat, are invalidated too
+    pub fn invalidate(&self, pool: &PgPool, chat_id: &ChatId) {
+        let cache_key = cache_key(pool, chat_id);
+        let mut state = self.state.write().unwrap();
+        let mut outdated = state
+            .values
+            .iter()
+            .filter(|((database, _), value)| {
+                *database == cache_key.0 && value.sources().contains(chat_id)
+            })
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<CacheKey>>();
+        outdated.push(cache_key);
+        for key in outdated {
+            state.values.remove(&key);
+            *state.versions.entry(key).or_default() += 1;
+        }
+    }
+}
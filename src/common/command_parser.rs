@@ -1,26 +1,50 @@
 use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::time::Duration;
 
 use once_cell::sync::{Lazy, OnceCell};
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, IntoStaticStr};
 
 use Command::*;
-use ControlItem::{KeyWord, MorphWord, Substring, Trigger};
+use ControlItem::{KeyWord, MorphWord, Regex, Substring, Trigger};
 use Token::*;
 
+use crate::common::db::{
+    BotWelcome, KeyNormalization, Language, LocalizedWord, PackVisibility, Role, SelectionMode,
+};
 use crate::common::error::ProcessError;
-use crate::common::lexer::{tokenize, Token};
+use crate::common::lexer::{joined_string, tokenize, Token};
+use crate::common::locale::Phrase;
 
-#[derive(Debug, Eq, PartialEq, EnumIter, Hash, Clone)]
+#[derive(Debug, Eq, PartialEq, EnumIter, IntoStaticStr, Hash, Clone, Copy)]
+#[strum(serialize_all = "snake_case")]
 pub enum Command {
     Help,
     Who,
     AnswerChance,
     Show,
+    Keys,
     Add,
+    Dictionary,
+    DeleteWords,
+    ImportWords,
+    Export,
+    Import,
+    CreatePack,
+    Packs,
+    PackAccess,
+    PackChance,
+    Subscribe,
+    Unsubscribe,
     Remember,
+    RememberForward,
+    RememberCopy,
+    RememberMedia,
     Delete,
+    Unlink,
+    Rename,
+    Move,
     Check,
     Say,
     Couple,
@@ -34,18 +58,227 @@ pub enum Command {
     Quote,
     Joke,
     Advice,
+    ChatLanguage,
+    WordForms,
+    TriggerSimilarity,
+    Welcome,
+    DeleteWelcome,
+    WelcomeDelay,
+    WelcomeBots,
+    Template,
+    Weight,
+    Selection,
+    Trash,
+    Restore,
+    Purge,
+    History,
+    Permission,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum ArgumentSchema<'a> {
+    // Any text after command and control item, passed as is
+    #[default]
+    Text,
+    // Several values separated by ","
+    List,
+    // Two values separated by ",": the old one and the new one
+    Pair,
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    EnumWord(Vec<&'a str>),
+    // Optional word from the list followed by several values separated by ","
+    EnumWordWithList(Vec<&'a str>),
+    // Chat member mentioned via @username or text mention of user without username
+    Member,
+    // Number with time unit: "30с", "10м", "2 часа"
+    Duration,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Argument<'a> {
+    Empty,
+    Text(&'a [Token<'a>]),
+    List(Vec<String>),
+    Pair(String, String),
+    Integer(i64),
+    EnumWord(&'a str),
+    EnumWordWithList(Option<&'a str>, Vec<String>),
+    Member(MemberArgument<'a>),
+    Duration(Duration),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum MemberArgument<'a> {
+    Username(&'a str),
+    // Telegram user id from text mention
+    UserId(i64),
+}
+
+fn duration_unit_seconds(unit: &str) -> Option<u64> {
+    match unit.to_lowercase().as_str() {
+        "s" | "sec" | "с" | "сек" | "секунд" | "секунда" | "секунды" | "секунду" => {
+            Some(1)
+        }
+        "m" | "min" | "м" | "мин" | "минут" | "минута" | "минуты" | "минуту" => {
+            Some(60)
+        }
+        "h" | "ч" | "час" | "часа" | "часов" => Some(60 * 60),
+        "d" | "д" | "день" | "дня" | "дней" => Some(24 * 60 * 60),
+        _ => None,
+    }
+}
+
+fn parse_duration(amount: &str, unit: &str) -> Option<Duration> {
+    match (amount.parse::<u64>(), duration_unit_seconds(unit)) {
+        (Ok(amount), Some(seconds)) if amount > 0 => {
+            amount.checked_mul(seconds).map(Duration::from_secs)
+        }
+        _ => None,
+    }
+}
+
+impl<'a> ArgumentSchema<'a> {
+    pub fn help(&self, language: &Language) -> String {
+        match (self, language) {
+            (ArgumentSchema::Text, Language::Ru) => String::from("произвольный текст"),
+            (ArgumentSchema::Text, Language::En) => String::from("any text"),
+            (ArgumentSchema::List, Language::Ru) => {
+                String::from("одно или несколько значений через \",\"")
+            }
+            (ArgumentSchema::List, Language::En) => {
+                String::from("one or several values separated by \",\"")
+            }
+            (ArgumentSchema::Pair, Language::Ru) => {
+                String::from("старое и новое значения через \",\"")
+            }
+            (ArgumentSchema::Pair, Language::En) => {
+                String::from("the old and the new values separated by \",\"")
+            }
+            (ArgumentSchema::Integer { min, max }, Language::Ru) => {
+                format!("целое число от {min} до {max}")
+            }
+            (ArgumentSchema::Integer { min, max }, Language::En) => {
+                format!("an integer from {min} to {max}")
+            }
+            (ArgumentSchema::EnumWord(words), Language::Ru) => {
+                format!("одно из значений: {}", words.join(", "))
+            }
+            (ArgumentSchema::EnumWord(words), Language::En) => {
+                format!("one of: {}", words.join(", "))
+            }
+            (ArgumentSchema::EnumWordWithList(words), Language::Ru) => format!(
+                "необязательное значение из: {}, и одно или несколько значений через \",\"",
+                words.join(", ")
+            ),
+            (ArgumentSchema::EnumWordWithList(words), Language::En) => format!(
+                "an optional value from: {}, and one or several values separated by \",\"",
+                words.join(", ")
+            ),
+            (ArgumentSchema::Member, Language::Ru) => String::from("упоминание участника чата"),
+            (ArgumentSchema::Member, Language::En) => String::from("a chat member mention"),
+            (ArgumentSchema::Duration, Language::Ru) => {
+                String::from("значение длительности, например: 30с, 10м, 2ч, 1д")
+            }
+            (ArgumentSchema::Duration, Language::En) => {
+                String::from("a duration, for example: 30s, 10m, 2h, 1d")
+            }
+        }
+    }
+
+    pub fn feedback(&self, language: &Language) -> String {
+        match language {
+            Language::Ru => format!(
+                "Указано неверное значение. Должно быть {}",
+                self.help(language)
+            ),
+            Language::En => format!("Invalid value. Expected {}", self.help(language)),
+        }
+    }
+
+    pub fn parse<'b>(&self, rest: &'b [Token<'b>]) -> Option<Argument<'b>>
+    where
+        'a: 'b,
+    {
+        if rest.is_empty() {
+            return Some(Argument::Empty);
+        }
+        match (self, rest) {
+            (ArgumentSchema::Text, _) => Some(Argument::Text(rest)),
+            (ArgumentSchema::List, _) => {
+                let values = joined_string(rest)
+                    .into_iter()
+                    .filter(|x| !x.is_empty())
+                    .collect::<Vec<String>>();
+                if values.is_empty() {
+                    None
+                } else {
+                    Some(Argument::List(values))
+                }
+            }
+            (ArgumentSchema::Pair, _) => match joined_string(rest).as_slice() {
+                [old, new] if !old.is_empty() && !new.is_empty() && old != new => {
+                    Some(Argument::Pair(old.to_string(), new.to_string()))
+                }
+                _ => None,
+            },
+            (ArgumentSchema::Integer { min, max }, [Number(value)]) => match value.parse::<i64>() {
+                Ok(x) if (*min..=*max).contains(&x) => Some(Argument::Integer(x)),
+                _ => None,
+            },
+            (ArgumentSchema::EnumWord(words), [word @ Word(_)]) => words
+                .iter()
+                .find(|x| &Word(x) == word)
+                .map(|x| Argument::EnumWord(x)),
+            (ArgumentSchema::EnumWordWithList(words), [first, tail @ ..]) => {
+                // Word followed by "," is the first value of the list
+                let word = match tail.first() {
+                    Some(Punctuation(",")) => None,
+                    _ => words.iter().find(|x| &Word(x) == first),
+                };
+                let values = joined_string(if word.is_some() { tail } else { rest })
+                    .into_iter()
+                    .filter(|x| !x.is_empty())
+                    .collect::<Vec<String>>();
+                match (word, values.is_empty()) {
+                    (None, true) => None,
+                    (word, _) => Some(Argument::EnumWordWithList(word.copied(), values)),
+                }
+            }
+            (ArgumentSchema::Member, [Mention(mention)]) => {
+                Some(Argument::Member(MemberArgument::Username(&mention[1..])))
+            }
+            (ArgumentSchema::Member, [TextMention(_, user_id)]) => {
+                Some(Argument::Member(MemberArgument::UserId(*user_id)))
+            }
+            (ArgumentSchema::Duration, [Word(value)]) => {
+                let unit_start = value.find(|c: char| !c.is_ascii_digit())?;
+                let (amount, unit) = value.split_at(unit_start);
+                parse_duration(amount, unit).map(Argument::Duration)
+            }
+            (ArgumentSchema::Duration, [Number(amount), Word(unit)]) => {
+                parse_duration(amount, unit).map(Argument::Duration)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct CommandSetting<'a> {
     pub aliases: Vec<&'a str>,
+    pub en_aliases: Vec<&'a str>,
     pub description: &'a str,
-    pub split_values: bool,
+    pub argument: ArgumentSchema<'a>,
     pub available_control_items: Option<Vec<ControlItem>>,
     pub default_control_item: Option<ControlItem>,
     pub required_value: bool,
     pub get_or_set_value: bool,
     pub required_reply: bool,
+    // Chats can override the role with permissions command
+    pub required_role: Role,
 }
 
 pub static COMMAND_SETTING_MAP: Lazy<HashMap<&'static Command, CommandSetting<'static>>> =
@@ -54,15 +287,17 @@ pub static COMMAND_SETTING_MAP: Lazy<HashMap<&'static Command, CommandSetting<'s
             (
                 &Help,
                 CommandSetting {
-                    aliases: vec!["help", "хелп", "хлеп", "помощь"],
+                    aliases: vec!["хелп", "хлеп", "помощь"],
+                    en_aliases: vec!["help"],
                     description: "Получить информацию о том, \
                     как пользоваться командами и ботом в целом.",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
@@ -81,264 +316,857 @@ pub static COMMAND_SETTING_MAP: Lazy<HashMap<&'static Command, CommandSetting<'s
                         "чье",
                         "чья",
                         "чей",
-                        "who",
                     ],
+                    en_aliases: vec!["who", "whose", "with whom", "about whom"],
                     description: "Случайно выбирает пользователя группы \
                     и приписывает заданное значение к имени пользователя",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &AnswerChance,
                 CommandSetting {
                     aliases: vec!["процент", "процент срабатывания"],
+                    en_aliases: vec!["answer chance", "percent"],
                     description: "Показ и установка процента автоматического \
                     срабатывания на сущности `бред` и `подстроки`. Чтобы показать значение - \
                     достаточно вызвать команду и указать на какой из доступных объектов это показать, \
                     без целочисленных параметров. Чтобы установить значение, \
                     необходимо добавить параметр из целого числа от 0 до 100",
-                    split_values: false,
+                    argument: ArgumentSchema::Integer { min: 0, max: 100 },
                     available_control_items: Some(vec![MorphWord, Substring]),
                     default_control_item: Some(Substring),
                     required_value: true,
                     get_or_set_value: true,
                     required_reply: false,
+                    required_role: Role::Admin,
                 },
             ),
             (
                 &Show,
                 CommandSetting {
-                    aliases: vec!["покажи", "show"],
+                    aliases: vec!["покажи"],
+                    en_aliases: vec!["show"],
                     description: "Показ ключей для контента, который выбран в качестве ответа",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: Some(vec![KeyWord]),
                     default_control_item: Some(KeyWord),
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: true,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &Keys,
+                CommandSetting {
+                    aliases: vec!["ключи"],
+                    en_aliases: vec!["keys"],
+                    description: "Постраничный показ всех ключей чата. \
+                    Если указать текст, показываются ключи, которые начинаются с него, \
+                    а * в тексте заменяет любой текст. \
+                    По кнопке с номером ключа присылаются его значения",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Add,
                 CommandSetting {
-                    aliases: vec!["добавь", "add"],
+                    aliases: vec!["добавь"],
+                    en_aliases: vec!["add"],
                     description: "Добавление слова в словарь для бреда",
-                    split_values: true,
+                    argument: ArgumentSchema::List,
                     available_control_items: Some(vec![MorphWord]),
                     default_control_item: None,
                     required_value: true,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &Dictionary,
+                CommandSetting {
+                    aliases: vec!["словарь"],
+                    en_aliases: vec!["dictionary"],
+                    description: "Постраничный показ слов из словаря для бреда",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &DeleteWords,
+                CommandSetting {
+                    aliases: vec!["удали бред", "удали из словаря"],
+                    en_aliases: vec!["delete nonsense"],
+                    description: "Удаление слов из словаря для бреда",
+                    argument: ArgumentSchema::List,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &ImportWords,
+                CommandSetting {
+                    aliases: vec!["импорт бреда", "импортируй бред"],
+                    en_aliases: vec!["import nonsense"],
+                    description: "Добавление слов в словарь для бреда из текста или файла, \
+                    указанного в ответе сообщения. Слова разделяются переносом строки или \",\"",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: true,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &Export,
+                CommandSetting {
+                    aliases: vec!["экспорт"],
+                    en_aliases: vec!["export"],
+                    description: "Выгрузка контента, словаря и настроек чата в файл. \
+                    Медиа из файла может отправить только бот, который его выгрузил",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &Import,
+                CommandSetting {
+                    aliases: vec!["импорт"],
+                    en_aliases: vec!["import"],
+                    description: "Загрузка контента и словаря из файла выгрузки, \
+                    указанного в ответе сообщения. Существующий контент и настройки чата сохраняются",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: true,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &CreatePack,
+                CommandSetting {
+                    aliases: vec!["создай пак"],
+                    en_aliases: vec!["create pack"],
+                    description: "Создание пака с указанным названием из контента чата. \
+                    Другие чаты могут подписаться на пак и отвечать его контентом",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &Packs,
+                CommandSetting {
+                    aliases: vec!["паки"],
+                    en_aliases: vec!["packs"],
+                    description: "Показ доступных паков, паки с подпиской чата отмечены",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &PackAccess,
+                CommandSetting {
+                    aliases: vec!["доступ пака"],
+                    en_aliases: vec!["pack access"],
+                    description: "Показ и установка доступа к паку чата. \
+                    На приватный пак может подписать чат только автор пака. \
+                    Чтобы показать значение - достаточно вызвать команду без параметров",
+                    argument: ArgumentSchema::EnumWord(
+                        PackVisibility::words(),
+                    ),
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: true,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &PackChance,
+                CommandSetting {
+                    aliases: vec!["процент пака"],
+                    en_aliases: vec!["pack chance"],
+                    description: "Показ и установка процента срабатывания контента пака чата \
+                    в подписанных чатах. \
+                    Чтобы показать значение - достаточно вызвать команду без параметров",
+                    argument: ArgumentSchema::Integer { min: 0, max: 100 },
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: true,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &Subscribe,
+                CommandSetting {
+                    aliases: vec!["подпишись"],
+                    en_aliases: vec!["subscribe"],
+                    description: "Подписка чата на пак с указанным названием",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &Unsubscribe,
+                CommandSetting {
+                    aliases: vec!["отпишись"],
+                    en_aliases: vec!["unsubscribe"],
+                    description: "Отписка чата от пака с указанным названием",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
                 },
             ),
             (
                 &Remember,
                 CommandSetting {
-                    aliases: vec!["запомни", "remember"],
+                    aliases: vec!["запомни"],
+                    en_aliases: vec!["remember"],
                     description: "Сохранение контента из выбранного \
                     в ответе сообщения на указанные ключи",
-                    split_values: true,
-                    available_control_items: Some(vec![Trigger, Substring]),
+                    argument: ArgumentSchema::List,
+                    available_control_items: Some(vec![Trigger, Substring, Regex]),
+                    default_control_item: Some(Substring),
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: true,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &RememberForward,
+                CommandSetting {
+                    aliases: vec!["запомни пересылкой"],
+                    en_aliases: vec!["remember as forward"],
+                    description: "Сохранение выбранного в ответе сообщения на указанные ключи. \
+                    Бот отвечает пересылкой сообщения с указанием автора",
+                    argument: ArgumentSchema::List,
+                    available_control_items: Some(vec![Trigger, Substring, Regex]),
                     default_control_item: Some(Substring),
                     required_value: true,
                     get_or_set_value: false,
                     required_reply: true,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &RememberCopy,
+                CommandSetting {
+                    aliases: vec!["запомни копией"],
+                    en_aliases: vec!["remember as copy"],
+                    description: "Сохранение выбранного в ответе сообщения на указанные ключи. \
+                    Бот отвечает копией сообщения с сохранением форматирования",
+                    argument: ArgumentSchema::List,
+                    available_control_items: Some(vec![Trigger, Substring, Regex]),
+                    default_control_item: Some(Substring),
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: true,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &RememberMedia,
+                CommandSetting {
+                    aliases: vec![
+                        "запомни на стикер",
+                        "запомни на гифку",
+                        "запомни на картинку",
+                        "запомни на медиа",
+                    ],
+                    en_aliases: vec!["remember on sticker", "remember on media"],
+                    description: "Сохранение ответа на стикер, гифку или картинку. \
+                    Сначала команда вызывается в ответ на медиа, затем в ответ на сообщение \
+                    с контентом для ответа. Если указать эмодзи, то контент из выбранного \
+                    в ответе сообщения сохранится на любой стикер с этим эмодзи",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: true,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Check,
                 CommandSetting {
-                    aliases: vec!["проверь", "проверка", "check"],
+                    aliases: vec!["проверь", "проверка"],
+                    en_aliases: vec!["check"],
                     description: "Проверка на наличие контента из указанных ключей в параметрах",
-                    split_values: false,
-                    available_control_items: Some(vec![Trigger, Substring]),
+                    argument: ArgumentSchema::Text,
+                    available_control_items: Some(vec![Trigger, Substring, Regex]),
                     default_control_item: Some(Substring),
                     required_value: true,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Say,
                 CommandSetting {
-                    aliases: vec!["скажи", "say"],
+                    aliases: vec!["скажи"],
+                    en_aliases: vec!["say"],
                     description: "Повторение написанного текста \
                     (скоро будет голосовым преобразователем)",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: true,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Delete,
                 CommandSetting {
-                    aliases: vec!["удали", "delete"],
+                    aliases: vec!["удали"],
+                    en_aliases: vec!["delete"],
                     description: "Удаление контента, указанного в ответе сообщения",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: true,
+                    required_role: Role::Author,
+                },
+            ),
+            (
+                &Unlink,
+                CommandSetting {
+                    aliases: vec!["отвяжи"],
+                    en_aliases: vec!["unlink"],
+                    description: "Удаление указанных ключей у контента, указанного в ответе сообщения. \
+                    Остальные ключи контента сохраняются",
+                    argument: ArgumentSchema::List,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: true,
+                    required_role: Role::Author,
+                },
+            ),
+            (
+                &Rename,
+                CommandSetting {
+                    aliases: vec!["переименуй"],
+                    en_aliases: vec!["rename"],
+                    description: "Переименование ключа у всего его контента. \
+                    Контент, который уже есть на новом ключе, удаляется со старого ключа",
+                    argument: ArgumentSchema::Pair,
+                    available_control_items: Some(vec![Trigger, Substring, Regex]),
+                    default_control_item: Some(Substring),
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &Move,
+                CommandSetting {
+                    aliases: vec!["перенеси"],
+                    en_aliases: vec!["move"],
+                    description: "Перенос контента, указанного в ответе сообщения, \
+                    со старого ключа на новый",
+                    argument: ArgumentSchema::Pair,
+                    available_control_items: Some(vec![Trigger, Substring, Regex]),
+                    default_control_item: Some(Substring),
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: true,
+                    required_role: Role::Author,
+                },
+            ),
+            (
+                &Trash,
+                CommandSetting {
+                    aliases: vec!["корзина"],
+                    en_aliases: vec!["trash"],
+                    description: "Показ недавно удаленного контента",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &Restore,
+                CommandSetting {
+                    aliases: vec!["верни"],
+                    en_aliases: vec!["restore", "undo"],
+                    description: "Восстановление удаленного контента из выбранного \
+                    в ответе сообщения. Без ответа на сообщение восстанавливается \
+                    контент последнего удаления",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Author,
+                },
+            ),
+            (
+                &Purge,
+                CommandSetting {
+                    aliases: vec!["очисти корзину"],
+                    en_aliases: vec!["empty trash", "purge"],
+                    description: "Окончательное удаление контента из корзины, \
+                    удаленного раньше указанного срока. По-умолчанию срок - 30 дней, \
+                    контент старше этого срока удаляется и автоматически",
+                    argument: ArgumentSchema::Duration,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &History,
+                CommandSetting {
+                    aliases: vec!["история"],
+                    en_aliases: vec!["history"],
+                    description: "Показ последних изменений контента и настроек чата",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &Permission,
+                CommandSetting {
+                    aliases: vec!["права"],
+                    en_aliases: vec!["permissions"],
+                    description: "Показ и установка прав, необходимых для команды: \
+                    участник, автор контента, админ чата или владелец бота. \
+                    Чтобы показать права - достаточно указать только команду",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
                 },
             ),
             (
                 &Couple,
                 CommandSetting {
-                    aliases: vec!["парочка", "пара", "couple"],
-                    description: "Вывод пары случайных пользователей чата",
-                    split_values: false,
+                    aliases: vec!["парочка", "пара"],
+                    en_aliases: vec!["couple"],
+                    description: "Вывод пары случайных пользователей чата. \
+                    Если упомянуть участника, пара будет подобрана для него",
+                    argument: ArgumentSchema::Member,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Top,
                 CommandSetting {
-                    aliases: vec!["топ", "top"],
+                    aliases: vec!["топ"],
+                    en_aliases: vec!["top"],
                     description: "Вывод списка пользователей чата в случайном порядке",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Channel,
                 CommandSetting {
-                    aliases: vec!["канал", "channel", "all"],
+                    aliases: vec!["канал"],
+                    en_aliases: vec!["channel", "all"],
                     description: "Отметка всех активных участников чата с тегами",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &RandomChance,
                 CommandSetting {
-                    aliases: vec!["вероятность", "шанс", "chance"],
+                    aliases: vec!["вероятность", "шанс"],
+                    en_aliases: vec!["chance"],
                     description: "Случайный процент числом",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &RandomChoose,
                 CommandSetting {
-                    aliases: vec!["выбери", "выбор", "choose"],
+                    aliases: vec!["выбери", "выбор"],
+                    en_aliases: vec!["choose"],
                     description: "Выбор одного из нескольких вариантов",
-                    split_values: true,
+                    argument: ArgumentSchema::List,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: true,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &GenerateNonsense,
                 CommandSetting {
-                    aliases: vec!["бред", "давай", "nonsense"],
+                    aliases: vec!["бред", "давай"],
+                    en_aliases: vec!["nonsense"],
                     description: "Искажение сообщения с заменой слов \
                     из оригинала на слова сохраненные в словаре",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: true,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Morph,
                 CommandSetting {
-                    aliases: vec!["морф", "морфируй", "morph"],
+                    aliases: vec!["морф", "морфируй"],
+                    en_aliases: vec!["morph"],
                     description: "Склонение указанного слова",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: true,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &MorphDebug,
                 CommandSetting {
-                    aliases: vec!["морф дебаг", "морфируй дебаг", "morph debug"],
+                    aliases: vec!["морф дебаг", "морфируй дебаг"],
+                    en_aliases: vec!["morph debug"],
                     description: "Склонение указанного слова с выводом деталей",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: true,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Quote,
                 CommandSetting {
-                    aliases: vec!["цит", "цитата", "quote"],
+                    aliases: vec!["цит", "цитата"],
+                    en_aliases: vec!["quote"],
                     description: "Получить мудрую цитату",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Joke,
                 CommandSetting {
-                    aliases: vec!["анекдот", "анек", "joke"],
+                    aliases: vec!["анекдот", "анек"],
+                    en_aliases: vec!["joke"],
                     description: "Получить анекдот",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Member,
                 },
             ),
             (
                 &Advice,
                 CommandSetting {
-                    aliases: vec!["совет", "advice"],
+                    aliases: vec!["совет"],
+                    en_aliases: vec!["advice"],
                     description: "Получить совет",
-                    split_values: false,
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &ChatLanguage,
+                CommandSetting {
+                    aliases: vec!["язык"],
+                    en_aliases: vec!["language", "lang"],
+                    description: "Показ и установка языка ответов бота в чате. \
+                    Чтобы показать значение - достаточно вызвать команду без параметров",
+                    argument: ArgumentSchema::EnumWord(
+                        Language::iter().map(|x| x.code()).collect(),
+                    ),
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: true,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &TriggerSimilarity,
+                CommandSetting {
+                    aliases: vec!["похожесть", "опечатки"],
+                    en_aliases: vec!["similarity", "typos"],
+                    description: "Показ и установка порога похожести триггеров в процентах. \
+                    Если порог меньше 100, то триггер сработает и на сообщение с опечатками, \
+                    например \"привет всм\" на триггер \"привет всем\". \
+                    Чтобы показать значение - достаточно вызвать команду без параметров",
+                    argument: ArgumentSchema::Integer { min: 1, max: 100 },
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: true,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &Welcome,
+                CommandSetting {
+                    aliases: vec!["приветствие"],
+                    en_aliases: vec!["welcome"],
+                    description: "Сохранение контента из выбранного в ответе сообщения \
+                    как приветствия новых участников чата. \
+                    В тексте приветствия {author} заменяется на имя нового участника",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: false,
+                    get_or_set_value: false,
+                    required_reply: true,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &DeleteWelcome,
+                CommandSetting {
+                    aliases: vec!["удали приветствие"],
+                    en_aliases: vec!["delete welcome"],
+                    description: "Удаление приветствия новых участников чата",
+                    argument: ArgumentSchema::Text,
                     available_control_items: None,
                     default_control_item: None,
                     required_value: false,
                     get_or_set_value: false,
                     required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &WelcomeDelay,
+                CommandSetting {
+                    aliases: vec!["удаление приветствия"],
+                    en_aliases: vec!["welcome deletion"],
+                    description: "Показ и установка задержки в секундах, \
+                    через которую приветствие удаляется. При 0 приветствие не удаляется. \
+                    Чтобы показать значение - достаточно вызвать команду без параметров",
+                    argument: ArgumentSchema::Integer { min: 0, max: 3600 },
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: true,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &WelcomeBots,
+                CommandSetting {
+                    aliases: vec!["приветствие ботов"],
+                    en_aliases: vec!["welcome bots"],
+                    description: "Показ и установка того, приветствуются ли добавленные боты. \
+                    Чтобы показать значение - достаточно вызвать команду без параметров",
+                    argument: ArgumentSchema::EnumWord(
+                        BotWelcome::words(),
+                    ),
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: true,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &Template,
+                CommandSetting {
+                    aliases: vec!["шаблон"],
+                    en_aliases: vec!["template"],
+                    description: "Показ того, как будет выглядеть текстовый ответ по шаблону. \
+                    В шаблоне можно использовать подстановки: {author} - автор сообщения, \
+                    {random_member} - случайный участник чата, {date} - дата, {time} - время, \
+                    {text} - текст сообщения, {rand:1-100} - случайное число из диапазона. \
+                    Фигурные скобки без подстановки пишутся как {{ и }}",
+                    argument: ArgumentSchema::Text,
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Member,
+                },
+            ),
+            (
+                &Weight,
+                CommandSetting {
+                    aliases: vec!["вес"],
+                    en_aliases: vec!["weight"],
+                    description: "Показ и установка веса контента из выбранного в ответе сообщения. \
+                    Чем больше вес, тем чаще контент выбирается среди вариантов ответа на ключ. \
+                    Чтобы показать значение - достаточно вызвать команду без параметров",
+                    argument: ArgumentSchema::Integer { min: 1, max: 100 },
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: true,
+                    required_reply: true,
+                    required_role: Role::Author,
+                },
+            ),
+            (
+                &Selection,
+                CommandSetting {
+                    aliases: vec!["режим выбора"],
+                    en_aliases: vec!["selection"],
+                    description: "Показ и установка режима выбора вариантов ответа на ключи: \
+                    случайно с учетом веса, по циклу или историей, которая продвигается \
+                    на каждое срабатывание и останавливается на последнем варианте. \
+                    Чтобы показать значение - достаточно указать только ключи",
+                    argument: ArgumentSchema::EnumWordWithList(
+                        SelectionMode::words(),
+                    ),
+                    available_control_items: Some(vec![Trigger, Substring, Regex]),
+                    default_control_item: Some(Substring),
+                    required_value: true,
+                    get_or_set_value: false,
+                    required_reply: false,
+                    required_role: Role::Admin,
+                },
+            ),
+            (
+                &WordForms,
+                CommandSetting {
+                    aliases: vec!["словоформы"],
+                    en_aliases: vec!["word forms", "stemming"],
+                    description: "Показ и установка учета словоформ в ключах. \
+                    Если учет включен, то ключ \"пиво\" сработает и на \"пива\", и на \"пивом\". \
+                    Чтобы показать значение - достаточно вызвать команду без параметров",
+                    argument: ArgumentSchema::EnumWord(
+                        KeyNormalization::words(),
+                    ),
+                    available_control_items: None,
+                    default_control_item: None,
+                    required_value: true,
+                    get_or_set_value: true,
+                    required_reply: false,
+                    required_role: Role::Admin,
                 },
             ),
         ]);
         assert!(Command::iter().all(|key| {
             let command_setting = command_settings.get(&key).unwrap();
             match command_setting {
-                x if x.aliases.is_empty() => false,
+                x if x.aliases.is_empty() || x.en_aliases.is_empty() => false,
                 CommandSetting {
                     available_control_items: Some(x),
                     ..
@@ -367,27 +1195,75 @@ pub enum ControlItem {
     Trigger,
     MorphWord,
     KeyWord,
+    Regex,
 }
 
 impl ControlItem {
     fn try_from_token<'a>(token: &'a Token<'a>) -> Option<&'a Self> {
         match token {
-            x if [Word("триггер"), Word("триггеры")].contains(x) => Some(&Trigger),
-            x if [Word("подстроку"), Word("подстроки"), Word("подстрок")].contains(x) => {
+            x if [
+                Word("триггер"),
+                Word("триггеры"),
+                Word("trigger"),
+                Word("triggers"),
+            ]
+            .contains(x) =>
+            {
+                Some(&Trigger)
+            }
+            x if [
+                Word("подстроку"),
+                Word("подстроки"),
+                Word("подстрок"),
+                Word("substring"),
+                Word("substrings"),
+            ]
+            .contains(x) =>
+            {
                 Some(&Substring)
             }
-            x if [Word("бред"), Word("бреда")].contains(x) => Some(&MorphWord),
-            x if [Word("ключ"), Word("ключи")].contains(x) => Some(&KeyWord),
+            x if [Word("бред"), Word("бреда"), Word("nonsense")].contains(x) => {
+                Some(&MorphWord)
+            }
+            x if [Word("ключ"), Word("ключи"), Word("key"), Word("keys")].contains(x) => {
+                Some(&KeyWord)
+            }
+            x if [
+                Word("регулярку"),
+                Word("регулярки"),
+                Word("регулярка"),
+                Word("regex"),
+                Word("regexes"),
+            ]
+            .contains(x) =>
+            {
+                Some(&Regex)
+            }
             _ => None,
         }
     }
 
-    pub fn name(&self) -> String {
-        match self {
-            Substring => String::from("подстроки"),
-            Trigger => String::from("триггеры"),
-            MorphWord => String::from("бред"),
-            KeyWord => String::from("ключи"),
+    pub fn name(&self, language: &Language) -> String {
+        match (self, language) {
+            (Substring, Language::Ru) => String::from("подстроки"),
+            (Substring, Language::En) => String::from("substrings"),
+            (Trigger, Language::Ru) => String::from("триггеры"),
+            (Trigger, Language::En) => String::from("triggers"),
+            (MorphWord, Language::Ru) => String::from("бред"),
+            (MorphWord, Language::En) => String::from("nonsense"),
+            (KeyWord, Language::Ru) => String::from("ключи"),
+            (KeyWord, Language::En) => String::from("keys"),
+            (Regex, Language::Ru) => String::from("регулярки"),
+            (Regex, Language::En) => String::from("regexes"),
+        }
+    }
+}
+
+impl<'a> CommandSetting<'a> {
+    pub fn localized_aliases(&self, language: &Language) -> &Vec<&'a str> {
+        match language {
+            Language::Ru => &self.aliases,
+            Language::En => &self.en_aliases,
         }
     }
 }
@@ -401,6 +1277,7 @@ fn command_keywords<'a>() -> &'static Vec<(&'a Command, Vec<Token<'a>>)> {
                 setting
                     .aliases
                     .iter()
+                    .chain(setting.en_aliases.iter())
                     .map(|keyword| (*command, tokenize(keyword)))
             })
             .collect();
@@ -411,21 +1288,43 @@ fn command_keywords<'a>() -> &'static Vec<(&'a Command, Vec<Token<'a>>)> {
 }
 
 pub fn is_bot_call(token: &Token) -> bool {
+    // Bot command in groups can be addressed to bot: /bread@bot_name
+    if let BotCommand(command) = token {
+        return command
+            .split('@')
+            .next()
+            .is_some_and(|x| x.eq_ignore_ascii_case("/bread"));
+    }
     [
         Word("хлеб"),
         Word("хлебушек"),
         Word("bread"),
         Word("bread_bot"),
+        Mention("@bread_bot"),
     ]
     .contains(token)
 }
 
+pub static ARGUMENT_FEEDBACK_MAP: Lazy<HashMap<(Command, Language), String>> = Lazy::new(|| {
+    COMMAND_SETTING_MAP
+        .iter()
+        .flat_map(|(&command, command_setting)| {
+            Language::iter().map(move |language| {
+                (
+                    (*command, language),
+                    command_setting.argument.feedback(&language),
+                )
+            })
+        })
+        .collect()
+});
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct CommandContainer<'a> {
     pub command: &'a Command,
     pub command_aliases: &'a [Token<'a>],
     pub control_item: Option<&'a ControlItem>,
-    pub split_values: bool,
+    pub argument: Argument<'a>,
     pub rest: &'a [Token<'a>],
 }
 
@@ -460,13 +1359,13 @@ fn find_bot_call_command<'a>(
 fn negative_control_item_settings<'a>(
     settings: &'a CommandSetting,
     rest: &'a [Token<'a>],
-) -> Result<(Option<&'a ControlItem>, &'a [Token<'a>]), ProcessError<'a>> {
+) -> Result<(Option<&'a ControlItem>, &'a [Token<'a>]), ProcessError> {
     match (
         &settings.available_control_items,
         &settings.default_control_item,
     ) {
         (Some(_), None) => Err(ProcessError::Feedback {
-            message: "Необходимо указать объект для редактирования",
+            message: Phrase::NeedControlItem,
         }),
         (Some(_), Some(dci)) => Ok((Some(dci), rest)),
         _ => Ok((None, rest)),
@@ -477,7 +1376,7 @@ fn positive_control_item_settings<'a>(
     settings: &'a CommandSetting,
     control_item: &'a ControlItem,
     rest: &'a [Token<'a>],
-) -> Result<(Option<&'a ControlItem>, &'a [Token<'a>]), ProcessError<'a>> {
+) -> Result<(Option<&'a ControlItem>, &'a [Token<'a>]), ProcessError> {
     match (
         &settings.available_control_items,
         &settings.default_control_item,
@@ -494,7 +1393,7 @@ fn positive_control_item_settings<'a>(
             if !available_control_items.contains(control_item) =>
         {
             Err(ProcessError::Feedback {
-                message: "Указан недопустимый объект для редактирования",
+                message: Phrase::InvalidControlItem,
             })
         }
         (None, None) => Ok((None, rest)),
@@ -505,13 +1404,13 @@ fn positive_control_item_settings<'a>(
 pub fn parse_command<'a>(
     tokens: &'a [Token<'_>],
     has_reply: bool,
-) -> Result<CommandContainer<'a>, ProcessError<'a>> {
+) -> Result<CommandContainer<'a>, ProcessError> {
     if let Some(validated_command) =
         find_bot_call_command(tokens).map(|(command, command_aliases, rest_after_command)| {
             let settings = COMMAND_SETTING_MAP.get(command).unwrap();
             if settings.required_reply && !has_reply {
                 return Err(ProcessError::Feedback {
-                    message: "Необходимо выбрать сообщение в ответ",
+                    message: Phrase::NeedReply,
                 });
             }
             match rest_after_command {
@@ -526,16 +1425,21 @@ pub fn parse_command<'a>(
             .and_then(|(control_item, rest)| match rest {
                 [] if settings.required_value && !settings.get_or_set_value => {
                     Err(ProcessError::Feedback {
-                        message: "Необходимо указать значения",
+                        message: Phrase::NeedValues,
                     })
                 }
-                _ => Ok(CommandContainer {
-                    command,
-                    command_aliases,
-                    control_item,
-                    split_values: settings.split_values,
-                    rest,
-                }),
+                _ => match settings.argument.parse(rest) {
+                    None => Err(ProcessError::Feedback {
+                        message: Phrase::InvalidArgument(*command),
+                    }),
+                    Some(argument) => Ok(CommandContainer {
+                        command,
+                        command_aliases,
+                        control_item,
+                        argument,
+                        rest,
+                    }),
+                },
             })
         })
     {
@@ -1,32 +1,91 @@
 use std::collections::HashMap;
+use std::sync::{RwLock, RwLockWriteGuard};
+use std::time::{Duration, Instant};
 
+use chrono::Utc;
+use log::warn;
 use once_cell::sync::Lazy;
-use rand::Rng;
 use sqlx::PgPool;
+use strum::IntoEnumIterator;
 use unicase::UniCase;
 
-use crate::common::answer_entity_service::{all_keys, substrings, triggers};
+use crate::common::album_service::album_content;
+use crate::common::answer_entity_service::{
+    all_keys, invalidate_indexes, regexes, select_answer_entity, substrings, triggers,
+};
+use crate::common::archive_service::{
+    export_archive, import_archive, ChatArchive, ARCHIVE_VERSION,
+};
+use crate::common::chat_cache::{cache_key, CacheKey};
 use crate::common::command_parser::{
-    find_command, parse_command, Command, CommandContainer, CommandSetting, ControlItem,
+    find_command, parse_command, Argument, Command, CommandContainer, ControlItem,
     COMMAND_SETTING_MAP,
 };
 use crate::common::db::{
-    AnswerEntity, ChatId, ChatToMemberId, DictionaryEntity, EntityContentType, EntityReactionType,
-    MemberId,
+    AnswerEntity, AnswerEntityId, AnswerKeySelection, AnswerPack, AuditAction, AuditLogEntry,
+    BotWelcome, Chat as ChatDB, ChatId, ChatToMemberId, ChatWelcome, CommandPermission,
+    DictionaryEntity, EntityContentType, EntityReactionType, KeyNormalization, Language,
+    LocalizedWord, Member, MemberId, PackVisibility, Role, SelectionMode,
 };
 use crate::common::error::ProcessError;
-use crate::common::lexer::{joined_string, tokens_to_string, Token};
+use crate::common::lexer::{normalize_text, source_text, tokenize, tokens_to_string, Token};
+use crate::common::locale::{command_description, translate, Phrase};
+use crate::common::permission_service::{
+    check_permission, command_name, member_role, required_role,
+};
+use crate::common::regex_index::compile_pattern;
 use crate::common::request::{Message, MessageBody, MessageExt};
 use crate::common::response::{
-    roll_reply_markup, text_message, text_message_with_roll, BaseBody, ResponseMessage,
+    roll_reply_markup, text_message, text_message_with_keyboard, text_message_with_roll, BaseBody,
+    ReplyMarkupButtonResponse, ReplyMarkupResponse, ResponseMessage,
 };
+use crate::common::telegram_client::download_text_file;
+use crate::common::template::{parse_template, render_template};
 use crate::common::user_service::{
-    morph_answer_chance, pretty_username, random_user_from_chat, set_morph_answer_chance,
-    set_substring_answer_chance, substring_answer_chance,
+    chat_language, key_normalization, member_from_argument, morph_answer_chance, pretty_username,
+    random_user_from_chat, random_user_from_chat_except, set_chat_language, set_key_normalization,
+    set_morph_answer_chance, set_substring_answer_chance, set_trigger_similarity,
+    substring_answer_chance, trigger_similarity,
 };
 
-static HELP_MAIN: Lazy<String> = Lazy::new(|| {
-    String::from("Привет. Я бот и меня зовут Хлебушек.\n\
+// Deleted content is kept in the trash for this time
+const TRASH_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const TRASH_LIST_LIMIT: i64 = 20;
+const HISTORY_LIST_LIMIT: i64 = 20;
+const KEYS_PAGE_SIZE: i64 = 10;
+const KEYS_ROW_SIZE: usize = 5;
+// Callback data of keys list buttons: "keys:<page>" and "key:<group id>"
+pub const KEYS_PAGE_CALLBACK: &str = "keys:";
+pub const KEY_VALUES_CALLBACK: &str = "key:";
+const DICTIONARY_PAGE_SIZE: i64 = 20;
+// Callback data of dictionary list buttons: "dictionary:<page>"
+pub const DICTIONARY_PAGE_CALLBACK: &str = "dictionary:";
+const DICTIONARY_IMPORT_MAX_SIZE: u64 = 1024 * 1024;
+const ARCHIVE_MAX_SIZE: u64 = 10 * 1024 * 1024;
+// Media chosen by the first call of the command waits for the answer content this time
+const PENDING_MEDIA_KEY_TTL: Duration = Duration::from_secs(10 * 60);
+
+struct PendingMediaKey {
+    created_at: Instant,
+    key: String,
+}
+
+type PendingMediaKeys = HashMap<(CacheKey, MemberId), PendingMediaKey>;
+
+static PENDING_MEDIA_KEYS: Lazy<RwLock<PendingMediaKeys>> = Lazy::new(Default::default);
+
+// Expired keys are dropped on every access, so keys of abandoned commands don't stay in memory
+fn pending_media_keys() -> RwLockWriteGuard<'static, PendingMediaKeys> {
+    let mut pending_media_keys = PENDING_MEDIA_KEYS.write().unwrap();
+    pending_media_keys.retain(|_, x| x.created_at.elapsed() < PENDING_MEDIA_KEY_TTL);
+    pending_media_keys
+}
+
+static HELP_MAIN: Lazy<HashMap<Language, String>> = Lazy::new(|| {
+    HashMap::from([
+        (
+            Language::Ru,
+            String::from("Привет. Я бот и меня зовут Хлебушек.\n\
     Я создан для того, чтобы делать ваши групповые чаты чуточку веселее. \
     Распознаю команды и рандомно и весело отвечаю на сообщения. \
     Достаточно добавить меня в группу и дать доступ на чтение сообщений. \
@@ -40,12 +99,37 @@ static HELP_MAIN: Lazy<String> = Lazy::new(|| {
     - Посмотреть детальную информацию о команде: 'хлеб хелп [команда]'\n\n\
     \t- значение для [команда] можно найти из списка\n\n\
     - Посмотреть информацию о механике и терминологии бота: 'хлеб хелп механика'\n\n\
-    Продвигать проект и оставлять пожелания можно на boosty: https://boosty.to/levkey/donate")
+    - Сменить язык ответов: 'хлеб язык [ru|en]'\n\n\
+    Продвигать проект и оставлять пожелания можно на boosty: https://boosty.to/levkey/donate"),
+        ),
+        (
+            Language::En,
+            String::from("Hi. I am a bot and my name is Bread.\n\
+    I was made to make your group chats a little more fun. \
+    I recognize commands and answer messages randomly and funny. \
+    Just add me to a group and give me access to read messages. \
+    To call me, follow the rules:\n\n\
+    - Call structure: 'bread [command] [object to edit] [value]'\n\
+    \t> [command] - required parameter\n\
+    \t> [object to edit] - optional parameter. Needed if the command requires it.\n\
+    \t> [value] - optional parameter. Needed if the command requires it.\n\
+    \t> Important: some commands require a value as a replied message.\n\n\
+    - See the list of all commands: 'bread help commands'.\n\n\
+    - See the details of a command: 'bread help [command]'\n\n\
+    \t- the value for [command] can be found in the list\n\n\
+    - See the information about the bot mechanics and terminology: 'bread help mechanics'\n\n\
+    - Change the language of replies: 'bread language [ru|en]'\n\n\
+    You can support the project and leave wishes on boosty: https://boosty.to/levkey/donate"),
+        ),
+    ])
 });
 
-static HELP_INSTRUCTIONS: Lazy<String> = Lazy::new(|| {
-    String::from(
-        "Основные элементы:\n\nЗначение \
+static HELP_INSTRUCTIONS: Lazy<HashMap<Language, String>> = Lazy::new(|| {
+    HashMap::from([
+        (
+            Language::Ru,
+            String::from(
+                "Основные элементы:\n\nЗначение \
     - это текст, картинка, видео, гифка, стикер или голосовое сообщение, \
     которое можно у меня сохранить и которые я буду подкидывать в момент, \
     когда ты меньше всего этого ожидаешь :)\n\n\
@@ -55,6 +139,8 @@ static HELP_INSTRUCTIONS: Lazy<String> = Lazy::new(|| {
     Подстрока - это часть строки, которое есть в тексте сообщения. \
     Я сработаю с определенным `процентом срабатывания` \
     и пришлю тебе сохраненное на эту часть строки `значение`\n\n\
+    Регулярка - это регулярное выражение, которое я ищу в тексте сообщения. \
+    В текстовом `значении` можно подставить найденные группы через $1, $2 и т.д.\n\n\
     Бред - это должно быть очень смешное слово, на которое \
     я по команде или с определенным `процентом срабатывания` меняю изначальные слова \
     у сообщения в случайной последовательности.\n\n\
@@ -65,99 +151,171 @@ static HELP_INSTRUCTIONS: Lazy<String> = Lazy::new(|| {
     по которым можно её вызвать. Это придумано для удобства\n\n\
     Объект редактирования - это обобщенное название `ключу`, `триггеру`, `бреду` и `подстроке`. \
     В некоторых командах их стоит перечислить для операций добавления или удаления",
-    )
+            ),
+        ),
+        (
+            Language::En,
+            String::from(
+                "Main elements:\n\nValue \
+    - is a text, picture, video, gif, sticker or voice message, \
+    which can be saved and which I will throw in at the moment \
+    when you least expect it :)\n\n\
+    Key - is a string to which a value is attached\n\n\
+    Trigger - is a whole string in the message text, to which I will react for 100% \
+    and send you the `value` saved on this string\n\n\
+    Substring - is a part of a string that is in the message text. \
+    I will react with a certain `answer chance` \
+    and send you the `value` saved on this part of the string\n\n\
+    Regex - is a regular expression, which I search for in the message text. \
+    Groups found by it can be substituted into a text `value` as $1, $2 and so on.\n\n\
+    Nonsense - is a very funny word, which \
+    I use on command or with a certain `answer chance` to replace the original words \
+    of the message in random order.\n\n\
+    Answer chance - is the percentage that sets how often \
+    I react automatically to `substrings` or `nonsense`. \
+    Different percentages can be set for `nonsense` and `substrings`. By default it is 15%\n\n\
+    Alias - is a pseudonym of a command. One command can have several aliases \
+    to call it. It is made for convenience\n\n\
+    Object to edit - is a general name for `key`, `trigger`, `nonsense` and `substring`. \
+    Some commands need them to be listed for adding or deleting operations",
+            ),
+        ),
+    ])
 });
 
-fn command_details_help(command_setting: &CommandSetting) -> String {
-    String::new()
-        + &format!(
+fn yes_or_no(value: bool, language: &Language) -> &'static str {
+    match (value, language) {
+        (true, Language::Ru) => "Да",
+        (false, Language::Ru) => "Нет",
+        (true, Language::En) => "Yes",
+        (false, Language::En) => "No",
+    }
+}
+
+fn command_details_help(command: &Command, language: &Language) -> String {
+    let command_setting = COMMAND_SETTING_MAP.get(command).unwrap();
+    let aliases = command_setting.localized_aliases(language);
+    let available_control_items = match &command_setting.available_control_items {
+        None => String::from("-"),
+        Some(_control_items) => {
+            let control_items_info = _control_items
+                .iter()
+                .map(|x| x.name(language))
+                .collect::<Vec<String>>()
+                .join(", ");
+            match (&command_setting.default_control_item, language) {
+                (Some(default_control_item), Language::Ru) => {
+                    control_items_info
+                        + ". Объект по-умолчанию: "
+                        + &default_control_item.name(language)
+                }
+                (Some(default_control_item), Language::En) => {
+                    control_items_info + ". Default object: " + &default_control_item.name(language)
+                }
+                (None, Language::Ru) => control_items_info + ". Указание объекта обязательно",
+                (None, Language::En) => control_items_info + ". The object is required",
+            }
+        }
+    };
+    let required_value = yes_or_no(command_setting.required_value, language);
+    let required_reply = yes_or_no(command_setting.required_reply, language);
+    let command_name = aliases[0];
+    let description = command_description(command, language);
+    let aliases = aliases.join("\", \"");
+    let argument = command_setting.argument.help(language);
+    let required_role = command_setting.required_role.word(language);
+    match language {
+        Language::Ru => format!(
             "Команда: \"{command_name}\". {description}\n\n\
     - Алиасы: [\"{aliases}\"]\n\
     - Объекты редактирования: {available_control_items}\n\
     - Значения обязательны: {required_value}\n\
     - Значение в виде ответа на сообщение: {required_reply}\n\
-    - Несколько значений через \",\" и \"или\": {split_values}",
-            command_name = command_setting.aliases[0],
-            description = command_setting.description,
-            aliases = command_setting.aliases.join("\", \""),
-            available_control_items = match &command_setting.available_control_items {
-                None => {
-                    String::from("-")
-                }
-                Some(_control_items) => {
-                    let control_items_info = _control_items
-                        .iter()
-                        .map(|x| x.name())
-                        .collect::<Vec<String>>()
-                        .join(", ");
-                    if let Some(default_control_item) = &command_setting.default_control_item {
-                        control_items_info
-                            + &(String::from(". Объект по-умолчанию: ")
-                                + &default_control_item.name())
-                    } else {
-                        control_items_info + ". Указание объекта обязательно"
-                    }
-                }
-            },
-            required_value = if command_setting.required_value {
-                "Да"
-            } else {
-                "Нет"
-            },
-            required_reply = if command_setting.required_reply {
-                "Да"
-            } else {
-                "Нет"
-            },
-            split_values = if command_setting.split_values {
-                "Да"
-            } else {
-                "Нет"
-            },
-        )
+    - Формат значения: {argument}\n\
+    - Права по-умолчанию: {required_role}"
+        ),
+        Language::En => format!(
+            "Command: \"{command_name}\". {description}\n\n\
+    - Aliases: [\"{aliases}\"]\n\
+    - Objects to edit: {available_control_items}\n\
+    - Values are required: {required_value}\n\
+    - Value as a replied message: {required_reply}\n\
+    - Value format: {argument}\n\
+    - Default permissions: {required_role}"
+        ),
+    }
 }
 
-static COMMAND_HELP_MAP: Lazy<HashMap<&'static Command, String>> = Lazy::new(|| {
+static COMMAND_HELP_MAP: Lazy<HashMap<(&'static Command, Language), String>> = Lazy::new(|| {
     let mut result = HashMap::new();
-    COMMAND_SETTING_MAP
-        .iter()
-        .for_each(|(&command, command_setting)| {
-            result.insert(command, command_details_help(command_setting));
+    COMMAND_SETTING_MAP.iter().for_each(|(&command, _)| {
+        Language::iter().for_each(|language| {
+            result.insert(
+                (command, language),
+                command_details_help(command, &language),
+            );
         });
+    });
     result
 });
 
-static COMMANDS_HELP_LIST: Lazy<String> = Lazy::new(|| {
-    COMMAND_SETTING_MAP.iter().enumerate().fold(
-        String::new(),
-        |mut output, (index, (_, command_setting))| {
-            output += &format!(
-                "{number}) {command_name}: {description}\n",
-                number = index + 1,
-                command_name = command_setting.aliases[0],
-                description = command_setting.description,
+static COMMANDS_HELP_LIST: Lazy<HashMap<Language, String>> = Lazy::new(|| {
+    Language::iter()
+        .map(|language| {
+            let commands_list = COMMAND_SETTING_MAP.iter().enumerate().fold(
+                String::new(),
+                |mut output, (index, (command, command_setting))| {
+                    output += &format!(
+                        "{number}) {command_name}: {description}\n",
+                        number = index + 1,
+                        command_name = command_setting.localized_aliases(&language)[0],
+                        description = command_description(command, &language),
+                    );
+                    output
+                },
             );
-            output
-        },
-    ) + "\nЧтобы узнать подробнее о нужной команде необходимо написать: \"хлеб help [команда]\""
+            (
+                language,
+                commands_list
+                    + match language {
+                        Language::Ru => {
+                            "\nЧтобы узнать подробнее о нужной команде \
+                            необходимо написать: \"хлеб хелп [команда]\""
+                        }
+                        Language::En => {
+                            "\nTo learn more about a command \
+                            write: \"bread help [command]\""
+                        }
+                    },
+            )
+        })
+        .collect()
 });
 
 fn help<'a>(
     command_container: &CommandContainer<'a>,
+    language: &Language,
     chat_id: i64,
     message_id: i64,
-) -> Result<ResponseMessage, ProcessError<'a>> {
+) -> Result<ResponseMessage, ProcessError> {
     Ok(text_message(
         match command_container.rest {
-            [argument, ..] if argument == &Token::Word("механика") => {
-                HELP_INSTRUCTIONS.to_owned()
+            [argument, ..]
+                if [Token::Word("механика"), Token::Word("mechanics")].contains(argument) =>
+            {
+                HELP_INSTRUCTIONS.get(language).unwrap().to_owned()
             }
-            [argument, ..] if argument == &Token::Word("команды") => {
-                COMMANDS_HELP_LIST.to_owned()
+            [argument, ..]
+                if [Token::Word("команды"), Token::Word("commands")].contains(argument) =>
+            {
+                COMMANDS_HELP_LIST.get(language).unwrap().to_owned()
             }
             tokens => match find_command(tokens) {
-                None => HELP_MAIN.to_owned(),
-                Some((command, _, _)) => COMMAND_HELP_MAP.get(command).unwrap().to_owned(),
+                None => HELP_MAIN.get(language).unwrap().to_owned(),
+                Some((command, _, _)) => COMMAND_HELP_MAP
+                    .get(&(command, *language))
+                    .unwrap()
+                    .to_owned(),
             },
         },
         chat_id,
@@ -180,7 +338,10 @@ static WHO_FORMS: Lazy<HashMap<UniCase<&'static str>, &str>> = Lazy::new(|| {
         (UniCase::new("чье"), "его(её):"),
         (UniCase::new("чья"), "его(её):"),
         (UniCase::new("чей"), "его(её):"),
-        (UniCase::new("who"), "его(её):"),
+        (UniCase::new("who"), ""),
+        (UniCase::new("whose"), "belongs to:"),
+        (UniCase::new("with whom"), "with:"),
+        (UniCase::new("about whom"), "about:"),
     ])
 });
 
@@ -190,7 +351,7 @@ async fn who<'a>(
     chat_db_id: &ChatId,
     chat_id: i64,
     message_id: i64,
-) -> Result<ResponseMessage, ProcessError<'a>> {
+) -> Result<ResponseMessage, ProcessError> {
     Ok(text_message_with_roll(
         match (
             tokens_to_string(command_container.rest, true),
@@ -222,300 +383,2110 @@ async fn who<'a>(
     ))
 }
 
+async fn couple<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let first_member = match &command_container.argument {
+        Argument::Member(member_argument) => {
+            member_from_argument(pool, chat_db_id, member_argument).await?
+        }
+        _ => random_user_from_chat(pool, chat_db_id).await?,
+    };
+    let second_member =
+        random_user_from_chat_except(pool, chat_db_id, std::slice::from_ref(&first_member.id))
+            .await?;
+    Ok(text_message_with_roll(
+        format!(
+            "{} ❤️ {}",
+            pretty_username(&first_member),
+            pretty_username(&second_member)
+        ),
+        chat_id,
+        message_id,
+    ))
+}
+
 async fn answer_chance<'a>(
     pool: &PgPool,
     command_container: &CommandContainer<'a>,
+    language: &Language,
     chat_db_id: &ChatId,
     chat_id: i64,
     message_id: i64,
-) -> Result<ResponseMessage, ProcessError<'a>> {
+) -> Result<ResponseMessage, ProcessError> {
     let control_item = command_container.control_item.unwrap();
-    match command_container.rest {
-        [] => match control_item {
+    match command_container.argument {
+        Argument::Empty => match control_item {
             ControlItem::Substring => substring_answer_chance(pool, chat_db_id).await,
             ControlItem::MorphWord => morph_answer_chance(pool, chat_db_id).await,
             _ => Err(ProcessError::Feedback {
-                message: "Объект редактирования не поддерживается",
+                message: Phrase::UnsupportedControlItem,
             }),
         }
         .map(|x| Ok(text_message(x.to_string(), chat_id, message_id)))?,
-        [Token::Word(_answer_chance)] => match _answer_chance.parse::<i16>() {
-            Ok(x) if (0..=100).contains(&x) => match control_item {
-                ControlItem::Substring => set_substring_answer_chance(pool, chat_db_id, x).await,
-                ControlItem::MorphWord => set_morph_answer_chance(pool, chat_db_id, x).await,
-                _ => Err(ProcessError::Feedback {
-                    message: "Объект редактирования не поддерживается",
-                }),
-            }
-            .map(|_| Ok(text_message("Сделано".to_string(), chat_id, message_id)))?,
+        Argument::Integer(x) => match control_item {
+            ControlItem::Substring => set_substring_answer_chance(pool, chat_db_id, x as i16).await,
+            ControlItem::MorphWord => set_morph_answer_chance(pool, chat_db_id, x as i16).await,
             _ => Err(ProcessError::Feedback {
-                message: "Указано неверное значение. Должно быть целое число от 0 до 100",
+                message: Phrase::UnsupportedControlItem,
+            }),
+        }
+        .map(|_| {
+            Ok(text_message(
+                translate(language, Phrase::Done),
+                chat_id,
+                message_id,
+            ))
+        })?,
+        _ => Err(ProcessError::Feedback {
+            message: Phrase::InvalidValue,
+        }),
+    }
+}
+
+async fn chat_language_command<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    match command_container.argument {
+        Argument::Empty => Ok(text_message(
+            format!(
+                "{}: {}",
+                translate(language, Phrase::ChatLanguage),
+                language.code()
+            ),
+            chat_id,
+            message_id,
+        )),
+        Argument::EnumWord(code) => match Language::from_code(code) {
+            Some(new_language) => set_chat_language(pool, chat_db_id, &new_language)
+                .await
+                .map(|_| text_message(translate(&new_language, Phrase::Done), chat_id, message_id)),
+            None => Err(ProcessError::Feedback {
+                message: Phrase::InvalidValue,
             }),
         },
         _ => Err(ProcessError::Feedback {
-            message: "Указано неверное значение. Должно быть целое число от 0 до 100",
+            message: Phrase::InvalidValue,
         }),
     }
 }
 
-async fn check<'a>(
+async fn trigger_similarity_command<'a>(
     pool: &PgPool,
     command_container: &CommandContainer<'a>,
+    language: &Language,
     chat_db_id: &ChatId,
     chat_id: i64,
     message_id: i64,
-) -> Result<ResponseMessage, ProcessError<'a>> {
-    let answer_entities = match command_container.control_item {
-        Some(ControlItem::Trigger) => triggers(pool, command_container.rest, chat_db_id).await,
-        Some(ControlItem::Substring) => substrings(pool, command_container.rest, chat_db_id).await,
-        _ => {
-            return Err(ProcessError::Feedback {
-                message: "Объект редактирования не поддерживается",
-            })
-        }
-    };
-    if answer_entities.is_empty() {
-        Err(ProcessError::Feedback {
-            message: "Ничего не было найдено",
-        })
-    } else {
-        let random_entity =
-            answer_entities[rand::thread_rng().gen_range(0..answer_entities.len())].clone();
-        // TODO: Move to ResponseMessage::from_answer_entity function
-        match &random_entity.content_type {
-            EntityContentType::Text => Ok(text_message_with_roll(
-                random_entity.value,
+) -> Result<ResponseMessage, ProcessError> {
+    match command_container.argument {
+        Argument::Empty => trigger_similarity(pool, chat_db_id).await.map(|x| {
+            text_message(
+                format!("{}: {}%", translate(language, Phrase::TriggerSimilarity), x),
                 chat_id,
                 message_id,
-            )),
-            EntityContentType::Voice => Ok(ResponseMessage::Voice {
-                base_body: BaseBody {
-                    chat_id,
-                    reply_to_message_id: Some(message_id),
-                    reply_markup: roll_reply_markup(),
-                },
-                voice: random_entity.value,
-                caption: random_entity.description,
-            }),
-            EntityContentType::Picture => Ok(ResponseMessage::Photo {
-                base_body: BaseBody {
-                    chat_id,
-                    reply_to_message_id: Some(message_id),
-                    reply_markup: roll_reply_markup(),
-                },
-                photo: random_entity.value,
-                caption: random_entity.description,
-            }),
-            EntityContentType::Animation => Ok(ResponseMessage::Animation {
-                base_body: BaseBody {
-                    chat_id,
-                    reply_to_message_id: Some(message_id),
-                    reply_markup: roll_reply_markup(),
-                },
-                animation: random_entity.value,
-                caption: random_entity.description,
-            }),
-            EntityContentType::Video => Ok(ResponseMessage::Video {
-                base_body: BaseBody {
-                    chat_id,
-                    reply_to_message_id: Some(message_id),
-                    reply_markup: roll_reply_markup(),
-                },
-                video: random_entity.value,
-                caption: random_entity.description,
-            }),
-            EntityContentType::VideoNote => Ok(ResponseMessage::VideoNote {
-                base_body: BaseBody {
-                    chat_id,
-                    reply_to_message_id: Some(message_id),
-                    reply_markup: roll_reply_markup(),
-                },
-                video_note: random_entity.value,
-            }),
-            EntityContentType::Sticker => Ok(ResponseMessage::Sticker {
-                base_body: BaseBody {
-                    chat_id,
-                    reply_to_message_id: Some(message_id),
-                    reply_markup: roll_reply_markup(),
-                },
-                sticker: random_entity.value,
-            }),
-            EntityContentType::Audio => Ok(ResponseMessage::Audio {
-                base_body: BaseBody {
-                    chat_id,
-                    reply_to_message_id: Some(message_id),
-                    reply_markup: roll_reply_markup(),
-                },
-                audio: random_entity.value,
-                caption: random_entity.description,
-            }),
-            EntityContentType::Document => Ok(ResponseMessage::Document {
-                base_body: BaseBody {
-                    chat_id,
-                    reply_to_message_id: Some(message_id),
-                    reply_markup: roll_reply_markup(),
-                },
-                document: random_entity.value,
-                caption: random_entity.description,
-            }),
-        }
+            )
+        }),
+        Argument::Integer(x) => set_trigger_similarity(pool, chat_db_id, x as i16)
+            .await
+            .map(|_| text_message(translate(language, Phrase::Done), chat_id, message_id)),
+        _ => Err(ProcessError::Feedback {
+            message: Phrase::InvalidValue,
+        }),
     }
 }
 
-async fn show_keys<'a>(
+async fn word_forms<'a>(
     pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
     chat_db_id: &ChatId,
-    reply_message_body: &MessageBody,
     chat_id: i64,
     message_id: i64,
-) -> Result<ResponseMessage, ProcessError<'a>> {
-    let (value, file_unique_id, _) = &reply_message_body.ext.content();
-    let found_keys = match &reply_message_body.ext {
-        MessageExt::Text { .. } => all_keys(pool, value, chat_db_id, false).await,
-        _ => all_keys(pool, &file_unique_id.clone().unwrap(), chat_db_id, true).await,
-    };
-    if found_keys.is_empty() {
-        return Err(ProcessError::Feedback {
-            message: "Ключей не найдено",
-        });
-    };
-    Ok(text_message(
-        found_keys.iter().fold(String::new(), |s, k| {
-            if s.is_empty() {
-                s + k
-            } else {
-                s + "," + " " + k
+) -> Result<ResponseMessage, ProcessError> {
+    match command_container.argument {
+        Argument::Empty => key_normalization(pool, chat_db_id).await.map(|x| {
+            text_message(
+                format!(
+                    "{}: {}",
+                    translate(language, Phrase::WordForms),
+                    x.word(language)
+                ),
+                chat_id,
+                message_id,
+            )
+        }),
+        Argument::EnumWord(word) => match KeyNormalization::from_word(word) {
+            Some(new_key_normalization) => {
+                set_key_normalization(pool, chat_db_id, &new_key_normalization)
+                    .await
+                    .map(|_| text_message(translate(language, Phrase::Done), chat_id, message_id))
             }
+            None => Err(ProcessError::Feedback {
+                message: Phrase::InvalidValue,
+            }),
+        },
+        _ => Err(ProcessError::Feedback {
+            message: Phrase::InvalidValue,
         }),
-        chat_id,
-        message_id,
-    ))
+    }
 }
 
-async fn remember<'a>(
+async fn check<'a>(
     pool: &PgPool,
     command_container: &CommandContainer<'a>,
+    language: &Language,
     chat_db_id: &ChatId,
-    chat_id: i64,
+    member_db_id: &MemberId,
+    message: &Message,
     message_id: i64,
-    reply_message_body: &MessageBody,
-) -> Result<ResponseMessage, ProcessError<'a>> {
-    let entity_content_type = EntityContentType::from_message_ext(&reply_message_body.ext);
-    let entity_reaction_type = match &command_container.control_item {
-        Some(ControlItem::Substring) => EntityReactionType::Substring,
-        Some(ControlItem::Trigger) => EntityReactionType::Trigger,
+) -> Result<ResponseMessage, ProcessError> {
+    let chat_id = message.direct().base.chat.id;
+    let text = source_text(
+        message.direct().ext.raw_text().unwrap_or_default(),
+        command_container.rest,
+    );
+    let (answer_entities, similarity) = match command_container.control_item {
+        Some(ControlItem::Trigger) => triggers(pool, command_container.rest, chat_db_id).await,
+        Some(ControlItem::Substring) => (
+            substrings(pool, command_container.rest, chat_db_id).await,
+            100,
+        ),
+        Some(ControlItem::Regex) => (regexes(pool, text, chat_db_id).await, 100),
         _ => {
             return Err(ProcessError::Feedback {
-                message: "Тип объекта редактирования не поддерживается",
+                message: Phrase::UnsupportedControlItem,
             })
         }
     };
-    let (value, file_unique_id, description) = reply_message_body.ext.content();
-    let existed_keys = AnswerEntity::existed_keys(
-        pool,
-        chat_db_id,
-        &value,
-        &file_unique_id,
-        &entity_content_type,
-        &entity_reaction_type,
+    let Some(mut random_entity) = select_answer_entity(pool, chat_db_id, answer_entities).await
+    else {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NothingFound,
+        });
+    };
+    // Values saved before templates validation are sent as is
+    if random_entity.content_type == EntityContentType::Text {
+        if let Ok(value) =
+            render_template(pool, &random_entity.value, chat_db_id, member_db_id, text).await
+        {
+            random_entity.value = value;
+        }
+    }
+    let key = random_entity.key.clone();
+    let response_message = ResponseMessage::from_answer_entity(
+        random_entity,
+        chat_id,
+        message_id,
+        roll_reply_markup(),
     )
-    .await;
-    if AnswerEntity::bulk_add_items(
+    .ok_or(ProcessError::Stop)?;
+    // Typo-tolerant trigger match shows how similar the found trigger is
+    Ok(match similarity {
+        100 => response_message,
+        _ => response_message.with_note(format!(
+            "{} «{}»: {}%",
+            translate(language, Phrase::SimilarityToTrigger),
+            key,
+            similarity
+        )),
+    })
+}
+
+async fn template<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let text = message.direct().ext.raw_text().unwrap_or_default();
+    render_template(
         pool,
-        joined_string(command_container.rest)
-            .iter()
-            .filter(|x| !x.is_empty() && !existed_keys.contains(x))
-            .collect::<Vec<_>>(),
+        source_text(text, command_container.rest),
         chat_db_id,
-        (&value, &file_unique_id, &description),
-        &entity_content_type,
-        &entity_reaction_type,
+        member_db_id,
+        text,
     )
     .await
-    {
-        Ok(text_message("Сделал".to_string(), chat_id, message_id))
-    } else {
-        Err(ProcessError::Feedback {
-            message: "Произошла ошибка добавления",
-        })
-    }
+    .map(|x| text_message(x, message.direct().base.chat.id, message_id))
 }
 
-pub async fn delete_entity<'a>(
+async fn weight<'a>(
     pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
     chat_db_id: &ChatId,
     chat_id: i64,
     message_id: i64,
     reply_message_body: &MessageBody,
-) -> Result<ResponseMessage, ProcessError<'a>> {
-    let (value, file_unique_id, _) = reply_message_body.ext.content();
-    let deleted_keys = AnswerEntity::delete(
-        pool,
-        chat_db_id,
-        &value,
-        &file_unique_id,
-        &EntityContentType::from_message_ext(&reply_message_body.ext),
-    )
-    .await;
-    if deleted_keys.is_empty() {
-        Err(ProcessError::Feedback {
-            message: "Ничего не было удалено",
-        })
-    } else {
-        Ok(text_message(
-            format!(
-                "Был удален контент на ключах: {}",
-                deleted_keys.iter().fold(String::new(), |s, k| {
-                    if s.is_empty() {
-                        s + k
-                    } else {
-                        s + "," + " " + k
-                    }
-                })
-            ),
-            chat_id,
-            message_id,
-        ))
+) -> Result<ResponseMessage, ProcessError> {
+    let (entity_content_type, (value, file_unique_id, _)) =
+        saved_reply_content(pool, chat_db_id, reply_message_body).await?;
+    match command_container.argument {
+        Argument::Empty => {
+            let weights = AnswerEntity::weights(
+                pool,
+                chat_db_id,
+                &value,
+                &file_unique_id,
+                &entity_content_type,
+            )
+            .await;
+            if weights.is_empty() {
+                return Err(ProcessError::Feedback {
+                    message: Phrase::NoKeysFound,
+                });
+            }
+            Ok(text_message(
+                format!(
+                    "{}: {}",
+                    translate(language, Phrase::Weight),
+                    weights
+                        .iter()
+                        .map(|(key, weight)| format!("{key} - {weight}"))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+                chat_id,
+                message_id,
+            ))
+        }
+        Argument::Integer(x) => match AnswerEntity::update_weight(
+            pool,
+            chat_db_id,
+            &value,
+            &file_unique_id,
+            &entity_content_type,
+            x as i16,
+        )
+        .await
+        {
+            Ok(keys) if keys.is_empty() => Err(ProcessError::Feedback {
+                message: Phrase::NoKeysFound,
+            }),
+            Ok(_) => Ok(text_message(
+                translate(language, Phrase::Done),
+                chat_id,
+                message_id,
+            )),
+            Err(_) => Err(ProcessError::Feedback {
+                message: Phrase::WeightError,
+            }),
+        },
+        _ => Err(ProcessError::Feedback {
+            message: Phrase::InvalidValue,
+        }),
     }
 }
 
-async fn add_dictionary_entity<'a>(
+async fn selection<'a>(
     pool: &PgPool,
     command_container: &CommandContainer<'a>,
+    language: &Language,
     chat_db_id: &ChatId,
     chat_id: i64,
     message_id: i64,
-) -> Result<ResponseMessage, ProcessError<'a>> {
-    let existed_values = DictionaryEntity::existed_values(pool, chat_db_id).await;
-    if DictionaryEntity::bulk_add_items(
-        pool,
-        joined_string(command_container.rest)
-            .iter()
-            .filter(|x| !x.is_empty() && !existed_values.contains(x))
-            .collect::<Vec<_>>(),
-        chat_db_id,
-    )
-    .await
-    {
-        Ok(text_message("Сделал".to_string(), chat_id, message_id))
-    } else {
-        Err(ProcessError::Feedback {
-            message: "Произошла ошибка добавления",
-        })
+) -> Result<ResponseMessage, ProcessError> {
+    let entity_reaction_type = match &command_container.control_item {
+        Some(ControlItem::Substring) => EntityReactionType::Substring,
+        Some(ControlItem::Trigger) => EntityReactionType::Trigger,
+        Some(ControlItem::Regex) => EntityReactionType::Regex,
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::UnsupportedContentType,
+            })
+        }
+    };
+    match &command_container.argument {
+        Argument::EnumWordWithList(_, keys) if keys.is_empty() => Err(ProcessError::Feedback {
+            message: Phrase::NeedValues,
+        }),
+        Argument::EnumWordWithList(None, keys) => {
+            let modes =
+                AnswerKeySelection::modes(pool, chat_db_id, keys, &entity_reaction_type).await;
+            Ok(text_message(
+                keys.iter()
+                    .map(|key| {
+                        let mode = modes
+                            .iter()
+                            .find(|(x, _)| x == key)
+                            .map(|(_, mode)| *mode)
+                            .unwrap_or_default();
+                        format!("{key}: {}", mode.word(language))
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+                chat_id,
+                message_id,
+            ))
+        }
+        Argument::EnumWordWithList(Some(word), keys) => match SelectionMode::from_word(word) {
+            Some(mode) => {
+                if AnswerKeySelection::update_mode(
+                    pool,
+                    chat_db_id,
+                    keys,
+                    &entity_reaction_type,
+                    &mode,
+                )
+                .await
+                {
+                    Ok(text_message(
+                        translate(language, Phrase::Done),
+                        chat_id,
+                        message_id,
+                    ))
+                } else {
+                    Err(ProcessError::Feedback {
+                        message: Phrase::SelectionError,
+                    })
+                }
+            }
+            None => Err(ProcessError::Feedback {
+                message: Phrase::InvalidValue,
+            }),
+        },
+        _ => Err(ProcessError::Feedback {
+            message: Phrase::InvalidValue,
+        }),
     }
 }
 
-pub async fn process_command<'a>(
-    tokens: &'a Option<Vec<Token<'a>>>,
-    message: &'a Message,
+async fn show_keys(
     pool: &PgPool,
-    _member_db_id: &MemberId,
     chat_db_id: &ChatId,
-    _chat_to_member_db_id: &ChatToMemberId,
-    is_roll: bool,
-) -> Result<ResponseMessage, ProcessError<'a>> {
-    let tokens = match tokens {
-        None => return Err(ProcessError::Next),
+    reply_message_body: &MessageBody,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let (_, (value, file_unique_id, _)) =
+        saved_reply_content(pool, chat_db_id, reply_message_body).await?;
+    // Contents without files as dice or locations are found by value as texts
+    let found_keys = match file_unique_id {
+        Some(file_unique_id) => all_keys(pool, &file_unique_id, chat_db_id, true).await,
+        None => all_keys(pool, &value, chat_db_id, false).await,
+    };
+    if found_keys.is_empty() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NoKeysFound,
+        });
+    };
+    let mut keys = vec![];
+    for (key, author_id) in found_keys {
+        // Content saved before authors tracking has no author
+        keys.push(match author_id {
+            Some(author_id) => match Member::one_by_id(pool, &author_id).await {
+                Some(author) => format!("{key} ({})", pretty_username(&author)),
+                None => key,
+            },
+            None => key,
+        });
+    }
+    Ok(text_message(keys.join(", "), chat_id, message_id))
+}
+
+// Search text is a key prefix, "*" in it matches any text
+fn key_search_pattern(search: &str) -> String {
+    let pattern = search
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+        .replace('*', "%");
+    match search.contains('*') {
+        true => pattern,
+        false => pattern + "%",
+    }
+}
+
+async fn keys_page(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    search: &str,
+    page: i64,
+) -> Result<(String, ReplyMarkupResponse), ProcessError> {
+    let pattern = key_search_pattern(search);
+    let count = AnswerEntity::key_groups_count(pool, chat_db_id, &pattern).await;
+    if count == 0 {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NoKeysFound,
+        });
+    }
+    let pages = (count + KEYS_PAGE_SIZE - 1) / KEYS_PAGE_SIZE;
+    let page = page.clamp(0, pages - 1);
+    let groups = AnswerEntity::key_groups(
+        pool,
+        chat_db_id,
+        &pattern,
+        KEYS_PAGE_SIZE,
+        page * KEYS_PAGE_SIZE,
+    )
+    .await;
+    let mut lines = vec![format!(
+        "{}: {}/{}",
+        translate(language, Phrase::Page),
+        page + 1,
+        pages
+    )];
+    let mut buttons = vec![];
+    for (index, group) in groups.iter().enumerate() {
+        // Keys are ordered by reaction and content types, so the header is printed on change
+        let header = format!(
+            "{}, {}:",
+            group.reaction_type.name(language),
+            group.content_type.name(language)
+        );
+        if index == 0
+            || groups[index - 1].reaction_type != group.reaction_type
+            || groups[index - 1].content_type != group.content_type
+        {
+            lines.push(format!("\n{header}"));
+        }
+        lines.push(match group.count {
+            1 => format!("{}. {}", index + 1, group.key),
+            count => format!("{}. {} ({count})", index + 1, group.key),
+        });
+        buttons.push(ReplyMarkupButtonResponse {
+            text: (index + 1).to_string(),
+            callback_data: format!("{KEY_VALUES_CALLBACK}{}", group.id),
+        });
+    }
+    let mut inline_keyboard = vec![];
+    while !buttons.is_empty() {
+        let rest = buttons.split_off(buttons.len().min(KEYS_ROW_SIZE));
+        inline_keyboard.push(buttons);
+        buttons = rest;
+    }
+    let navigation = page_navigation(KEYS_PAGE_CALLBACK, page, pages);
+    if !navigation.is_empty() {
+        inline_keyboard.push(navigation);
+    }
+    Ok((lines.join("\n"), ReplyMarkupResponse { inline_keyboard }))
+}
+
+// Buttons to the previous and the next pages, which exist
+fn page_navigation(callback: &str, page: i64, pages: i64) -> Vec<ReplyMarkupButtonResponse> {
+    [(page > 0, "←", page - 1), (page + 1 < pages, "→", page + 1)]
+        .into_iter()
+        .filter(|(is_shown, _, _)| *is_shown)
+        .map(|(_, text, page)| ReplyMarkupButtonResponse {
+            text: text.to_string(),
+            callback_data: format!("{callback}{page}"),
+        })
+        .collect()
+}
+
+async fn keys<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let search = source_text(
+        message.direct().ext.raw_text().unwrap_or_default(),
+        command_container.rest,
+    );
+    let (text, reply_markup) = keys_page(pool, language, chat_db_id, search, 0).await?;
+    Ok(text_message_with_keyboard(
+        text,
+        message.direct().base.chat.id,
+        message_id,
+        reply_markup,
+    ))
+}
+
+// Page is changed in the message with keys, search is taken from the replied command
+pub async fn keys_page_callback(
+    pool: &PgPool,
+    message: &Message,
+    chat_db_id: &ChatId,
+    page: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let text = message
+        .reply()
+        .and_then(|x| x.ext.raw_text())
+        .unwrap_or_default();
+    let tokens = tokenize(text);
+    let search = match parse_command(&tokens, false) {
+        Ok(command_container) if command_container.command == &Command::Keys => {
+            source_text(text, command_container.rest)
+        }
+        _ => "",
+    };
+    let language = &chat_language(pool, chat_db_id).await.unwrap_or_default();
+    let (text, reply_markup) = keys_page(pool, language, chat_db_id, search, page).await?;
+    Ok(ResponseMessage::EditText {
+        chat_id: message.direct().base.chat.id,
+        message_id: message.direct().base.message_id,
+        text,
+        reply_markup: Some(reply_markup),
+    })
+}
+
+// Text values are sent together, of other values only the first one is sent
+pub async fn key_values_callback(
+    pool: &PgPool,
+    message: &Message,
+    chat_db_id: &ChatId,
+    id: &AnswerEntityId,
+) -> Result<ResponseMessage, ProcessError> {
+    let chat_id = message.direct().base.chat.id;
+    let message_id = message.direct().base.message_id;
+    let mut values = AnswerEntity::key_group_values(pool, chat_db_id, id).await;
+    let count = values.len();
+    if count == 0 {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NothingFound,
+        });
+    }
+    if values[0].content_type == EntityContentType::Text {
+        return Ok(text_message(
+            values
+                .into_iter()
+                .map(|x| x.value)
+                .collect::<Vec<String>>()
+                .join("\n\n"),
+            chat_id,
+            message_id,
+        ));
+    }
+    let response_message =
+        ResponseMessage::from_answer_entity(values.remove(0), chat_id, message_id, None)
+            .ok_or(ProcessError::Stop)?;
+    Ok(match count {
+        1 => response_message,
+        _ => response_message.with_note(format!("1/{count}")),
+    })
+}
+
+// Value, file unique id and description of content
+pub type ContentParts = (String, Option<String>, Option<String>);
+
+// Content of the replied message, a part of album stands for the whole album
+fn reply_content(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    reply_message_body: &MessageBody,
+) -> Result<(EntityContentType, ContentParts), ProcessError> {
+    if !reply_message_body.ext.can_be_remembered() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::UnsupportedMessage,
+        });
+    }
+    Ok(match album_content(pool, chat_db_id, reply_message_body) {
+        Some(content) => (EntityContentType::MediaGroup, content),
+        None => (
+            EntityContentType::from_message_ext(&reply_message_body.ext),
+            reply_message_body.ext.content(),
+        ),
+    })
+}
+
+// Forwards and copies are sent from the replied message, so it keeps its formatting and author
+fn forward_content(reply_message_body: &MessageBody) -> ContentParts {
+    (
+        format!(
+            "{}:{}",
+            reply_message_body.base.chat.id, reply_message_body.base.message_id
+        ),
+        None,
+        None,
+    )
+}
+
+// Content of the replied message to find it among saved answers. Forwards and copies are saved
+// by their source, so the replied message is looked up as a source first
+pub async fn saved_reply_content(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    reply_message_body: &MessageBody,
+) -> Result<(EntityContentType, ContentParts), ProcessError> {
+    let source = forward_content(reply_message_body);
+    match AnswerEntity::source_content_type(pool, chat_db_id, &source.0).await {
+        Some(content_type) => Ok((content_type, source)),
+        None => reply_content(pool, chat_db_id, reply_message_body),
+    }
+}
+
+async fn remember<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let chat_id = message.direct().base.chat.id;
+    let reply_message_body = message.reply().unwrap();
+    let entity_reaction_type = match &command_container.control_item {
+        Some(ControlItem::Substring) => EntityReactionType::Substring,
+        Some(ControlItem::Trigger) => EntityReactionType::Trigger,
+        Some(ControlItem::Regex) => EntityReactionType::Regex,
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::UnsupportedContentType,
+            })
+        }
+    };
+    let keys = match (&entity_reaction_type, &command_container.argument) {
+        // Commas are common in patterns, so the whole text is a single pattern
+        (EntityReactionType::Regex, _) => {
+            let text = message.direct().ext.raw_text().unwrap_or_default();
+            let pattern = source_text(text, command_container.rest);
+            compile_pattern(pattern).map_err(|message| ProcessError::Feedback { message })?;
+            vec![pattern.to_string()]
+        }
+        (_, Argument::List(keys)) => keys.clone(),
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::NeedValues,
+            })
+        }
+    };
+    let (entity_content_type, (value, file_unique_id, description)) =
+        match command_container.command {
+            Command::RememberForward => (
+                EntityContentType::Forward,
+                forward_content(reply_message_body),
+            ),
+            Command::RememberCopy => (EntityContentType::Copy, forward_content(reply_message_body)),
+            _ => reply_content(pool, chat_db_id, reply_message_body)?,
+        };
+    if entity_content_type == EntityContentType::Text {
+        parse_template(&value).map_err(|message| ProcessError::Feedback { message })?;
+    }
+    let existed_keys = AnswerEntity::existed_keys(
+        pool,
+        chat_db_id,
+        &value,
+        &file_unique_id,
+        &entity_content_type,
+        &entity_reaction_type,
+    )
+    .await;
+    let new_keys = keys
+        .iter()
+        .filter(|x| !existed_keys.contains(x))
+        .collect::<Vec<_>>();
+    let details = new_keys
+        .iter()
+        .map(|x| x.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    if AnswerEntity::bulk_add_items(
+        pool,
+        new_keys,
+        chat_db_id,
+        (&value, &file_unique_id, &description),
+        &entity_content_type,
+        &entity_reaction_type,
+        member_db_id,
+    )
+    .await
+    {
+        invalidate_indexes(pool, chat_db_id);
+        log_action(
+            pool,
+            chat_db_id,
+            member_db_id,
+            AuditAction::Remember,
+            &details,
+        )
+        .await;
+        Ok(text_message(
+            translate(language, Phrase::DidIt),
+            chat_id,
+            message_id,
+        ))
+    } else {
+        Err(ProcessError::Feedback {
+            message: Phrase::AddError,
+        })
+    }
+}
+
+// The first call chooses the media, the second one chooses the answer on it.
+// With emoji the answer is saved at once on any sticker with the emoji
+async fn remember_media<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let chat_id = message.direct().base.chat.id;
+    let reply_message_body = message.reply().unwrap();
+    let pending_key = (cache_key(pool, chat_db_id), member_db_id.clone());
+    let emoji = source_text(
+        message.direct().ext.raw_text().unwrap_or_default(),
+        command_container.rest,
+    );
+    let key = match emoji.trim() {
+        "" => pending_media_keys().remove(&pending_key).map(|x| x.key),
+        emoji if emoji.chars().any(char::is_alphanumeric) => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::NeedStickerEmoji,
+            })
+        }
+        emoji => Some(emoji.to_string()),
+    };
+    let Some(key) = key else {
+        let Some(media_key) = reply_message_body.ext.media_keys().into_iter().next() else {
+            return Err(ProcessError::Feedback {
+                message: Phrase::NeedMediaReply,
+            });
+        };
+        pending_media_keys().insert(
+            pending_key,
+            PendingMediaKey {
+                created_at: Instant::now(),
+                key: media_key,
+            },
+        );
+        return Ok(text_message(
+            translate(language, Phrase::NeedAnswerReply),
+            chat_id,
+            message_id,
+        ));
+    };
+    let (entity_content_type, (value, file_unique_id, description)) =
+        reply_content(pool, chat_db_id, reply_message_body)?;
+    if entity_content_type == EntityContentType::Text {
+        parse_template(&value).map_err(|message| ProcessError::Feedback { message })?;
+    }
+    let existed_keys = AnswerEntity::existed_keys(
+        pool,
+        chat_db_id,
+        &value,
+        &file_unique_id,
+        &entity_content_type,
+        &EntityReactionType::Media,
+    )
+    .await;
+    if !existed_keys.contains(&key)
+        && !AnswerEntity::bulk_add_items(
+            pool,
+            vec![&key],
+            chat_db_id,
+            (&value, &file_unique_id, &description),
+            &entity_content_type,
+            &EntityReactionType::Media,
+            member_db_id,
+        )
+        .await
+    {
+        return Err(ProcessError::Feedback {
+            message: Phrase::AddError,
+        });
+    }
+    invalidate_indexes(pool, chat_db_id);
+    log_action(pool, chat_db_id, member_db_id, AuditAction::Remember, &key).await;
+    Ok(text_message(
+        translate(language, Phrase::DidIt),
+        chat_id,
+        message_id,
+    ))
+}
+
+pub async fn delete_entity(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+    reply_message_body: &MessageBody,
+) -> Result<ResponseMessage, ProcessError> {
+    let (entity_content_type, (value, file_unique_id, _)) =
+        saved_reply_content(pool, chat_db_id, reply_message_body).await?;
+    let deleted_keys = AnswerEntity::delete(
+        pool,
+        chat_db_id,
+        &value,
+        &file_unique_id,
+        &entity_content_type,
+        member_db_id,
+    )
+    .await;
+    // Trash is purged on deletions, because there is no scheduler for it
+    purge_trash(pool, chat_db_id, TRASH_RETENTION).await.ok();
+    if deleted_keys.is_empty() {
+        Err(ProcessError::Feedback {
+            message: Phrase::NothingDeleted,
+        })
+    } else {
+        invalidate_indexes(pool, chat_db_id);
+        log_action(
+            pool,
+            chat_db_id,
+            member_db_id,
+            AuditAction::Delete,
+            &deleted_keys.join(", "),
+        )
+        .await;
+        Ok(text_message(
+            format!(
+                "{}: {}",
+                translate(language, Phrase::DeletedKeys),
+                deleted_keys.iter().fold(String::new(), |s, k| {
+                    if s.is_empty() {
+                        s + k
+                    } else {
+                        s + "," + " " + k
+                    }
+                })
+            ),
+            chat_id,
+            message_id,
+        ))
+    }
+}
+
+async fn unlink<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let Argument::List(keys) = &command_container.argument else {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NeedValues,
+        });
+    };
+    let (entity_content_type, (value, file_unique_id, _)) =
+        saved_reply_content(pool, chat_db_id, message.reply().unwrap()).await?;
+    let unlinked_keys = AnswerEntity::unlink(
+        pool,
+        chat_db_id,
+        (&value, &file_unique_id, &entity_content_type),
+        keys,
+        member_db_id,
+    )
+    .await;
+    if unlinked_keys.is_empty() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NothingDeleted,
+        });
+    }
+    invalidate_indexes(pool, chat_db_id);
+    log_action(
+        pool,
+        chat_db_id,
+        member_db_id,
+        AuditAction::Unlink,
+        &unlinked_keys.join(", "),
+    )
+    .await;
+    Ok(text_message(
+        format!(
+            "{}: {}",
+            translate(language, Phrase::UnlinkedKeys),
+            unlinked_keys.join(", ")
+        ),
+        message.direct().base.chat.id,
+        message_id,
+    ))
+}
+
+// Old and new keys are matched as remember stores them: patterns keep their source text,
+// so they are taken from the message text instead of the normalized argument
+fn edited_keys(
+    command_container: &CommandContainer,
+    message: &Message,
+) -> Result<(EntityReactionType, String, String), ProcessError> {
+    let Argument::Pair(old_key, new_key) = &command_container.argument else {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NeedValues,
+        });
+    };
+    match &command_container.control_item {
+        Some(ControlItem::Trigger) => Ok((
+            EntityReactionType::Trigger,
+            old_key.clone(),
+            new_key.clone(),
+        )),
+        Some(ControlItem::Substring) => Ok((
+            EntityReactionType::Substring,
+            old_key.clone(),
+            new_key.clone(),
+        )),
+        Some(ControlItem::Regex) => {
+            let text = message.direct().ext.raw_text().unwrap_or_default();
+            // Comma may be glued to punctuation of the pattern, so source text is split
+            let patterns = source_text(text, command_container.rest)
+                .split(',')
+                .map(str::trim)
+                .collect::<Vec<&str>>();
+            let [old_pattern, new_pattern] = patterns[..] else {
+                return Err(ProcessError::Feedback {
+                    message: Phrase::NeedValues,
+                });
+            };
+            compile_pattern(new_pattern).map_err(|message| ProcessError::Feedback { message })?;
+            Ok((
+                EntityReactionType::Regex,
+                old_pattern.to_string(),
+                new_pattern.to_string(),
+            ))
+        }
+        _ => Err(ProcessError::Feedback {
+            message: Phrase::UnsupportedContentType,
+        }),
+    }
+}
+
+async fn rename<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let (entity_reaction_type, old_key, new_key) = edited_keys(command_container, message)?;
+    match AnswerEntity::rename_key(
+        pool,
+        chat_db_id,
+        &old_key,
+        &new_key,
+        &entity_reaction_type,
+        member_db_id,
+    )
+    .await
+    {
+        Ok(0) => Err(ProcessError::Feedback {
+            message: Phrase::NoKeysFound,
+        }),
+        Ok(_) => {
+            invalidate_indexes(pool, chat_db_id);
+            log_action(
+                pool,
+                chat_db_id,
+                member_db_id,
+                AuditAction::Rename,
+                &format!("{old_key} → {new_key}"),
+            )
+            .await;
+            Ok(text_message(
+                translate(language, Phrase::Done),
+                message.direct().base.chat.id,
+                message_id,
+            ))
+        }
+        Err(_) => Err(ProcessError::Feedback {
+            message: Phrase::RenameError,
+        }),
+    }
+}
+
+async fn move_content<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let (entity_reaction_type, old_key, new_key) = edited_keys(command_container, message)?;
+    let (entity_content_type, (value, file_unique_id, _)) =
+        saved_reply_content(pool, chat_db_id, message.reply().unwrap()).await?;
+    match AnswerEntity::move_content(
+        pool,
+        chat_db_id,
+        (&value, &file_unique_id, &entity_content_type),
+        &old_key,
+        &new_key,
+        &entity_reaction_type,
+        member_db_id,
+    )
+    .await
+    {
+        Ok(0) => Err(ProcessError::Feedback {
+            message: Phrase::NoKeysFound,
+        }),
+        Ok(_) => {
+            invalidate_indexes(pool, chat_db_id);
+            log_action(
+                pool,
+                chat_db_id,
+                member_db_id,
+                AuditAction::Move,
+                &format!("{old_key} → {new_key}"),
+            )
+            .await;
+            Ok(text_message(
+                translate(language, Phrase::Done),
+                message.direct().base.chat.id,
+                message_id,
+            ))
+        }
+        Err(_) => Err(ProcessError::Feedback {
+            message: Phrase::MoveError,
+        }),
+    }
+}
+
+async fn purge_trash(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    retention: Duration,
+) -> Result<u64, ProcessError> {
+    let deleted_before = chrono::Duration::from_std(retention)
+        .ok()
+        .and_then(|x| Utc::now().naive_utc().checked_sub_signed(x))
+        .ok_or(ProcessError::Feedback {
+            message: Phrase::InvalidValue,
+        })?;
+    AnswerEntity::purge(pool, chat_db_id, deleted_before)
+        .await
+        .map_err(|_| ProcessError::Feedback {
+            message: Phrase::PurgeError,
+        })
+}
+
+async fn trash(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let deleted_entities = AnswerEntity::deleted(pool, chat_db_id, TRASH_LIST_LIMIT).await;
+    if deleted_entities.is_empty() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::TrashEmpty,
+        });
+    }
+    let mut lines = vec![];
+    for (index, entity) in deleted_entities.iter().enumerate() {
+        let content = match entity.content_type {
+            EntityContentType::Text => {
+                format!("«{}»", entity.value.chars().take(50).collect::<String>())
+            }
+            _ => entity.content_type.name(language).to_string(),
+        };
+        let deleted_by = match &entity.deleted_by {
+            Some(member_db_id) => Member::one_by_id(pool, member_db_id)
+                .await
+                .map(|x| pretty_username(&x))
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+        lines.push(format!(
+            "{}) {}: {} - {}, {}",
+            index + 1,
+            entity.key,
+            content,
+            deleted_by,
+            entity.deleted_at.format("%d.%m.%Y %H:%M")
+        ));
+    }
+    Ok(text_message(lines.join("\n"), chat_id, message_id))
+}
+
+async fn restore(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+    reply_message_body: Option<&MessageBody>,
+) -> Result<ResponseMessage, ProcessError> {
+    let restored_keys = match reply_message_body {
+        Some(reply_message_body) => {
+            let (entity_content_type, (value, file_unique_id, _)) =
+                saved_reply_content(pool, chat_db_id, reply_message_body).await?;
+            AnswerEntity::restore(
+                pool,
+                chat_db_id,
+                &value,
+                &file_unique_id,
+                &entity_content_type,
+            )
+            .await
+        }
+        None => AnswerEntity::restore_last_deleted(pool, chat_db_id).await,
+    };
+    if restored_keys.is_empty() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NothingRestored,
+        });
+    }
+    invalidate_indexes(pool, chat_db_id);
+    log_action(
+        pool,
+        chat_db_id,
+        member_db_id,
+        AuditAction::Restore,
+        &restored_keys.join(", "),
+    )
+    .await;
+    Ok(text_message(
+        format!(
+            "{}: {}",
+            translate(language, Phrase::RestoredKeys),
+            restored_keys.join(", ")
+        ),
+        chat_id,
+        message_id,
+    ))
+}
+
+async fn purge<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let retention = match command_container.argument {
+        Argument::Empty => TRASH_RETENTION,
+        Argument::Duration(x) => x,
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::InvalidValue,
+            })
+        }
+    };
+    let purged = purge_trash(pool, chat_db_id, retention).await?;
+    log_action(
+        pool,
+        chat_db_id,
+        member_db_id,
+        AuditAction::Purge,
+        &purged.to_string(),
+    )
+    .await;
+    Ok(text_message(
+        format!(
+            "{}: {}",
+            translate(language, Phrase::PurgedFromTrash),
+            purged
+        ),
+        chat_id,
+        message_id,
+    ))
+}
+
+async fn history(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let entries = AuditLogEntry::recent(pool, chat_db_id, HISTORY_LIST_LIMIT).await;
+    if entries.is_empty() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::HistoryEmpty,
+        });
+    }
+    let mut lines = vec![];
+    for (index, entry) in entries.iter().enumerate() {
+        let author = match &entry.member_id {
+            Some(member_db_id) => Member::one_by_id(pool, member_db_id)
+                .await
+                .map(|x| pretty_username(&x))
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+        lines.push(format!(
+            "{}) {} {} {}: {}",
+            index + 1,
+            entry.created_at.format("%d.%m.%Y %H:%M"),
+            author,
+            entry.action.name(language),
+            entry.details
+        ));
+    }
+    Ok(text_message(lines.join("\n"), chat_id, message_id))
+}
+
+async fn permission<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let chat_id = message.direct().base.chat.id;
+    // Without a command only roles different from the member one are shown
+    if command_container.rest.is_empty() {
+        let roles = CommandPermission::roles(pool, chat_db_id).await;
+        let mut lines = COMMAND_SETTING_MAP
+            .iter()
+            .filter_map(|(&command, command_setting)| {
+                let role = roles
+                    .iter()
+                    .find(|(x, _)| x == command_name(command))
+                    .map(|(_, role)| *role)
+                    .unwrap_or(command_setting.required_role);
+                (role != Role::Member).then(|| {
+                    format!(
+                        "{}: {}",
+                        command_setting.localized_aliases(language)[0],
+                        role.word(language)
+                    )
+                })
+            })
+            .collect::<Vec<String>>();
+        lines.sort();
+        return Ok(text_message(lines.join("\n"), chat_id, message_id));
+    }
+    let Some((command, _, rest)) = find_command(command_container.rest) else {
+        return Err(ProcessError::Feedback {
+            message: Phrase::InvalidValue,
+        });
+    };
+    let command_setting = COMMAND_SETTING_MAP.get(command).unwrap();
+    let command_alias = command_setting.localized_aliases(language)[0];
+    match rest {
+        [] => Ok(text_message(
+            format!(
+                "{}: {}",
+                command_alias,
+                required_role(pool, chat_db_id, command)
+                    .await
+                    .word(language)
+            ),
+            chat_id,
+            message_id,
+        )),
+        [Token::Word(word)] => {
+            let Some(role) = Role::from_word(word) else {
+                return Err(ProcessError::Feedback {
+                    message: Phrase::InvalidValue,
+                });
+            };
+            if role > member_role(pool, chat_db_id, member_db_id, chat_id).await {
+                return Err(ProcessError::Feedback {
+                    message: Phrase::PermissionAboveOwn,
+                });
+            }
+            // Override equal to the default role is not stored
+            match role == command_setting.required_role {
+                true => {
+                    CommandPermission::delete_role(pool, chat_db_id, command_name(command)).await
+                }
+                false => {
+                    CommandPermission::update_role(pool, chat_db_id, command_name(command), &role)
+                        .await
+                }
+            }
+            .map_err(|_| ProcessError::Feedback {
+                message: Phrase::PermissionError,
+            })?;
+            log_action(
+                pool,
+                chat_db_id,
+                member_db_id,
+                AuditAction::Setting,
+                &format!(
+                    "{} {} {}",
+                    tokens_to_string(command_container.command_aliases, false),
+                    command_alias,
+                    role.word(language)
+                ),
+            )
+            .await;
+            Ok(text_message(
+                translate(language, Phrase::Done),
+                chat_id,
+                message_id,
+            ))
+        }
+        _ => Err(ProcessError::Feedback {
+            message: Phrase::InvalidValue,
+        }),
+    }
+}
+
+// Audit log is secondary, so its failure doesn't fail the logged command
+async fn log_action(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    action: AuditAction,
+    details: &str,
+) {
+    if !AuditLogEntry::add(pool, chat_db_id, member_db_id, &action, details).await {
+        warn!("audit log record {:?} failed for {:?}", action, chat_db_id);
+    }
+}
+
+// Setting commands change a value only when it is given, otherwise they show it
+fn is_setting_change(command_container: &CommandContainer) -> bool {
+    match (command_container.command, &command_container.argument) {
+        (Command::Welcome | Command::DeleteWelcome, _) => true,
+        (_, Argument::Empty) => false,
+        (Command::Selection, Argument::EnumWordWithList(mode, _)) => mode.is_some(),
+        (command, _) => [
+            Command::AnswerChance,
+            Command::ChatLanguage,
+            Command::TriggerSimilarity,
+            Command::WelcomeDelay,
+            Command::WelcomeBots,
+            Command::WordForms,
+            Command::Weight,
+            Command::CreatePack,
+            Command::PackAccess,
+            Command::PackChance,
+            Command::Subscribe,
+            Command::Unsubscribe,
+        ]
+        .contains(command),
+    }
+}
+
+async fn add_dictionary_entity<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let Argument::List(values) = &command_container.argument else {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NeedValues,
+        });
+    };
+    match DictionaryEntity::bulk_add_items(pool, values.iter().collect(), chat_db_id, member_db_id)
+        .await
+    {
+        Ok(added_values) => {
+            if !added_values.is_empty() {
+                log_action(
+                    pool,
+                    chat_db_id,
+                    member_db_id,
+                    AuditAction::Add,
+                    &added_values.join(", "),
+                )
+                .await;
+            }
+            Ok(text_message(
+                translate(language, Phrase::DidIt),
+                chat_id,
+                message_id,
+            ))
+        }
+        Err(_) => Err(ProcessError::Feedback {
+            message: Phrase::AddError,
+        }),
+    }
+}
+
+async fn dictionary_page(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    page: i64,
+) -> Result<(String, ReplyMarkupResponse), ProcessError> {
+    let count = DictionaryEntity::count(pool, chat_db_id).await;
+    if count == 0 {
+        return Err(ProcessError::Feedback {
+            message: Phrase::DictionaryEmpty,
+        });
+    }
+    let pages = (count + DICTIONARY_PAGE_SIZE - 1) / DICTIONARY_PAGE_SIZE;
+    let page = page.clamp(0, pages - 1);
+    let values = DictionaryEntity::values(
+        pool,
+        chat_db_id,
+        DICTIONARY_PAGE_SIZE,
+        page * DICTIONARY_PAGE_SIZE,
+    )
+    .await;
+    let mut lines = vec![format!(
+        "{}: {}/{}\n",
+        translate(language, Phrase::Page),
+        page + 1,
+        pages
+    )];
+    for (index, value) in values.iter().enumerate() {
+        lines.push(format!(
+            "{}. {value}",
+            page * DICTIONARY_PAGE_SIZE + index as i64 + 1
+        ));
+    }
+    let navigation = page_navigation(DICTIONARY_PAGE_CALLBACK, page, pages);
+    Ok((
+        lines.join("\n"),
+        ReplyMarkupResponse {
+            inline_keyboard: match navigation.is_empty() {
+                true => vec![],
+                false => vec![navigation],
+            },
+        },
+    ))
+}
+
+async fn dictionary(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let (text, reply_markup) = dictionary_page(pool, language, chat_db_id, 0).await?;
+    Ok(text_message_with_keyboard(
+        text,
+        chat_id,
+        message_id,
+        reply_markup,
+    ))
+}
+
+pub async fn dictionary_page_callback(
+    pool: &PgPool,
+    message: &Message,
+    chat_db_id: &ChatId,
+    page: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let language = &chat_language(pool, chat_db_id).await.unwrap_or_default();
+    let (text, reply_markup) = dictionary_page(pool, language, chat_db_id, page).await?;
+    Ok(ResponseMessage::EditText {
+        chat_id: message.direct().base.chat.id,
+        message_id: message.direct().base.message_id,
+        text,
+        reply_markup: Some(reply_markup),
+    })
+}
+
+async fn delete_words<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let Argument::List(values) = &command_container.argument else {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NeedValues,
+        });
+    };
+    let deleted_values = DictionaryEntity::delete(pool, chat_db_id, values).await;
+    if deleted_values.is_empty() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NothingDeleted,
+        });
+    }
+    log_action(
+        pool,
+        chat_db_id,
+        member_db_id,
+        AuditAction::DeleteWords,
+        &deleted_values.join(", "),
+    )
+    .await;
+    Ok(text_message(
+        format!(
+            "{}: {}",
+            translate(language, Phrase::DeletedWords),
+            deleted_values.join(", ")
+        ),
+        chat_id,
+        message_id,
+    ))
+}
+
+// Words are separated by new lines and commas
+fn dictionary_words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = vec![];
+    for word in text.split(['\n', ',']) {
+        let word = normalize_text(word.trim().to_string());
+        if !word.is_empty() && !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words
+}
+
+async fn import_words(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+    reply_message_body: &MessageBody,
+) -> Result<ResponseMessage, ProcessError> {
+    let text = match &reply_message_body.ext {
+        MessageExt::Text { text, .. } => text.clone(),
+        MessageExt::Document { document, .. } => {
+            download_text_file(&document.file_id, DICTIONARY_IMPORT_MAX_SIZE)
+                .await
+                .ok_or(ProcessError::Feedback {
+                    message: Phrase::FileReadError,
+                })?
+        }
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::UnsupportedContentType,
+            })
+        }
+    };
+    let words = dictionary_words(&text);
+    let added_words =
+        DictionaryEntity::bulk_add_items(pool, words.iter().collect(), chat_db_id, member_db_id)
+            .await
+            .map_err(|_| ProcessError::Feedback {
+                message: Phrase::AddError,
+            })?;
+    if !added_words.is_empty() {
+        log_action(
+            pool,
+            chat_db_id,
+            member_db_id,
+            AuditAction::Import,
+            &added_words.len().to_string(),
+        )
+        .await;
+    }
+    Ok(text_message(
+        format!(
+            "{}: {}",
+            translate(language, Phrase::AddedWords),
+            added_words.len()
+        ),
+        chat_id,
+        message_id,
+    ))
+}
+
+async fn export(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let archive = export_archive(pool, chat_db_id).await;
+    let caption = format!(
+        "{}: {}\n{}: {}",
+        translate(language, Phrase::Answers),
+        archive.answers.len(),
+        translate(language, Phrase::Words),
+        archive.dictionary.len()
+    );
+    Ok(ResponseMessage::NewDocument {
+        base_body: BaseBody {
+            chat_id,
+            reply_to_message_id: Some(message_id),
+            reply_markup: None,
+        },
+        file_name: format!("archive_{}.json", Utc::now().format("%Y%m%d")),
+        content: serde_json::to_string_pretty(&archive).unwrap(),
+        caption: Some(caption),
+    })
+}
+
+async fn import(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+    reply_message_body: &MessageBody,
+) -> Result<ResponseMessage, ProcessError> {
+    let MessageExt::Document { document, .. } = &reply_message_body.ext else {
+        return Err(ProcessError::Feedback {
+            message: Phrase::UnsupportedContentType,
+        });
+    };
+    let text = download_text_file(&document.file_id, ARCHIVE_MAX_SIZE)
+        .await
+        .ok_or(ProcessError::Feedback {
+            message: Phrase::FileReadError,
+        })?;
+    let archive =
+        serde_json::from_str::<ChatArchive>(&text).map_err(|_| ProcessError::Feedback {
+            message: Phrase::InvalidArchive,
+        })?;
+    if archive.version > ARCHIVE_VERSION {
+        return Err(ProcessError::Feedback {
+            message: Phrase::UnsupportedArchiveVersion,
+        });
+    }
+    let report = import_archive(pool, chat_db_id, member_db_id, &archive)
+        .await
+        .map_err(|_| ProcessError::Feedback {
+            message: Phrase::ImportError,
+        })?;
+    log_action(
+        pool,
+        chat_db_id,
+        member_db_id,
+        AuditAction::ImportArchive,
+        &format!("{}, {}", report.added_answers, report.added_words),
+    )
+    .await;
+    Ok(text_message(report.text(language), chat_id, message_id))
+}
+
+// Names are normalized as keys are, so packs are found regardless of case
+fn pack_name<'a>(command_container: &CommandContainer<'a>) -> Result<String, ProcessError> {
+    let name = normalize_text(tokens_to_string(command_container.rest, false));
+    match name.trim() {
+        "" => Err(ProcessError::Feedback {
+            message: Phrase::NeedPackName,
+        }),
+        name => Ok(name.to_string()),
+    }
+}
+
+async fn chat_pack(pool: &PgPool, chat_db_id: &ChatId) -> Result<AnswerPack, ProcessError> {
+    AnswerPack::by_chat(pool, chat_db_id)
+        .await
+        .ok_or(ProcessError::Feedback {
+            message: Phrase::NoChatPack,
+        })
+}
+
+async fn pack_by_name(pool: &PgPool, name: &str) -> Result<AnswerPack, ProcessError> {
+    AnswerPack::by_name(pool, name)
+        .await
+        .ok_or(ProcessError::Feedback {
+            message: Phrase::PackNotFound,
+        })
+}
+
+async fn create_pack<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let name = pack_name(command_container)?;
+    if AnswerPack::by_chat(pool, chat_db_id).await.is_some() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::ChatHasPack,
+        });
+    }
+    if AnswerPack::by_name(pool, &name).await.is_some() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::PackNameTaken,
+        });
+    }
+    match AnswerPack::create(pool, chat_db_id, &name, member_db_id).await {
+        Ok(_) => Ok(text_message(
+            translate(language, Phrase::Done),
+            chat_id,
+            message_id,
+        )),
+        Err(err) => {
+            warn!(
+                "create pack failed with error: {} for {:?}",
+                err, chat_db_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::PackError,
+            })
+        }
+    }
+}
+
+async fn packs(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let packs = AnswerPack::visible(pool, chat_db_id).await;
+    if packs.is_empty() {
+        return Err(ProcessError::Feedback {
+            message: Phrase::NoPacksFound,
+        });
+    }
+    let subscribed = AnswerPack::subscribed(pool, chat_db_id)
+        .await
+        .into_iter()
+        .map(|x| x.id)
+        .collect::<Vec<_>>();
+    let text = packs
+        .iter()
+        .map(|x| {
+            format!(
+                "{}{} - {}, {}%",
+                if subscribed.contains(&x.id) {
+                    "✓ "
+                } else {
+                    ""
+                },
+                x.name,
+                PackVisibility::from_is_private(x.is_private).word(language),
+                x.answer_chance
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    Ok(text_message(text, chat_id, message_id))
+}
+
+async fn pack_access<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let pack = chat_pack(pool, chat_db_id).await?;
+    let is_private = match command_container.argument {
+        Argument::Empty => {
+            return Ok(text_message(
+                format!(
+                    "{}: {}",
+                    translate(language, Phrase::PackAccess),
+                    PackVisibility::from_is_private(pack.is_private).word(language)
+                ),
+                chat_id,
+                message_id,
+            ))
+        }
+        Argument::EnumWord(word) => match PackVisibility::from_word(word) {
+            Some(visibility) => visibility == PackVisibility::Private,
+            None => {
+                return Err(ProcessError::Feedback {
+                    message: Phrase::InvalidValue,
+                })
+            }
+        },
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::InvalidValue,
+            })
+        }
+    };
+    match AnswerPack::update_is_private(pool, chat_db_id, is_private).await {
+        Ok(_) => Ok(text_message(
+            translate(language, Phrase::Done),
+            chat_id,
+            message_id,
+        )),
+        Err(err) => {
+            warn!(
+                "update pack access failed with error: {} for {:?}",
+                err, chat_db_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::PackError,
+            })
+        }
+    }
+}
+
+async fn welcome(
+    pool: &PgPool,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let (content_type, (value, file_unique_id, description)) =
+        reply_content(pool, chat_db_id, message.reply().unwrap())?;
+    if content_type == EntityContentType::Text {
+        parse_template(&value).map_err(|message| ProcessError::Feedback { message })?;
+    }
+    match ChatWelcome::save(
+        pool,
+        chat_db_id,
+        (&value, &file_unique_id, &description),
+        &content_type,
+        member_db_id,
+    )
+    .await
+    {
+        Ok(_) => Ok(text_message(
+            translate(language, Phrase::DidIt),
+            message.direct().base.chat.id,
+            message_id,
+        )),
+        Err(err) => {
+            warn!(
+                "save welcome failed with error: {} for {:?}",
+                err, chat_db_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::WelcomeSaveError,
+            })
+        }
+    }
+}
+
+async fn welcome_delay<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let delete_after = match command_container.argument {
+        Argument::Empty => {
+            return Ok(text_message(
+                format!(
+                    "{}: {}",
+                    translate(language, Phrase::WelcomeDelay),
+                    ChatDB::welcome_delete_after(pool, chat_db_id)
+                        .await
+                        .unwrap_or_default()
+                ),
+                chat_id,
+                message_id,
+            ))
+        }
+        Argument::Integer(x) => x as i16,
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::InvalidValue,
+            })
+        }
+    };
+    match ChatDB::update_welcome_delete_after(pool, chat_db_id, delete_after).await {
+        Ok(_) => Ok(text_message(
+            translate(language, Phrase::Done),
+            chat_id,
+            message_id,
+        )),
+        Err(err) => {
+            warn!(
+                "update welcome delay failed with error: {} for {:?}",
+                err, chat_db_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::WelcomeUpdateError,
+            })
+        }
+    }
+}
+
+async fn welcome_bots<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let is_welcome_for_bots = match command_container.argument {
+        Argument::Empty => {
+            let is_welcome_for_bots = ChatDB::is_welcome_for_bots(pool, chat_db_id)
+                .await
+                .unwrap_or_default();
+            return Ok(text_message(
+                format!(
+                    "{}: {}",
+                    translate(language, Phrase::WelcomeBots),
+                    BotWelcome::from_is_welcome_for_bots(is_welcome_for_bots).word(language)
+                ),
+                chat_id,
+                message_id,
+            ));
+        }
+        Argument::EnumWord(word) => match BotWelcome::from_word(word) {
+            Some(bot_welcome) => bot_welcome == BotWelcome::Greet,
+            None => {
+                return Err(ProcessError::Feedback {
+                    message: Phrase::InvalidValue,
+                })
+            }
+        },
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::InvalidValue,
+            })
+        }
+    };
+    match ChatDB::update_is_welcome_for_bots(pool, chat_db_id, is_welcome_for_bots).await {
+        Ok(_) => Ok(text_message(
+            translate(language, Phrase::Done),
+            chat_id,
+            message_id,
+        )),
+        Err(err) => {
+            warn!(
+                "update welcome for bots failed with error: {} for {:?}",
+                err, chat_db_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::WelcomeUpdateError,
+            })
+        }
+    }
+}
+
+async fn pack_chance<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let pack = chat_pack(pool, chat_db_id).await?;
+    let answer_chance = match command_container.argument {
+        Argument::Empty => {
+            return Ok(text_message(
+                format!(
+                    "{}: {}%",
+                    translate(language, Phrase::PackChance),
+                    pack.answer_chance
+                ),
+                chat_id,
+                message_id,
+            ))
+        }
+        Argument::Integer(x) => x as i16,
+        _ => {
+            return Err(ProcessError::Feedback {
+                message: Phrase::InvalidValue,
+            })
+        }
+    };
+    match AnswerPack::update_answer_chance(pool, chat_db_id, answer_chance).await {
+        Ok(_) => Ok(text_message(
+            translate(language, Phrase::Done),
+            chat_id,
+            message_id,
+        )),
+        Err(err) => {
+            warn!(
+                "update pack chance failed with error: {} for {:?}",
+                err, chat_db_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::PackError,
+            })
+        }
+    }
+}
+
+// Chat is subscribed to a private pack only by the author of the pack
+async fn subscribe<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let pack = pack_by_name(pool, &pack_name(command_container)?).await?;
+    if &pack.chat_id == chat_db_id {
+        return Err(ProcessError::Feedback {
+            message: Phrase::OwnPackSubscription,
+        });
+    }
+    if pack.is_private
+        && pack.author_id.as_ref() != Some(member_db_id)
+        && member_role(pool, chat_db_id, member_db_id, chat_id).await < Role::Owner
+    {
+        return Err(ProcessError::Feedback {
+            message: Phrase::PrivatePack,
+        });
+    }
+    match AnswerPack::subscribe(pool, &pack.id, chat_db_id).await {
+        Ok(true) => {
+            invalidate_indexes(pool, chat_db_id);
+            Ok(text_message(
+                translate(language, Phrase::Done),
+                chat_id,
+                message_id,
+            ))
+        }
+        Ok(false) => Err(ProcessError::Feedback {
+            message: Phrase::AlreadySubscribed,
+        }),
+        Err(err) => {
+            warn!("subscribe failed with error: {} for {:?}", err, chat_db_id);
+            Err(ProcessError::Feedback {
+                message: Phrase::PackError,
+            })
+        }
+    }
+}
+
+async fn unsubscribe<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    language: &Language,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<ResponseMessage, ProcessError> {
+    let pack = pack_by_name(pool, &pack_name(command_container)?).await?;
+    match AnswerPack::unsubscribe(pool, &pack.id, chat_db_id).await {
+        Ok(true) => {
+            invalidate_indexes(pool, chat_db_id);
+            Ok(text_message(
+                translate(language, Phrase::Done),
+                chat_id,
+                message_id,
+            ))
+        }
+        Ok(false) => Err(ProcessError::Feedback {
+            message: Phrase::NotSubscribed,
+        }),
+        Err(err) => {
+            warn!(
+                "unsubscribe failed with error: {} for {:?}",
+                err, chat_db_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::PackError,
+            })
+        }
+    }
+}
+
+pub async fn process_command<'a>(
+    tokens: &'a Option<Vec<Token<'a>>>,
+    message: &'a Message,
+    pool: &PgPool,
+    member_db_id: &MemberId,
+    chat_db_id: &ChatId,
+    _chat_to_member_db_id: &ChatToMemberId,
+    is_roll: bool,
+) -> Result<ResponseMessage, ProcessError> {
+    let tokens = match tokens {
+        None => return Err(ProcessError::Next),
         Some(_tokens) if _tokens.is_empty() => return Err(ProcessError::Next),
         Some(_tokens) => _tokens,
     };
@@ -525,64 +2496,410 @@ pub async fn process_command<'a>(
     } else {
         message.direct().base.message_id
     };
-    match parse_command(tokens, message.reply().is_some()) {
-        Ok(command_container) => match &command_container.command {
-            Command::Help => help(&command_container, chat_id, message_id),
-            Command::Who => who(pool, &command_container, chat_db_id, chat_id, message_id).await,
-            Command::AnswerChance => {
-                answer_chance(pool, &command_container, chat_db_id, chat_id, message_id).await
-            }
-            Command::Show => {
-                show_keys(
-                    pool,
-                    chat_db_id,
-                    message.reply().unwrap(),
-                    chat_id,
-                    message_id,
-                )
-                .await
-            }
-            Command::Remember => {
-                remember(
-                    pool,
-                    &command_container,
-                    chat_db_id,
-                    chat_id,
-                    message_id,
-                    message.reply().unwrap(),
-                )
-                .await
-            }
-            Command::Delete => {
-                delete_entity(
-                    pool,
-                    chat_db_id,
+    let command_container = parse_command(tokens, message.reply().is_some())?;
+    check_permission(pool, &command_container, chat_db_id, member_db_id, message).await?;
+    let language = &chat_language(pool, chat_db_id).await.unwrap_or_default();
+    let response = match &command_container.command {
+        Command::Help => help(&command_container, language, chat_id, message_id),
+        Command::Who => who(pool, &command_container, chat_db_id, chat_id, message_id).await,
+        Command::AnswerChance => {
+            answer_chance(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Show => {
+            show_keys(
+                pool,
+                chat_db_id,
+                message.reply().unwrap(),
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Remember | Command::RememberForward | Command::RememberCopy => {
+            remember(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::RememberMedia => {
+            remember_media(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::Delete => {
+            delete_entity(
+                pool,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+                message.reply().unwrap(),
+            )
+            .await
+        }
+        Command::Unlink => {
+            unlink(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::Rename => {
+            rename(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::Move => {
+            move_content(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::Check => {
+            check(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::Add => {
+            add_dictionary_entity(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Dictionary => dictionary(pool, language, chat_db_id, chat_id, message_id).await,
+        Command::DeleteWords => {
+            delete_words(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::ImportWords => {
+            import_words(
+                pool,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+                message.reply().unwrap(),
+            )
+            .await
+        }
+        Command::Export => export(pool, language, chat_db_id, chat_id, message_id).await,
+        Command::CreatePack => {
+            create_pack(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Packs => packs(pool, language, chat_db_id, chat_id, message_id).await,
+        Command::PackAccess => {
+            pack_access(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::PackChance => {
+            pack_chance(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Subscribe => {
+            subscribe(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Unsubscribe => {
+            unsubscribe(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Import => {
+            import(
+                pool,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+                message.reply().unwrap(),
+            )
+            .await
+        }
+        Command::GenerateNonsense => todo!(),
+        Command::MorphDebug => todo!(),
+        Command::Morph => todo!(),
+        Command::Top => todo!(),
+        Command::Couple => couple(pool, &command_container, chat_db_id, chat_id, message_id).await,
+        Command::Channel => todo!(),
+        Command::RandomChance => todo!(),
+        Command::RandomChoose => todo!(),
+        Command::Say => todo!(),
+        Command::Quote => todo!(),
+        Command::Joke => todo!(),
+        Command::Advice => todo!(),
+        Command::Template => {
+            template(
+                pool,
+                &command_container,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::Weight => {
+            weight(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+                message.reply().unwrap(),
+            )
+            .await
+        }
+        Command::Selection => {
+            selection(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Trash => trash(pool, language, chat_db_id, chat_id, message_id).await,
+        Command::History => history(pool, language, chat_db_id, chat_id, message_id).await,
+        Command::Keys => {
+            keys(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::Permission => {
+            permission(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::Restore => {
+            restore(
+                pool,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+                message.reply(),
+            )
+            .await
+        }
+        Command::Purge => {
+            purge(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                member_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::TriggerSimilarity => {
+            trigger_similarity_command(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::Welcome => {
+            welcome(
+                pool,
+                language,
+                chat_db_id,
+                member_db_id,
+                message,
+                message_id,
+            )
+            .await
+        }
+        Command::DeleteWelcome => {
+            if ChatWelcome::delete(pool, chat_db_id).await {
+                Ok(text_message(
+                    translate(language, Phrase::Done),
                     chat_id,
                     message_id,
-                    message.reply().unwrap(),
-                )
-                .await
-            }
-            Command::Check => {
-                check(pool, &command_container, chat_db_id, chat_id, message_id).await
-            }
-            Command::Add => {
-                add_dictionary_entity(pool, &command_container, chat_db_id, chat_id, message_id)
-                    .await
+                ))
+            } else {
+                Err(ProcessError::Feedback {
+                    message: Phrase::NoWelcome,
+                })
             }
-            Command::GenerateNonsense => todo!(),
-            Command::MorphDebug => todo!(),
-            Command::Morph => todo!(),
-            Command::Top => todo!(),
-            Command::Couple => todo!(),
-            Command::Channel => todo!(),
-            Command::RandomChance => todo!(),
-            Command::RandomChoose => todo!(),
-            Command::Say => todo!(),
-            Command::Quote => todo!(),
-            Command::Joke => todo!(),
-            Command::Advice => todo!(),
-        },
-        Err(err) => Err(err),
+        }
+        Command::WelcomeDelay => {
+            welcome_delay(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::WelcomeBots => {
+            welcome_bots(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::WordForms => {
+            word_forms(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+        Command::ChatLanguage => {
+            chat_language_command(
+                pool,
+                &command_container,
+                language,
+                chat_db_id,
+                chat_id,
+                message_id,
+            )
+            .await
+        }
+    };
+    if response.is_ok() && is_setting_change(&command_container) {
+        let text = message.direct().ext.raw_text().unwrap_or_default();
+        log_action(
+            pool,
+            chat_db_id,
+            member_db_id,
+            AuditAction::Setting,
+            source_text(text, &tokens[1..]),
+        )
+        .await;
     }
+    response
 }
@@ -1,6 +1,9 @@
+use crate::common::lexer::stem_text;
 use crate::common::request::MessageExt;
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 
 use sqlx::{query, query_as, Error, FromRow, PgPool, Pool, Postgres, QueryBuilder, Row};
 
@@ -8,7 +11,7 @@ use sqlx::{query, query_as, Error, FromRow, PgPool, Pool, Postgres, QueryBuilder
 #[sqlx(transparent)]
 pub struct ChatToMemberId(i32);
 
-#[derive(Clone, Serialize, Deserialize, Debug, FromRow, sqlx::Type, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, Debug, FromRow, sqlx::Type, PartialEq, Eq, Hash)]
 #[sqlx(transparent)]
 pub struct MemberId(i32);
 
@@ -20,6 +23,10 @@ pub struct AnswerEntityId(i32);
 #[sqlx(transparent)]
 pub struct DictionaryEntityId(i32);
 
+#[derive(Clone, Serialize, Deserialize, Debug, FromRow, sqlx::Type, PartialEq)]
+#[sqlx(transparent)]
+pub struct AnswerPackId(i32);
+
 #[derive(Clone, Serialize, Deserialize, Debug, FromRow)]
 pub struct Member {
     pub id: MemberId,
@@ -30,7 +37,7 @@ pub struct Member {
     pub last_name: String,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, FromRow, sqlx::Type, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, Debug, FromRow, sqlx::Type, PartialEq, Eq, Hash)]
 #[sqlx(transparent)]
 pub struct ChatId(i32);
 
@@ -41,7 +48,7 @@ pub struct Chat {
     pub name: String,
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, sqlx::Type, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, sqlx::Type, Deserialize, Serialize)]
 #[sqlx(
     type_name = "answerentitycontenttypesenum",
     rename_all = "SCREAMING_SNAKE_CASE"
@@ -56,6 +63,19 @@ pub enum EntityContentType {
     Sticker,
     Audio,
     Document,
+    // Album, value is the list of its parts in JSON
+    MediaGroup,
+    // Message of chat, which is forwarded, value is "<chat id>:<message id>"
+    Forward,
+    // Message of chat, which is copied, value is "<chat id>:<message id>"
+    Copy,
+    // Value is emoji of the dice
+    Dice,
+    // Values of the following types are their Telegram objects in JSON
+    Poll,
+    Location,
+    Contact,
+    Venue,
 }
 
 impl EntityContentType {
@@ -70,11 +90,61 @@ impl EntityContentType {
             MessageExt::Animation { .. } => EntityContentType::Animation,
             MessageExt::Document { .. } => EntityContentType::Document,
             MessageExt::Audio { .. } => EntityContentType::Audio,
+            MessageExt::Dice { .. } => EntityContentType::Dice,
+            MessageExt::Poll { .. } => EntityContentType::Poll,
+            MessageExt::Location { .. } => EntityContentType::Location,
+            MessageExt::Contact { .. } => EntityContentType::Contact,
+            MessageExt::Venue { .. } => EntityContentType::Venue,
+            // Such messages are never remembered
+            MessageExt::NewChatMembers { .. }
+            | MessageExt::LeftChatMember { .. }
+            | MessageExt::MigrateToChat { .. }
+            | MessageExt::MigrateFromChat { .. }
+            | MessageExt::Unknown {} => EntityContentType::Text,
+        }
+    }
+
+    pub fn name(&self, language: &Language) -> &'static str {
+        match (self, language) {
+            (EntityContentType::Text, Language::Ru) => "текст",
+            (EntityContentType::Voice, Language::Ru) => "голосовое",
+            (EntityContentType::Picture, Language::Ru) => "картинка",
+            (EntityContentType::Animation, Language::Ru) => "гифка",
+            (EntityContentType::Video, Language::Ru) => "видео",
+            (EntityContentType::VideoNote, Language::Ru) => "кружок",
+            (EntityContentType::Sticker, Language::Ru) => "стикер",
+            (EntityContentType::Audio, Language::Ru) => "аудио",
+            (EntityContentType::Document, Language::Ru) => "документ",
+            (EntityContentType::MediaGroup, Language::Ru) => "альбом",
+            (EntityContentType::Forward, Language::Ru) => "пересылка",
+            (EntityContentType::Copy, Language::Ru) => "копия",
+            (EntityContentType::Dice, Language::Ru) => "кубик",
+            (EntityContentType::Poll, Language::Ru) => "опрос",
+            (EntityContentType::Location, Language::Ru) => "геопозиция",
+            (EntityContentType::Contact, Language::Ru) => "контакт",
+            (EntityContentType::Venue, Language::Ru) => "место",
+            (EntityContentType::Text, Language::En) => "text",
+            (EntityContentType::Voice, Language::En) => "voice",
+            (EntityContentType::Picture, Language::En) => "picture",
+            (EntityContentType::Animation, Language::En) => "gif",
+            (EntityContentType::Video, Language::En) => "video",
+            (EntityContentType::VideoNote, Language::En) => "video note",
+            (EntityContentType::Sticker, Language::En) => "sticker",
+            (EntityContentType::Audio, Language::En) => "audio",
+            (EntityContentType::Document, Language::En) => "document",
+            (EntityContentType::MediaGroup, Language::En) => "album",
+            (EntityContentType::Forward, Language::En) => "forward",
+            (EntityContentType::Copy, Language::En) => "copy",
+            (EntityContentType::Dice, Language::En) => "dice",
+            (EntityContentType::Poll, Language::En) => "poll",
+            (EntityContentType::Location, Language::En) => "location",
+            (EntityContentType::Contact, Language::En) => "contact",
+            (EntityContentType::Venue, Language::En) => "venue",
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, sqlx::Type, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, sqlx::Type, Deserialize, Serialize)]
 #[sqlx(
     type_name = "answerentitytypesenum",
     rename_all = "SCREAMING_SNAKE_CASE"
@@ -82,6 +152,244 @@ impl EntityContentType {
 pub enum EntityReactionType {
     Trigger,
     Substring,
+    // Key is a regular expression, text values can use its captures as $1
+    Regex,
+    // Key is unique id of sticker, gif or photo file or emoji of sticker
+    Media,
+}
+
+impl EntityReactionType {
+    pub fn name(&self, language: &Language) -> &'static str {
+        match (self, language) {
+            (EntityReactionType::Trigger, Language::Ru) => "триггер",
+            (EntityReactionType::Substring, Language::Ru) => "подстрока",
+            (EntityReactionType::Regex, Language::Ru) => "регулярка",
+            (EntityReactionType::Media, Language::Ru) => "медиа",
+            (EntityReactionType::Trigger, Language::En) => "trigger",
+            (EntityReactionType::Substring, Language::En) => "substring",
+            (EntityReactionType::Regex, Language::En) => "regex",
+            (EntityReactionType::Media, Language::En) => "media",
+        }
+    }
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    EnumIter,
+    sqlx::Type,
+    Deserialize,
+    Serialize,
+)]
+#[sqlx(type_name = "chatlanguageenum", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Language {
+    #[default]
+    Ru,
+    En,
+}
+
+impl Language {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::Ru => "ru",
+            Language::En => "en",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Language::iter().find(|x| x.code().eq_ignore_ascii_case(code))
+    }
+}
+
+// Values of chat settings, which are shown and set by words of any language
+pub trait LocalizedWord: IntoEnumIterator {
+    fn word(&self, language: &Language) -> &'static str;
+
+    fn words() -> Vec<&'static str> {
+        Self::iter()
+            .flat_map(|x| Language::iter().map(move |language| x.word(&language)))
+            .collect()
+    }
+
+    fn from_word(word: &str) -> Option<Self> {
+        Self::iter()
+            .find(|x| Language::iter().any(|language| x.word(&language).eq_ignore_ascii_case(word)))
+    }
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    EnumIter,
+    sqlx::Type,
+    Deserialize,
+    Serialize,
+)]
+#[sqlx(type_name = "keynormalizationenum", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum KeyNormalization {
+    // Keys are matched in lowercase only
+    #[default]
+    Lowercase,
+    // Keys are matched by word stems, so different word forms are matched
+    Stem,
+}
+
+impl LocalizedWord for KeyNormalization {
+    fn word(&self, language: &Language) -> &'static str {
+        match (self, language) {
+            (KeyNormalization::Lowercase, Language::Ru) => "выкл",
+            (KeyNormalization::Stem, Language::Ru) => "вкл",
+            (KeyNormalization::Lowercase, Language::En) => "off",
+            (KeyNormalization::Stem, Language::En) => "on",
+        }
+    }
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    EnumIter,
+    sqlx::Type,
+    Deserialize,
+    Serialize,
+)]
+#[sqlx(type_name = "selectionmodeenum", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SelectionMode {
+    // Random variant, variants with greater weight are chosen more often
+    #[default]
+    Weighted,
+    // Variants one after another, the first one goes after the last one
+    Cycle,
+    // Variants one after another, the last one is repeated after the end
+    Story,
+}
+
+impl LocalizedWord for SelectionMode {
+    fn word(&self, language: &Language) -> &'static str {
+        match (self, language) {
+            (SelectionMode::Weighted, Language::Ru) => "случайно",
+            (SelectionMode::Cycle, Language::Ru) => "цикл",
+            (SelectionMode::Story, Language::Ru) => "история",
+            (SelectionMode::Weighted, Language::En) => "random",
+            (SelectionMode::Cycle, Language::En) => "cycle",
+            (SelectionMode::Story, Language::En) => "story",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, EnumIter)]
+pub enum PackVisibility {
+    // Any chat can subscribe to the pack
+    #[default]
+    Public,
+    // Only the author of the pack can subscribe chats to it
+    Private,
+}
+
+impl LocalizedWord for PackVisibility {
+    fn word(&self, language: &Language) -> &'static str {
+        match (self, language) {
+            (PackVisibility::Public, Language::Ru) => "публичный",
+            (PackVisibility::Private, Language::Ru) => "приватный",
+            (PackVisibility::Public, Language::En) => "public",
+            (PackVisibility::Private, Language::En) => "private",
+        }
+    }
+}
+
+impl PackVisibility {
+    pub fn from_is_private(is_private: bool) -> Self {
+        match is_private {
+            true => PackVisibility::Private,
+            false => PackVisibility::Public,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, EnumIter)]
+pub enum BotWelcome {
+    // Bots added to chat are not greeted
+    #[default]
+    Skip,
+    Greet,
+}
+
+impl LocalizedWord for BotWelcome {
+    fn word(&self, language: &Language) -> &'static str {
+        match (self, language) {
+            (BotWelcome::Skip, Language::Ru) => "пропускать",
+            (BotWelcome::Greet, Language::Ru) => "приветствовать",
+            (BotWelcome::Skip, Language::En) => "skip",
+            (BotWelcome::Greet, Language::En) => "greet",
+        }
+    }
+}
+
+impl BotWelcome {
+    pub fn from_is_welcome_for_bots(is_welcome_for_bots: bool) -> Self {
+        match is_welcome_for_bots {
+            true => BotWelcome::Greet,
+            false => BotWelcome::Skip,
+        }
+    }
+}
+
+// Roles are ordered by permissions, every role has permissions of the lower ones
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    Hash,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    sqlx::Type,
+    Deserialize,
+    Serialize,
+)]
+#[sqlx(type_name = "memberroleenum", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Role {
+    #[default]
+    Member,
+    // Member who saved the content, which is edited by command
+    Author,
+    // Administrator of telegram chat
+    Admin,
+    // Owner of the bot from configuration
+    Owner,
+}
+
+impl LocalizedWord for Role {
+    fn word(&self, language: &Language) -> &'static str {
+        match (self, language) {
+            (Role::Member, Language::Ru) => "участник",
+            (Role::Author, Language::Ru) => "автор",
+            (Role::Admin, Language::Ru) => "админ",
+            (Role::Owner, Language::Ru) => "владелец",
+            (Role::Member, Language::En) => "member",
+            (Role::Author, Language::En) => "author",
+            (Role::Admin, Language::En) => "admin",
+            (Role::Owner, Language::En) => "owner",
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, FromRow)]
@@ -94,6 +402,101 @@ pub struct AnswerEntity {
     pub value: String,
     pub description: Option<String>,
     pub file_unique_id: Option<String>,
+    pub weight: i16,
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct DeletedAnswerEntity {
+    pub key: String,
+    pub content_type: EntityContentType,
+    pub value: String,
+    pub deleted_by: Option<MemberId>,
+    pub deleted_at: NaiveDateTime,
+}
+
+// Entities of one key with the same reaction and content types
+#[derive(Clone, Debug, FromRow)]
+pub struct AnswerKeyGroup {
+    // The first entity of group, which identifies it
+    pub id: AnswerEntityId,
+    pub reaction_type: EntityReactionType,
+    pub content_type: EntityContentType,
+    pub key: String,
+    pub count: i64,
+}
+
+// Entity in a chat archive, which can be imported into another chat
+#[derive(Clone, Serialize, Deserialize, Debug, FromRow, PartialEq)]
+pub struct ArchivedAnswerEntity {
+    pub key: String,
+    pub reaction_type: EntityReactionType,
+    pub content_type: EntityContentType,
+    pub value: String,
+    pub description: Option<String>,
+    pub file_unique_id: Option<String>,
+    pub weight: i16,
+    // Media value is a file id, which can be sent only by the exporting bot
+    pub bot_specific: bool,
+}
+
+pub struct AnswerKeySelection;
+
+pub struct CommandPermission;
+
+#[derive(Clone, Copy, Debug, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "auditactionenum", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditAction {
+    Remember,
+    Delete,
+    Restore,
+    Purge,
+    Add,
+    Setting,
+    Unlink,
+    Rename,
+    Move,
+    Import,
+    DeleteWords,
+    ImportArchive,
+}
+
+impl AuditAction {
+    pub fn name(&self, language: &Language) -> &'static str {
+        match (self, language) {
+            (AuditAction::Remember, Language::Ru) => "запомнил",
+            (AuditAction::Delete, Language::Ru) => "удалил",
+            (AuditAction::Restore, Language::Ru) => "восстановил",
+            (AuditAction::Purge, Language::Ru) => "очистил корзину",
+            (AuditAction::Add, Language::Ru) => "добавил бред",
+            (AuditAction::Setting, Language::Ru) => "изменил настройку",
+            (AuditAction::Unlink, Language::Ru) => "отвязал ключи",
+            (AuditAction::Rename, Language::Ru) => "переименовал ключ",
+            (AuditAction::Move, Language::Ru) => "перенес контент",
+            (AuditAction::Import, Language::Ru) => "импортировал бред",
+            (AuditAction::DeleteWords, Language::Ru) => "удалил бред",
+            (AuditAction::ImportArchive, Language::Ru) => "импортировал архив",
+            (AuditAction::Remember, Language::En) => "remembered",
+            (AuditAction::Delete, Language::En) => "deleted",
+            (AuditAction::Restore, Language::En) => "restored",
+            (AuditAction::Purge, Language::En) => "emptied the trash",
+            (AuditAction::Add, Language::En) => "added nonsense",
+            (AuditAction::Setting, Language::En) => "changed a setting",
+            (AuditAction::Unlink, Language::En) => "unlinked keys",
+            (AuditAction::Rename, Language::En) => "renamed a key",
+            (AuditAction::Move, Language::En) => "moved content",
+            (AuditAction::Import, Language::En) => "imported nonsense",
+            (AuditAction::DeleteWords, Language::En) => "deleted nonsense",
+            (AuditAction::ImportArchive, Language::En) => "imported an archive",
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct AuditLogEntry {
+    pub member_id: Option<MemberId>,
+    pub action: AuditAction,
+    pub details: String,
+    pub created_at: NaiveDateTime,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, FromRow)]
@@ -103,6 +506,26 @@ pub struct DictionaryEntity {
     pub value: String,
 }
 
+// Pack content is the content of its source chat, subscribed chats answer with it too
+#[derive(Clone, Debug, FromRow)]
+pub struct AnswerPack {
+    pub id: AnswerPackId,
+    pub chat_id: ChatId,
+    pub name: String,
+    pub author_id: Option<MemberId>,
+    pub is_private: bool,
+    pub answer_chance: i16,
+}
+
+// Content, which is sent to new members of chat
+#[derive(Clone, Debug, FromRow)]
+pub struct ChatWelcome {
+    pub chat_id: ChatId,
+    pub content_type: EntityContentType,
+    pub value: String,
+    pub description: Option<String>,
+}
+
 impl ChatId {
     #[allow(dead_code)]
     pub fn new(v: i32) -> Self {
@@ -110,6 +533,18 @@ impl ChatId {
     }
 }
 
+impl AnswerEntityId {
+    pub fn new(v: i32) -> Self {
+        AnswerEntityId(v)
+    }
+}
+
+impl std::fmt::Display for AnswerEntityId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl MemberId {
     #[allow(dead_code)]
     pub fn new(v: i32) -> Self {
@@ -135,6 +570,40 @@ impl Member {
             .ok()
     }
 
+    pub async fn one_in_chat_by_username(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        username: &str,
+    ) -> Option<Member> {
+        query_as::<_, Member>(
+            "SELECT members.id, members.member_id, members.is_bot, members.username, members.last_name, members.first_name \
+        FROM members JOIN chats_to_members ON chats_to_members.member_id = members.id \
+        WHERE chats_to_members.chat_id = $1 AND lower(members.username) = lower($2);",
+        )
+        .bind(chat_id)
+        .bind(username)
+        .fetch_one(pool)
+        .await
+        .ok()
+    }
+
+    pub async fn one_in_chat_by_member_id(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        member_id: i64,
+    ) -> Option<Member> {
+        query_as::<_, Member>(
+            "SELECT members.id, members.member_id, members.is_bot, members.username, members.last_name, members.first_name \
+        FROM members JOIN chats_to_members ON chats_to_members.member_id = members.id \
+        WHERE chats_to_members.chat_id = $1 AND members.member_id = $2;",
+        )
+        .bind(chat_id)
+        .bind(member_id)
+        .fetch_one(pool)
+        .await
+        .ok()
+    }
+
     pub async fn update_names(
         pool: &Pool<Postgres>,
         member_id: i64,
@@ -143,7 +612,7 @@ impl Member {
         last_name: &str,
     ) -> Result<MemberId, Error> {
         query("UPDATE members SET username = $1, first_name = $2, last_name = $3, updated_at = now() \
-        WHERE member_id = $4\
+        WHERE member_id = $4 \
         RETURNING id;")
             .bind(username)
             .bind(first_name)
@@ -183,7 +652,7 @@ impl Member {
         chat_id: &ChatId,
     ) -> Option<ChatToMemberId> {
         query(
-            "UPDATE chats_to_members SET updated_at=now() \
+            "UPDATE chats_to_members SET updated_at=now(), is_active = true \
         WHERE member_id = $1 \
         AND chat_id = $2 \
         RETURNING id;",
@@ -213,10 +682,28 @@ impl Member {
         .map(|x| x.get::<ChatToMemberId, _>("id"))
     }
 
+    // Member, who left chat, is active again after its next message
+    pub async fn leave_chat(
+        pool: &Pool<Postgres>,
+        member_id: &MemberId,
+        chat_id: &ChatId,
+    ) -> Result<(), Error> {
+        query(
+            "UPDATE chats_to_members SET is_active = false, updated_at = now() \
+        WHERE member_id = $1 AND chat_id = $2;",
+        )
+        .bind(member_id)
+        .bind(chat_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+    }
+
     pub async fn chat_members(pool: &Pool<Postgres>, chat_id: &ChatId) -> Vec<MemberId> {
         query(
             "SELECT chats_to_members.member_id FROM chats_to_members JOIN members on members.id = chats_to_members.member_id \
-        WHERE chats_to_members.chat_id = $1 AND chats_to_members.updated_at >= now() - INTERVAL '30 DAYS' AND members.is_bot is false",
+        WHERE chats_to_members.chat_id = $1 AND chats_to_members.updated_at >= now() - INTERVAL '30 DAYS' \
+        AND chats_to_members.is_active AND members.is_bot is false",
         )
         .bind(chat_id)
         .fetch_all(pool)
@@ -268,6 +755,155 @@ impl Chat {
             .map(|x| x.get::<ChatId, _>("id"))
     }
 
+    pub async fn update_is_active(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+        is_active: bool,
+    ) -> Result<(), Error> {
+        query("UPDATE chats SET is_active = $2, updated_at = now() WHERE id = $1;")
+            .bind(chat_id)
+            .bind(is_active)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+
+    // Data of the old chat is moved to the new telegram chat id. The new chat can be already
+    // created by its first messages, then all its data is merged into the old one, which
+    // replaces it. Data set in the new chat is newer, so it replaces conflicting data of the old
+    // one. Returns ids of the old chat and of the replaced new one or None, if the old chat
+    // is unknown or already migrated
+    pub async fn migrate(
+        pool: &Pool<Postgres>,
+        old_chat_id: i64,
+        new_chat_id: i64,
+    ) -> Result<Option<(ChatId, Option<ChatId>)>, Error> {
+        let mut transaction = pool.begin().await?;
+        let Some(old_id) = query("SELECT id FROM chats WHERE chat_id = $1;")
+            .bind(old_chat_id)
+            .fetch_optional(&mut *transaction)
+            .await?
+            .map(|x| x.get::<ChatId, _>("id"))
+        else {
+            return Ok(None);
+        };
+        let new_id = query("SELECT id FROM chats WHERE chat_id = $1;")
+            .bind(new_chat_id)
+            .fetch_optional(&mut *transaction)
+            .await?
+            .map(|x| x.get::<ChatId, _>("id"));
+        if let Some(new_id) = &new_id {
+            // Settings are stored in chat row, a setting of the new chat is taken, if it differs
+            // from the value of a just created chat
+            query(
+                "UPDATE chats SET \
+                language = CASE WHEN n.language <> 'RU' \
+                    THEN n.language ELSE chats.language END, \
+                key_normalization = CASE WHEN n.key_normalization <> 'LOWERCASE' \
+                    THEN n.key_normalization ELSE chats.key_normalization END, \
+                trigger_similarity = CASE WHEN n.trigger_similarity <> 100 \
+                    THEN n.trigger_similarity ELSE chats.trigger_similarity END, \
+                morph_answer_chance = CASE WHEN n.morph_answer_chance <> 15 \
+                    THEN n.morph_answer_chance ELSE chats.morph_answer_chance END, \
+                substring_answer_chance = CASE WHEN n.substring_answer_chance <> 15 \
+                    THEN n.substring_answer_chance ELSE chats.substring_answer_chance END, \
+                welcome_delete_after = CASE WHEN n.welcome_delete_after <> 0 \
+                    THEN n.welcome_delete_after ELSE chats.welcome_delete_after END, \
+                is_welcome_for_bots = chats.is_welcome_for_bots OR n.is_welcome_for_bots \
+                FROM chats n WHERE chats.id = $1 AND n.id = $2;",
+            )
+            .bind(&old_id)
+            .bind(new_id)
+            .execute(&mut *transaction)
+            .await?;
+            // Members are kept with ids of the old chat
+            query(
+                "UPDATE chats_to_members SET chat_id = $1 \
+                WHERE chat_id = $2 AND member_id NOT IN (\
+                    SELECT member_id FROM chats_to_members \
+                    WHERE chat_id = $1 AND member_id IS NOT NULL\
+                );",
+            )
+            .bind(&old_id)
+            .bind(new_id)
+            .execute(&mut *transaction)
+            .await?;
+            // Pack of the old chat stays, subscribers of the pack of the new chat are moved to it
+            query(
+                "UPDATE answer_packs SET chat_id = $1 \
+                WHERE chat_id = $2 AND NOT EXISTS (SELECT 1 FROM answer_packs WHERE chat_id = $1);",
+            )
+            .bind(&old_id)
+            .bind(new_id)
+            .execute(&mut *transaction)
+            .await?;
+            query(
+                "INSERT INTO answer_packs_to_chats (pack_id, chat_id, created_at) \
+                SELECT o.id, s.chat_id, s.created_at FROM answer_packs_to_chats s \
+                JOIN answer_packs n ON n.id = s.pack_id AND n.chat_id = $2 \
+                JOIN answer_packs o ON o.chat_id = $1 \
+                ON CONFLICT (pack_id, chat_id) DO NOTHING;",
+            )
+            .bind(&old_id)
+            .bind(new_id)
+            .execute(&mut *transaction)
+            .await?;
+            // Rows with unique keys in chat are moved with replacement of the old ones
+            for (table, keys) in [
+                ("answer_packs_to_chats", "pack_id"),
+                ("answer_key_selections", "key, reaction_type"),
+                ("command_permissions", "command"),
+                ("dictionary_entities", "value"),
+                ("chat_welcomes", ""),
+            ] {
+                let conflict = keys
+                    .split(", ")
+                    .filter(|x| !x.is_empty())
+                    .map(|x| format!(" AND o.{x} = n.{x}"))
+                    .collect::<String>();
+                query(&format!(
+                    "DELETE FROM {table} o USING {table} n \
+                    WHERE o.chat_id = $1 AND n.chat_id = $2{conflict};"
+                ))
+                .bind(&old_id)
+                .bind(new_id)
+                .execute(&mut *transaction)
+                .await?;
+                query(&format!("UPDATE {table} SET chat_id = $1 WHERE chat_id = $2;"))
+                    .bind(&old_id)
+                    .bind(new_id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+            for table in ["answer_entities", "audit_log"] {
+                query(&format!("UPDATE {table} SET chat_id = $1 WHERE chat_id = $2;"))
+                    .bind(&old_id)
+                    .bind(new_id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+            // The old chat can be subscribed to the pack of the new one, which is its own now
+            query(
+                "DELETE FROM answer_packs_to_chats s USING answer_packs p \
+                WHERE s.pack_id = p.id AND s.chat_id = p.chat_id AND s.chat_id = $1;",
+            )
+            .bind(&old_id)
+            .execute(&mut *transaction)
+            .await?;
+            query("DELETE FROM chats WHERE id = $1;")
+                .bind(new_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+        query("UPDATE chats SET chat_id = $2, is_active = true, updated_at = now() WHERE id = $1;")
+            .bind(&old_id)
+            .bind(new_chat_id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(Some((old_id, new_id)))
+    }
+
     pub async fn substring_answer_chance(pool: &Pool<Postgres>, chat_id: &ChatId) -> Option<i16> {
         query("SELECT substring_answer_chance FROM chats WHERE id = $1")
             .bind(chat_id)
@@ -299,6 +935,119 @@ impl Chat {
             .map(|x| x.get::<ChatId, _>("id"))
     }
 
+    pub async fn language(pool: &Pool<Postgres>, chat_id: &ChatId) -> Option<Language> {
+        query("SELECT language FROM chats WHERE id = $1")
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .ok()
+            .map(|x| x.get::<Language, _>("language"))
+    }
+
+    pub async fn update_language(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+        language: &Language,
+    ) -> Result<ChatId, Error> {
+        query("UPDATE chats set language = $1 WHERE id = $2 RETURNING id;")
+            .bind(language)
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<ChatId, _>("id"))
+    }
+
+    pub async fn key_normalization(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+    ) -> Option<KeyNormalization> {
+        query("SELECT key_normalization FROM chats WHERE id = $1")
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .ok()
+            .map(|x| x.get::<KeyNormalization, _>("key_normalization"))
+    }
+
+    pub async fn update_key_normalization(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+        key_normalization: &KeyNormalization,
+    ) -> Result<ChatId, Error> {
+        query("UPDATE chats set key_normalization = $1 WHERE id = $2 RETURNING id;")
+            .bind(key_normalization)
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<ChatId, _>("id"))
+    }
+
+    pub async fn trigger_similarity(pool: &Pool<Postgres>, chat_id: &ChatId) -> Option<i16> {
+        query("SELECT trigger_similarity FROM chats WHERE id = $1")
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .ok()
+            .map(|x| x.get::<i16, _>("trigger_similarity"))
+    }
+
+    pub async fn update_trigger_similarity(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+        trigger_similarity: i16,
+    ) -> Result<ChatId, Error> {
+        query("UPDATE chats set trigger_similarity = $1 WHERE id = $2 RETURNING id;")
+            .bind(trigger_similarity)
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<ChatId, _>("id"))
+    }
+
+    pub async fn welcome_delete_after(pool: &Pool<Postgres>, chat_id: &ChatId) -> Option<i16> {
+        query("SELECT welcome_delete_after FROM chats WHERE id = $1")
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .ok()
+            .map(|x| x.get::<i16, _>("welcome_delete_after"))
+    }
+
+    pub async fn update_welcome_delete_after(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+        welcome_delete_after: i16,
+    ) -> Result<ChatId, Error> {
+        query("UPDATE chats set welcome_delete_after = $1 WHERE id = $2 RETURNING id;")
+            .bind(welcome_delete_after)
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<ChatId, _>("id"))
+    }
+
+    pub async fn is_welcome_for_bots(pool: &Pool<Postgres>, chat_id: &ChatId) -> Option<bool> {
+        query("SELECT is_welcome_for_bots FROM chats WHERE id = $1")
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .ok()
+            .map(|x| x.get::<bool, _>("is_welcome_for_bots"))
+    }
+
+    pub async fn update_is_welcome_for_bots(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+        is_welcome_for_bots: bool,
+    ) -> Result<ChatId, Error> {
+        query("UPDATE chats set is_welcome_for_bots = $1 WHERE id = $2 RETURNING id;")
+            .bind(is_welcome_for_bots)
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<ChatId, _>("id"))
+    }
+
     pub async fn update_morph_answer_chance(
         pool: &Pool<Postgres>,
         chat_id: &ChatId,
@@ -320,54 +1069,129 @@ impl AnswerEntity {
         keys: &[String],
         entity_reaction_type: &EntityReactionType,
     ) -> Vec<AnswerEntity> {
-        query_as::<_, AnswerEntity>(&format!(
+        query_as::<_, AnswerEntity>(
             "SELECT id, chat_id, content_type, value, \
-            reaction_type, key, description, file_unique_id \
+            reaction_type, key, description, file_unique_id, weight \
             FROM answer_entities \
-            WHERE key in ({key_list}) and chat_id = $1 and reaction_type = $2;",
-            key_list = keys.iter().fold(String::new(), |s, k| if s.is_empty() {
-                s + "'" + k + "'"
-            } else {
-                s + "," + "'" + k + "'"
-            })
-        ))
+            WHERE key = ANY($3) and reaction_type = $2 and is_active \
+            and (chat_id = $1 OR chat_id IN (SELECT answer_packs.chat_id FROM answer_packs \
+            JOIN answer_packs_to_chats ON answer_packs_to_chats.pack_id = answer_packs.id \
+            WHERE answer_packs_to_chats.chat_id = $1)) \
+            ORDER BY id;",
+        )
         .bind(chat_id)
         .bind(entity_reaction_type)
+        .bind(keys)
         .fetch_all(pool)
         .await
         .unwrap_or_default()
     }
 
-    pub async fn find_keys_by_value(
-        pool: &Pool<Postgres>,
-        chat_id: &ChatId,
-        value: &String,
-    ) -> Vec<String> {
-        query("SELECT key FROM answer_entities WHERE value = $1 AND chat_id = $2")
-            .bind(value)
-            .bind(chat_id)
-            .fetch_all(pool)
-            .await
-            .unwrap_or_default()
-            .iter()
-            .map(|x| x.get::<String, _>("key"))
-            .collect::<Vec<String>>()
-    }
-
-    pub async fn find_keys_by_file_unique_id(
+    pub async fn find_values_by_stemmed_keys(
         pool: &Pool<Postgres>,
         chat_id: &ChatId,
-        file_unique_id: &String,
-    ) -> Vec<String> {
-        query("SELECT key FROM answer_entities WHERE file_unique_id = $1 AND chat_id = $2")
+        stemmed_keys: &[String],
+        entity_reaction_type: &EntityReactionType,
+    ) -> Vec<AnswerEntity> {
+        query_as::<_, AnswerEntity>(
+            "SELECT id, chat_id, content_type, value, \
+            reaction_type, key, description, file_unique_id, weight \
+            FROM answer_entities \
+            WHERE stemmed_key = ANY($3) and reaction_type = $2 and is_active \
+            and (chat_id = $1 OR chat_id IN (SELECT answer_packs.chat_id FROM answer_packs \
+            JOIN answer_packs_to_chats ON answer_packs_to_chats.pack_id = answer_packs.id \
+            WHERE answer_packs_to_chats.chat_id = $1)) \
+            ORDER BY id;",
+        )
+        .bind(chat_id)
+        .bind(entity_reaction_type)
+        .bind(stemmed_keys)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    pub async fn substring_keys(
+        pool: &PgPool,
+        chat_ids: &[ChatId],
+    ) -> Vec<(String, Option<String>)> {
+        query(
+            "SELECT DISTINCT key, stemmed_key FROM answer_entities \
+            WHERE chat_id = ANY($1) AND reaction_type = $2 AND is_active;",
+        )
+        .bind(chat_ids)
+        .bind(EntityReactionType::Substring)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| (x.get::<String, _>("key"), x.get::<Option<String>, _>("stemmed_key")))
+        .collect()
+    }
+
+    pub async fn trigger_keys(
+        pool: &PgPool,
+        chat_ids: &[ChatId],
+    ) -> Vec<(String, Option<String>)> {
+        query(
+            "SELECT DISTINCT key, stemmed_key FROM answer_entities \
+            WHERE chat_id = ANY($1) AND reaction_type = $2 AND is_active;",
+        )
+        .bind(chat_ids)
+        .bind(EntityReactionType::Trigger)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| (x.get::<String, _>("key"), x.get::<Option<String>, _>("stemmed_key")))
+        .collect()
+    }
+
+    pub async fn regex_keys(pool: &PgPool, chat_ids: &[ChatId]) -> Vec<String> {
+        query(
+            "SELECT DISTINCT key FROM answer_entities \
+            WHERE chat_id = ANY($1) AND reaction_type = $2 AND is_active;",
+        )
+        .bind(chat_ids)
+        .bind(EntityReactionType::Regex)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| x.get::<String, _>("key"))
+        .collect()
+    }
+
+    pub async fn find_keys_by_value(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+        value: &String,
+    ) -> Vec<(String, Option<MemberId>)> {
+        query("SELECT key, author_id FROM answer_entities WHERE value = $1 AND chat_id = $2 AND is_active ORDER BY id")
+            .bind(value)
+            .bind(chat_id)
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|x| (x.get::<String, _>("key"), x.get::<Option<MemberId>, _>("author_id")))
+            .collect()
+    }
+
+    pub async fn find_keys_by_file_unique_id(
+        pool: &Pool<Postgres>,
+        chat_id: &ChatId,
+        file_unique_id: &String,
+    ) -> Vec<(String, Option<MemberId>)> {
+        query("SELECT key, author_id FROM answer_entities WHERE file_unique_id = $1 AND chat_id = $2 AND is_active ORDER BY id")
             .bind(file_unique_id)
             .bind(chat_id)
             .fetch_all(pool)
             .await
             .unwrap_or_default()
             .iter()
-            .map(|x| x.get::<String, _>("key"))
-            .collect::<Vec<String>>()
+            .map(|x| (x.get::<String, _>("key"), x.get::<Option<MemberId>, _>("author_id")))
+            .collect()
     }
     pub async fn existed_keys(
         pool: &PgPool,
@@ -377,7 +1201,7 @@ impl AnswerEntity {
         entity_content_type: &EntityContentType,
         entity_reaction_type: &EntityReactionType,
     ) -> Vec<String> {
-        query("SELECT key FROM answer_entities WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) AND content_type = $4 AND reaction_type = $5")
+        query("SELECT key FROM answer_entities WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) AND content_type = $4 AND reaction_type = $5 AND is_active")
             .bind(chat_id)
             .bind(value)
             .bind(file_unique_id)
@@ -391,23 +1215,215 @@ impl AnswerEntity {
             .collect::<Vec<String>>()
     }
 
+    // Entities are only deactivated, so they can be restored from the trash until purge
     pub async fn delete(
         pool: &PgPool,
         chat_id: &ChatId,
         value: &String,
         file_unique_id: &Option<String>,
         entity_content_type: &EntityContentType,
+        deleted_by: &MemberId,
     ) -> Vec<String> {
         query(
-            "DELETE FROM answer_entities \
+            "UPDATE answer_entities \
+        SET is_active = false, deleted_at = $5, deleted_by = $6, updated_at = $5 \
+        WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) \
+        AND content_type = $4 AND is_active \
+        RETURNING key;",
+        )
+        .bind(chat_id)
+        .bind(value)
+        .bind(file_unique_id)
+        .bind(entity_content_type)
+        .bind(Utc::now().naive_utc())
+        .bind(deleted_by)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| x.get::<String, _>("key"))
+        .collect::<Vec<String>>()
+    }
+
+    // Only the given keys of content are deleted, other keys keep it
+    pub async fn unlink(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        content: (&String, &Option<String>, &EntityContentType),
+        keys: &[String],
+        deleted_by: &MemberId,
+    ) -> Vec<String> {
+        let (value, file_unique_id, entity_content_type) = content;
+        query(
+            "UPDATE answer_entities \
+        SET is_active = false, deleted_at = $6, deleted_by = $7, updated_at = $6 \
+        WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) \
+        AND content_type = $4 AND key = ANY($5) AND is_active \
+        RETURNING key;",
+        )
+        .bind(chat_id)
+        .bind(value)
+        .bind(file_unique_id)
+        .bind(entity_content_type)
+        .bind(keys)
+        .bind(Utc::now().naive_utc())
+        .bind(deleted_by)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| x.get::<String, _>("key"))
+        .collect::<Vec<String>>()
+    }
+
+    // Content, which already exists on the new key, is deleted from the old key
+    // instead of being duplicated. Returns number of entities removed from the old key
+    pub async fn rename_key(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        old_key: &String,
+        new_key: &String,
+        entity_reaction_type: &EntityReactionType,
+        renamed_by: &MemberId,
+    ) -> Result<u64, Error> {
+        let mut transaction = pool.begin().await?;
+        let now = Utc::now().naive_utc();
+        let merged = query(
+            "UPDATE answer_entities a \
+            SET is_active = false, deleted_at = $4, deleted_by = $5, updated_at = $4 \
+            WHERE a.chat_id = $1 AND a.key = $2 AND a.reaction_type = $6 AND a.is_active \
+            AND EXISTS (\
+                SELECT 1 FROM answer_entities d WHERE d.chat_id = a.chat_id AND d.key = $3 \
+                AND d.reaction_type = a.reaction_type AND d.content_type = a.content_type \
+                AND (d.value = a.value OR d.file_unique_id = a.file_unique_id) AND d.is_active\
+            );",
+        )
+        .bind(chat_id)
+        .bind(old_key)
+        .bind(new_key)
+        .bind(now)
+        .bind(renamed_by)
+        .bind(entity_reaction_type)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        let renamed = query(
+            "UPDATE answer_entities SET key = $3, stemmed_key = $4, updated_at = $5 \
+            WHERE chat_id = $1 AND key = $2 AND reaction_type = $6 AND is_active;",
+        )
+        .bind(chat_id)
+        .bind(old_key)
+        .bind(new_key)
+        .bind(stem_text(new_key))
+        .bind(now)
+        .bind(entity_reaction_type)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        // Selection mode of the new key wins, if both keys have it
+        query(
+            "UPDATE answer_key_selections s SET key = $3 \
+            WHERE s.chat_id = $1 AND s.key = $2 AND s.reaction_type = $4 AND NOT EXISTS (\
+                SELECT 1 FROM answer_key_selections n WHERE n.chat_id = s.chat_id \
+                AND n.key = $3 AND n.reaction_type = s.reaction_type\
+            );",
+        )
+        .bind(chat_id)
+        .bind(old_key)
+        .bind(new_key)
+        .bind(entity_reaction_type)
+        .execute(&mut *transaction)
+        .await?;
+        query(
+            "DELETE FROM answer_key_selections \
+            WHERE chat_id = $1 AND key = $2 AND reaction_type = $3;",
+        )
+        .bind(chat_id)
+        .bind(old_key)
+        .bind(entity_reaction_type)
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(merged + renamed)
+    }
+
+    // Moves only the given content, other content of the old key stays on it.
+    // Returns number of entities removed from the old key
+    pub async fn move_content(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        content: (&String, &Option<String>, &EntityContentType),
+        old_key: &String,
+        new_key: &String,
+        entity_reaction_type: &EntityReactionType,
+        moved_by: &MemberId,
+    ) -> Result<u64, Error> {
+        let (value, file_unique_id, entity_content_type) = content;
+        let mut transaction = pool.begin().await?;
+        let now = Utc::now().naive_utc();
+        let merged = query(
+            "UPDATE answer_entities a \
+            SET is_active = false, deleted_at = $7, deleted_by = $8, updated_at = $7 \
+            WHERE a.chat_id = $1 AND (a.value = $2 OR a.file_unique_id = $3) \
+            AND a.content_type = $4 AND a.key = $5 AND a.reaction_type = $9 AND a.is_active \
+            AND EXISTS (\
+                SELECT 1 FROM answer_entities d WHERE d.chat_id = a.chat_id AND d.key = $6 \
+                AND d.reaction_type = a.reaction_type AND d.content_type = a.content_type \
+                AND (d.value = a.value OR d.file_unique_id = a.file_unique_id) AND d.is_active\
+            );",
+        )
+        .bind(chat_id)
+        .bind(value)
+        .bind(file_unique_id)
+        .bind(entity_content_type)
+        .bind(old_key)
+        .bind(new_key)
+        .bind(now)
+        .bind(moved_by)
+        .bind(entity_reaction_type)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        let moved = query(
+            "UPDATE answer_entities SET key = $6, stemmed_key = $7, updated_at = $8 \
+            WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) \
+            AND content_type = $4 AND key = $5 AND reaction_type = $9 AND is_active;",
+        )
+        .bind(chat_id)
+        .bind(value)
+        .bind(file_unique_id)
+        .bind(entity_content_type)
+        .bind(old_key)
+        .bind(new_key)
+        .bind(stem_text(new_key))
+        .bind(now)
+        .bind(entity_reaction_type)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        transaction.commit().await?;
+        Ok(merged + moved)
+    }
+
+    pub async fn restore(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        value: &String,
+        file_unique_id: &Option<String>,
+        entity_content_type: &EntityContentType,
+    ) -> Vec<String> {
+        query(
+            "UPDATE answer_entities \
+        SET is_active = true, deleted_at = NULL, deleted_by = NULL, updated_at = $5 \
         WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) \
-        AND content_type = $4 \
+        AND content_type = $4 AND NOT is_active AND deleted_at IS NOT NULL \
         RETURNING key;",
         )
         .bind(chat_id)
         .bind(value)
         .bind(file_unique_id)
         .bind(entity_content_type)
+        .bind(Utc::now().naive_utc())
         .fetch_all(pool)
         .await
         .unwrap_or_default()
@@ -416,6 +1432,274 @@ impl AnswerEntity {
         .collect::<Vec<String>>()
     }
 
+    // Restores entities of the last deletion in chat
+    pub async fn restore_last_deleted(pool: &PgPool, chat_id: &ChatId) -> Vec<String> {
+        query(
+            "UPDATE answer_entities \
+        SET is_active = true, deleted_at = NULL, deleted_by = NULL, updated_at = $2 \
+        WHERE chat_id = $1 AND NOT is_active AND deleted_at = (\
+            SELECT max(deleted_at) FROM answer_entities WHERE chat_id = $1 AND NOT is_active\
+        ) \
+        RETURNING key;",
+        )
+        .bind(chat_id)
+        .bind(Utc::now().naive_utc())
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| x.get::<String, _>("key"))
+        .collect::<Vec<String>>()
+    }
+
+    pub async fn deleted(pool: &PgPool, chat_id: &ChatId, limit: i64) -> Vec<DeletedAnswerEntity> {
+        query_as::<_, DeletedAnswerEntity>(
+            "SELECT key, content_type, value, deleted_by, deleted_at FROM answer_entities \
+            WHERE chat_id = $1 AND NOT is_active AND deleted_at IS NOT NULL \
+            ORDER BY deleted_at DESC, id LIMIT $2;",
+        )
+        .bind(chat_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    // Entities deleted before the time are removed permanently
+    pub async fn purge(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        deleted_before: NaiveDateTime,
+    ) -> Result<u64, Error> {
+        query(
+            "DELETE FROM answer_entities \
+            WHERE chat_id = $1 AND NOT is_active AND deleted_at < $2;",
+        )
+        .bind(chat_id)
+        .bind(deleted_before)
+        .execute(pool)
+        .await
+        .map(|x| x.rows_affected())
+    }
+
+    // Type of forward or copy saved from the source "<chat id>:<message id>",
+    // active and recently saved ones first
+    pub async fn source_content_type(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        source: &String,
+    ) -> Option<EntityContentType> {
+        query(
+            "SELECT content_type FROM answer_entities \
+            WHERE chat_id = $1 AND value = $2 AND content_type IN ('FORWARD', 'COPY') \
+            ORDER BY is_active DESC, id DESC LIMIT 1;",
+        )
+        .bind(chat_id)
+        .bind(source)
+        .fetch_optional(pool)
+        .await
+        .ok()?
+        .map(|x| x.get::<EntityContentType, _>("content_type"))
+    }
+
+    // Authors of both active and deleted entities with the content
+    pub async fn authors(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        value: &String,
+        file_unique_id: &Option<String>,
+        entity_content_type: &EntityContentType,
+    ) -> Vec<Option<MemberId>> {
+        query(
+            "SELECT author_id FROM answer_entities \
+            WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) AND content_type = $4;",
+        )
+        .bind(chat_id)
+        .bind(value)
+        .bind(file_unique_id)
+        .bind(entity_content_type)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| x.get::<Option<MemberId>, _>("author_id"))
+        .collect()
+    }
+
+    // Keys are matched by ILIKE pattern
+    pub async fn key_groups(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        pattern: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<AnswerKeyGroup> {
+        query_as::<_, AnswerKeyGroup>(
+            "SELECT min(id) AS id, reaction_type, content_type, key, count(*) AS count \
+            FROM answer_entities WHERE chat_id = $1 AND is_active AND key ILIKE $2 \
+            GROUP BY reaction_type, content_type, key \
+            ORDER BY reaction_type, content_type, key LIMIT $3 OFFSET $4;",
+        )
+        .bind(chat_id)
+        .bind(pattern)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    pub async fn key_groups_count(pool: &PgPool, chat_id: &ChatId, pattern: &str) -> i64 {
+        query(
+            "SELECT count(DISTINCT (reaction_type, content_type, key)) AS count \
+            FROM answer_entities WHERE chat_id = $1 AND is_active AND key ILIKE $2;",
+        )
+        .bind(chat_id)
+        .bind(pattern)
+        .fetch_one(pool)
+        .await
+        .map(|x| x.get::<i64, _>("count"))
+        .unwrap_or_default()
+    }
+
+    // Entities of the group identified by its first entity
+    pub async fn key_group_values(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        id: &AnswerEntityId,
+    ) -> Vec<AnswerEntity> {
+        query_as::<_, AnswerEntity>(
+            "SELECT a.id, a.chat_id, a.content_type, a.reaction_type, a.key, a.value, \
+            a.description, a.file_unique_id, a.weight FROM answer_entities a \
+            JOIN answer_entities g ON g.chat_id = a.chat_id AND g.key = a.key \
+            AND g.reaction_type = a.reaction_type AND g.content_type = a.content_type \
+            WHERE g.id = $1 AND a.chat_id = $2 AND a.is_active ORDER BY a.id;",
+        )
+        .bind(id)
+        .bind(chat_id)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    pub async fn weights(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        value: &String,
+        file_unique_id: &Option<String>,
+        entity_content_type: &EntityContentType,
+    ) -> Vec<(String, i16)> {
+        query(
+            "SELECT key, weight FROM answer_entities \
+            WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) AND content_type = $4 \
+            AND is_active ORDER BY key;",
+        )
+        .bind(chat_id)
+        .bind(value)
+        .bind(file_unique_id)
+        .bind(entity_content_type)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| (x.get::<String, _>("key"), x.get::<i16, _>("weight")))
+        .collect()
+    }
+
+    pub async fn update_weight(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        value: &String,
+        file_unique_id: &Option<String>,
+        entity_content_type: &EntityContentType,
+        weight: i16,
+    ) -> Result<Vec<String>, Error> {
+        query(
+            "UPDATE answer_entities SET weight = $5, updated_at = $6 \
+            WHERE chat_id = $1 AND (value = $2 OR file_unique_id = $3) AND content_type = $4 \
+            AND is_active RETURNING key;",
+        )
+        .bind(chat_id)
+        .bind(value)
+        .bind(file_unique_id)
+        .bind(entity_content_type)
+        .bind(weight)
+        .bind(Utc::now())
+        .fetch_all(pool)
+        .await
+        .map(|x| x.iter().map(|x| x.get::<String, _>("key")).collect())
+    }
+
+    pub async fn archived(pool: &PgPool, chat_id: &ChatId) -> Vec<ArchivedAnswerEntity> {
+        query_as::<_, ArchivedAnswerEntity>(
+            "SELECT key, reaction_type, content_type, value, description, file_unique_id, weight, \
+            content_type IN ('VOICE', 'PICTURE', 'ANIMATION', 'VIDEO', 'VIDEO_NOTE', 'STICKER', \
+            'AUDIO', 'DOCUMENT', 'MEDIA_GROUP') AS bot_specific \
+            FROM answer_entities WHERE chat_id = $1 AND is_active ORDER BY id;",
+        )
+        .bind(chat_id)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    // Entities, which already exist in chat, are skipped. Returns number of added entities
+    pub async fn bulk_import(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        entities: &[ArchivedAnswerEntity],
+        author_id: &MemberId,
+    ) -> Result<u64, Error> {
+        let mut transaction = pool.begin().await?;
+        let mut added = 0;
+        // Chunks keep the number of query parameters under the postgres limit
+        for chunk in entities.chunks(1000) {
+            let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+                "WITH new (key, stemmed_key, value, file_unique_id, description, content_type, reaction_type, weight) AS (",
+            );
+            query_builder.push_values(chunk, |mut binds, entity| {
+                binds
+                    .push_bind(&entity.key)
+                    .push_bind(stem_text(&entity.key))
+                    .push_bind(&entity.value)
+                    .push_bind(&entity.file_unique_id)
+                    .push_bind(&entity.description)
+                    .push_bind(&entity.content_type)
+                    .push_bind(&entity.reaction_type)
+                    .push_bind(entity.weight);
+            });
+            query_builder.push(
+                ") INSERT INTO answer_entities(key, stemmed_key, value, file_unique_id, description, content_type, reaction_type, weight, chat_id, author_id, is_active, created_at, updated_at) \
+                SELECT n.key, n.stemmed_key, n.value, n.file_unique_id, n.description, n.content_type, n.reaction_type, n.weight, ",
+            );
+            query_builder
+                .push_bind(chat_id)
+                .push(", ")
+                .push_bind(author_id)
+                .push(", true, ")
+                .push_bind(Utc::now())
+                .push(", ")
+                .push_bind(Utc::now());
+            query_builder
+                .push(
+                    " FROM new n WHERE NOT EXISTS (SELECT 1 FROM answer_entities a WHERE a.chat_id = ",
+                )
+                .push_bind(chat_id)
+                .push(
+                    " AND a.key = n.key AND a.reaction_type = n.reaction_type \
+                    AND a.content_type = n.content_type AND a.is_active \
+                    AND (a.value = n.value OR a.file_unique_id = n.file_unique_id))",
+                );
+            added += query_builder
+                .build()
+                .execute(&mut *transaction)
+                .await?
+                .rows_affected();
+        }
+        transaction.commit().await?;
+        Ok(added)
+    }
+
     pub async fn bulk_add_items(
         pool: &PgPool,
         keys: Vec<&String>,
@@ -423,20 +1707,23 @@ impl AnswerEntity {
         content: (&String, &Option<String>, &Option<String>),
         entity_content_type: &EntityContentType,
         entity_reaction_type: &EntityReactionType,
+        author_id: &MemberId,
     ) -> bool {
         let (value, file_unique_id, description) = content;
         let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
-            "INSERT INTO answer_entities(key, value, file_unique_id, description, content_type, reaction_type, chat_id, is_active, created_at, updated_at) "
+            "INSERT INTO answer_entities(key, stemmed_key, value, file_unique_id, description, content_type, reaction_type, chat_id, author_id, is_active, created_at, updated_at) "
         );
         query_builder.push_values(&keys, |mut binds, key| {
             binds
                 .push_bind(key)
+                .push_bind(stem_text(key))
                 .push_bind(value)
                 .push_bind(file_unique_id)
                 .push_bind(description)
                 .push_bind(entity_content_type)
                 .push_bind(entity_reaction_type)
                 .push_bind(chat_db_id)
+                .push_bind(author_id)
                 .push_bind(true)
                 .push_bind(Utc::now())
                 .push_bind(Utc::now());
@@ -446,34 +1733,425 @@ impl AnswerEntity {
     }
 }
 
-impl DictionaryEntity {
-    pub async fn existed_values(
+impl AnswerKeySelection {
+    pub async fn modes(
         pool: &PgPool,
         chat_id: &ChatId,
-    ) -> Vec<String> {
-        query("SELECT value FROM dictionary_entities WHERE chat_id = $1")
+        keys: &[String],
+        entity_reaction_type: &EntityReactionType,
+    ) -> Vec<(String, SelectionMode)> {
+        query(
+            "SELECT key, mode FROM answer_key_selections \
+            WHERE chat_id = $1 AND key = ANY($2) AND reaction_type = $3;",
+        )
+        .bind(chat_id)
+        .bind(keys)
+        .bind(entity_reaction_type)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|x| (x.get::<String, _>("key"), x.get::<SelectionMode, _>("mode")))
+        .collect()
+    }
+
+    // Mode is changed with the position reset, so cycle and story start from the beginning
+    pub async fn update_mode(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        keys: &[String],
+        entity_reaction_type: &EntityReactionType,
+        mode: &SelectionMode,
+    ) -> bool {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO answer_key_selections(chat_id, key, reaction_type, mode, position) ",
+        );
+        query_builder.push_values(keys, |mut binds, key| {
+            binds
+                .push_bind(chat_id)
+                .push_bind(key)
+                .push_bind(entity_reaction_type)
+                .push_bind(mode)
+                .push_bind(0);
+        });
+        query_builder.push(
+            " ON CONFLICT (chat_id, key, reaction_type) \
+            DO UPDATE SET mode = excluded.mode, position = 0",
+        );
+        query_builder.build().execute(pool).await.is_ok()
+    }
+
+    // Number of previous hits of key, the hit is counted atomically
+    pub async fn hit(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        key: &String,
+        entity_reaction_type: &EntityReactionType,
+    ) -> Result<i32, Error> {
+        query(
+            "UPDATE answer_key_selections SET position = position + 1 \
+            WHERE chat_id = $1 AND key = $2 AND reaction_type = $3 \
+            RETURNING position - 1 AS position;",
+        )
+        .bind(chat_id)
+        .bind(key)
+        .bind(entity_reaction_type)
+        .fetch_one(pool)
+        .await
+        .map(|x| x.get::<i32, _>("position"))
+    }
+}
+
+impl CommandPermission {
+    pub async fn roles(pool: &PgPool, chat_id: &ChatId) -> Vec<(String, Role)> {
+        query("SELECT command, role FROM command_permissions WHERE chat_id = $1;")
+            .bind(chat_id)
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|x| (x.get::<String, _>("command"), x.get::<Role, _>("role")))
+            .collect()
+    }
+
+    pub async fn role(pool: &PgPool, chat_id: &ChatId, command: &str) -> Option<Role> {
+        query("SELECT role FROM command_permissions WHERE chat_id = $1 AND command = $2;")
+            .bind(chat_id)
+            .bind(command)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<Role, _>("role"))
+            .ok()
+    }
+
+    pub async fn update_role(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        command: &str,
+        role: &Role,
+    ) -> Result<(), Error> {
+        query(
+            "INSERT INTO command_permissions(chat_id, command, role) VALUES ($1, $2, $3) \
+            ON CONFLICT (chat_id, command) DO UPDATE SET role = excluded.role;",
+        )
+        .bind(chat_id)
+        .bind(command)
+        .bind(role)
+        .execute(pool)
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn delete_role(pool: &PgPool, chat_id: &ChatId, command: &str) -> Result<(), Error> {
+        query("DELETE FROM command_permissions WHERE chat_id = $1 AND command = $2;")
+            .bind(chat_id)
+            .bind(command)
+            .execute(pool)
+            .await
+            .map(|_| ())
+    }
+}
+
+impl DictionaryEntity {
+    pub async fn all_values(pool: &PgPool, chat_id: &ChatId) -> Vec<String> {
+        query("SELECT value FROM dictionary_entities WHERE chat_id = $1 ORDER BY value;")
             .bind(chat_id)
             .fetch_all(pool)
             .await
             .unwrap_or_default()
             .iter()
             .map(|x| x.get::<String, _>("value"))
-            .collect::<Vec<String>>()
+            .collect()
     }
-    
-    pub async fn bulk_add_items(pool: &PgPool, values: Vec<&String>, chat_db_id: &ChatId) -> bool {
+
+    pub async fn values(pool: &PgPool, chat_id: &ChatId, limit: i64, offset: i64) -> Vec<String> {
+        query("SELECT value FROM dictionary_entities WHERE chat_id = $1 ORDER BY value LIMIT $2 OFFSET $3;")
+            .bind(chat_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|x| x.get::<String, _>("value"))
+            .collect()
+    }
+
+    pub async fn count(pool: &PgPool, chat_id: &ChatId) -> i64 {
+        query("SELECT count(*) AS count FROM dictionary_entities WHERE chat_id = $1;")
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<i64, _>("count"))
+            .unwrap_or_default()
+    }
+
+    pub async fn delete(pool: &PgPool, chat_id: &ChatId, values: &[String]) -> Vec<String> {
+        query("DELETE FROM dictionary_entities WHERE chat_id = $1 AND value = ANY($2) RETURNING value;")
+            .bind(chat_id)
+            .bind(values)
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|x| x.get::<String, _>("value"))
+            .collect()
+    }
+
+    // Values existing in chat are skipped, so only the added values are returned
+    pub async fn bulk_add_items(
+        pool: &PgPool,
+        values: Vec<&String>,
+        chat_db_id: &ChatId,
+        author_id: &MemberId,
+    ) -> Result<Vec<String>, Error> {
+        if values.is_empty() {
+            return Ok(vec![]);
+        }
         let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
-            "INSERT INTO dictionary_entities(value, chat_id, is_active, created_at, updated_at) ",
+            "INSERT INTO dictionary_entities(value, chat_id, author_id, is_active, created_at, updated_at) ",
         );
         query_builder.push_values(&values, |mut binds, value| {
             binds
                 .push_bind(value)
                 .push_bind(chat_db_id)
+                .push_bind(author_id)
                 .push_bind(true)
                 .push_bind(Utc::now())
                 .push_bind(Utc::now());
         });
-        let insert_query = query_builder.build();
-        insert_query.fetch_all(pool).await.is_ok()
+        query_builder.push(" ON CONFLICT (chat_id, value) DO NOTHING RETURNING value");
+        query_builder
+            .build()
+            .fetch_all(pool)
+            .await
+            .map(|x| x.iter().map(|x| x.get::<String, _>("value")).collect())
+    }
+}
+
+const ANSWER_PACK_COLUMNS: &str = "answer_packs.id, answer_packs.chat_id, answer_packs.name, \
+    answer_packs.author_id, answer_packs.is_private, answer_packs.answer_chance";
+
+impl AnswerPack {
+    pub async fn create(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        name: &str,
+        author_id: &MemberId,
+    ) -> Result<AnswerPackId, Error> {
+        query(
+            "INSERT INTO answer_packs (chat_id, name, author_id, created_at) \
+            VALUES ($1, $2, $3, $4) RETURNING id;",
+        )
+        .bind(chat_id)
+        .bind(name)
+        .bind(author_id)
+        .bind(Utc::now().naive_utc())
+        .fetch_one(pool)
+        .await
+        .map(|x| x.get::<AnswerPackId, _>("id"))
+    }
+
+    pub async fn by_chat(pool: &PgPool, chat_id: &ChatId) -> Option<AnswerPack> {
+        query_as::<_, AnswerPack>(&format!(
+            "SELECT {ANSWER_PACK_COLUMNS} FROM answer_packs WHERE chat_id = $1;"
+        ))
+        .bind(chat_id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    pub async fn by_name(pool: &PgPool, name: &str) -> Option<AnswerPack> {
+        query_as::<_, AnswerPack>(&format!(
+            "SELECT {ANSWER_PACK_COLUMNS} FROM answer_packs WHERE name = $1;"
+        ))
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    // Public packs, pack of the chat and packs the chat is subscribed to
+    pub async fn visible(pool: &PgPool, chat_id: &ChatId) -> Vec<AnswerPack> {
+        query_as::<_, AnswerPack>(&format!(
+            "SELECT {ANSWER_PACK_COLUMNS} FROM answer_packs \
+            WHERE NOT is_private OR chat_id = $1 \
+            OR id IN (SELECT pack_id FROM answer_packs_to_chats WHERE chat_id = $1) \
+            ORDER BY name;"
+        ))
+        .bind(chat_id)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    pub async fn subscribed(pool: &PgPool, chat_id: &ChatId) -> Vec<AnswerPack> {
+        query_as::<_, AnswerPack>(&format!(
+            "SELECT {ANSWER_PACK_COLUMNS} FROM answer_packs \
+            JOIN answer_packs_to_chats ON answer_packs_to_chats.pack_id = answer_packs.id \
+            WHERE answer_packs_to_chats.chat_id = $1 ORDER BY answer_packs.name;"
+        ))
+        .bind(chat_id)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    // Chats whose content is used for answers in the chat: the chat itself and its packs
+    pub async fn source_chats(pool: &PgPool, chat_id: &ChatId) -> Vec<ChatId> {
+        let mut chat_ids = vec![chat_id.clone()];
+        chat_ids.extend(
+            AnswerPack::subscribed(pool, chat_id)
+                .await
+                .into_iter()
+                .map(|x| x.chat_id),
+        );
+        chat_ids
+    }
+
+    pub async fn update_is_private(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        is_private: bool,
+    ) -> Result<AnswerPackId, Error> {
+        query("UPDATE answer_packs SET is_private = $1 WHERE chat_id = $2 RETURNING id;")
+            .bind(is_private)
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<AnswerPackId, _>("id"))
+    }
+
+    pub async fn update_answer_chance(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        answer_chance: i16,
+    ) -> Result<AnswerPackId, Error> {
+        query("UPDATE answer_packs SET answer_chance = $1 WHERE chat_id = $2 RETURNING id;")
+            .bind(answer_chance)
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .map(|x| x.get::<AnswerPackId, _>("id"))
+    }
+
+    // False is returned, if the chat is already subscribed
+    pub async fn subscribe(
+        pool: &PgPool,
+        pack_id: &AnswerPackId,
+        chat_id: &ChatId,
+    ) -> Result<bool, Error> {
+        query(
+            "INSERT INTO answer_packs_to_chats (pack_id, chat_id, created_at) VALUES ($1, $2, $3) \
+            ON CONFLICT DO NOTHING;",
+        )
+        .bind(pack_id)
+        .bind(chat_id)
+        .bind(Utc::now().naive_utc())
+        .execute(pool)
+        .await
+        .map(|x| x.rows_affected() > 0)
+    }
+
+    // False is returned, if the chat is not subscribed
+    pub async fn unsubscribe(
+        pool: &PgPool,
+        pack_id: &AnswerPackId,
+        chat_id: &ChatId,
+    ) -> Result<bool, Error> {
+        query("DELETE FROM answer_packs_to_chats WHERE pack_id = $1 AND chat_id = $2;")
+            .bind(pack_id)
+            .bind(chat_id)
+            .execute(pool)
+            .await
+            .map(|x| x.rows_affected() > 0)
+    }
+}
+
+impl AuditLogEntry {
+    pub async fn add(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        member_id: &MemberId,
+        action: &AuditAction,
+        details: &str,
+    ) -> bool {
+        query(
+            "INSERT INTO audit_log(chat_id, member_id, action, details, created_at) \
+            VALUES ($1, $2, $3, $4, $5);",
+        )
+        .bind(chat_id)
+        .bind(member_id)
+        .bind(action)
+        .bind(details)
+        .bind(Utc::now().naive_utc())
+        .execute(pool)
+        .await
+        .is_ok()
+    }
+
+    pub async fn recent(pool: &PgPool, chat_id: &ChatId, limit: i64) -> Vec<AuditLogEntry> {
+        query_as::<_, AuditLogEntry>(
+            "SELECT member_id, action, details, created_at FROM audit_log \
+            WHERE chat_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2;",
+        )
+        .bind(chat_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+    }
+}
+
+impl ChatWelcome {
+    pub async fn by_chat(pool: &PgPool, chat_id: &ChatId) -> Option<ChatWelcome> {
+        query_as::<_, ChatWelcome>(
+            "SELECT chat_id, content_type, value, description FROM chat_welcomes \
+            WHERE chat_id = $1;",
+        )
+        .bind(chat_id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+    }
+
+    // Chat has only one welcome, so the new one replaces the old one
+    pub async fn save(
+        pool: &PgPool,
+        chat_id: &ChatId,
+        content: (&String, &Option<String>, &Option<String>),
+        content_type: &EntityContentType,
+        author_id: &MemberId,
+    ) -> Result<(), Error> {
+        let (value, file_unique_id, description) = content;
+        query(
+            "INSERT INTO chat_welcomes \
+            (chat_id, content_type, value, file_unique_id, description, author_id, updated_at) \
+            VALUES ($1, $2, $3, $4, $5, $6, now()) \
+            ON CONFLICT (chat_id) DO UPDATE SET content_type = $2, value = $3, \
+            file_unique_id = $4, description = $5, author_id = $6, updated_at = now();",
+        )
+        .bind(chat_id)
+        .bind(content_type)
+        .bind(value)
+        .bind(file_unique_id)
+        .bind(description)
+        .bind(author_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn delete(pool: &PgPool, chat_id: &ChatId) -> bool {
+        query("DELETE FROM chat_welcomes WHERE chat_id = $1;")
+            .bind(chat_id)
+            .execute(pool)
+            .await
+            .map(|x| x.rows_affected() > 0)
+            .unwrap_or_default()
     }
 }
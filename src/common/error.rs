@@ -1,9 +1,11 @@
+use crate::common::locale::Phrase;
+
 #[derive(Debug, PartialEq)]
-pub enum ProcessError<'a> {
+pub enum ProcessError {
     // Force stop process message
     Stop,
     // Skip current process go to next process
     Next,
     // Force stop process message for send feedback to user
-    Feedback{message: &'a str},
+    Feedback{message: Phrase},
 }
@@ -5,18 +5,22 @@ use log::warn;
 use serde_json::Value;
 use sqlx::postgres::PgPool;
 
-use crate::common::message_service::process_message;
-use crate::common::request::RequestPayload;
+use crate::common::message_service::{process_chat_member_update, process_message};
+use crate::common::request::TelegramUpdate;
 
 pub async fn telegram_webhook_route(
     State(pool): State<PgPool>,
     Json(payload): Json<Value>,
 ) -> StatusCode {
-    match serde_json::from_value::<RequestPayload>(payload) {
-        Ok(request_payload) => {
+    match serde_json::from_value::<TelegramUpdate>(payload) {
+        Ok(TelegramUpdate::Message(request_payload)) => {
             process_message(&pool, &request_payload).await;
             StatusCode::OK
         }
+        Ok(TelegramUpdate::MyChatMember { my_chat_member, .. }) => {
+            process_chat_member_update(&pool, &my_chat_member).await;
+            StatusCode::OK
+        }
         Err(err) => {
             warn!("Receipt not supported body. {err}");
             StatusCode::OK
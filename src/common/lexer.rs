@@ -1,11 +1,38 @@
+use std::collections::HashSet;
+
+use once_cell::sync::Lazy;
+use rust_stemmers::{Algorithm, Stemmer};
 use unicase::UniCase;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::common::request::{MessageEntity, MessageEntityKind};
+use Token::{
+    BotCommand, Emoji, Hashtag, Mention, Newline, Number, Punctuation, Quoted, Symbol, TextMention,
+    Url, Word,
+};
 
-use Token::{Newline, Punctuation, Symbol, Word};
+const ESCAPE: char = '\\';
+const ESCAPABLE: [char; 5] = [',', '"', '«', '»', ESCAPE];
 
 #[derive(Copy, Clone, Debug)]
 pub enum Token<'a> {
     Newline,
     Word(&'a str),
+    // Text in "..." or «...» including quotes with original spacing
+    Quoted(&'a str),
+    // @username with "@"
+    Mention(&'a str),
+    // Mention of user without username, known only from message entities: text and user id
+    TextMention(&'a str, i64),
+    // /command or /command@bot_name, known only from message entities
+    BotCommand(&'a str),
+    Url(&'a str),
+    // #tag with "#"
+    Hashtag(&'a str),
+    // One or several emoji graphemes without spaces
+    Emoji(&'a str),
+    // Integer or decimal number with optional sign: -1, 2.5, 3,14
+    Number(&'a str),
     Punctuation(&'a str),
     Symbol(&'a str),
 }
@@ -13,7 +40,13 @@ pub enum Token<'a> {
 impl<'a> PartialEq for Token<'a> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (&Word(a), &Word(b)) => UniCase::new(a) == UniCase::new(b),
+            (&Word(a), &Word(b))
+            | (&Quoted(a), &Quoted(b))
+            | (&Mention(a), &Mention(b))
+            | (&BotCommand(a), &BotCommand(b))
+            | (&Hashtag(a), &Hashtag(b)) => UniCase::new(a) == UniCase::new(b),
+            (&TextMention(_, a), &TextMention(_, b)) => a == b,
+            (&Url(a), &Url(b)) | (&Emoji(a), &Emoji(b)) | (&Number(a), &Number(b)) => a == b,
             (&Punctuation(a), &Punctuation(b)) => a == b,
             (&Symbol(a), &Symbol(b)) => a == b,
             (&Newline, &Newline) => true,
@@ -22,14 +55,76 @@ impl<'a> PartialEq for Token<'a> {
     }
 }
 
+impl<'a> Token<'a> {
+    // Slice of original text the token is made of, newline has no such slice
+    fn source(&self) -> Option<&'a str> {
+        match *self {
+            Newline => None,
+            Word(x)
+            | Quoted(x)
+            | Mention(x)
+            | TextMention(x, _)
+            | BotCommand(x)
+            | Url(x)
+            | Hashtag(x)
+            | Emoji(x)
+            | Number(x)
+            | Punctuation(x)
+            | Symbol(x) => Some(x),
+        }
+    }
+}
+
+// Original text from the first to the last token including spacing between them
+pub fn source_text<'a>(text: &'a str, tokens: &[Token<'a>]) -> &'a str {
+    let offset = |x: &str| {
+        (x.as_ptr() as usize)
+            .checked_sub(text.as_ptr() as usize)
+            .filter(|offset| offset + x.len() <= text.len())
+    };
+    let sources = tokens
+        .iter()
+        .filter_map(Token::source)
+        .collect::<Vec<&str>>();
+    match (
+        sources.first().and_then(|x| offset(x)),
+        sources
+            .last()
+            .and_then(|x| offset(x).map(|offset| offset + x.len())),
+    ) {
+        (Some(start), Some(end)) if start < end => &text[start..end],
+        _ => "",
+    }
+}
+
 pub fn tokens_to_string<'a>(tokens: &'a [Token<'a>], remove_question_mark: bool) -> String {
     tokens
         .iter()
         .enumerate()
         .fold(String::new(), |result, (index, token)| match token {
             Newline => result + "\n",
-            Word(word) if result.is_empty() => result + word,
-            Word(word) => result + " " + word,
+            Quoted(word) if result.is_empty() => result + word,
+            Quoted(word) => result + " " + word,
+            Word(word)
+            | Mention(word)
+            | TextMention(word, _)
+            | BotCommand(word)
+            | Url(word)
+            | Hashtag(word)
+            | Emoji(word)
+            | Number(word)
+                if result.is_empty() =>
+            {
+                result + word
+            }
+            Word(word)
+            | Mention(word)
+            | TextMention(word, _)
+            | BotCommand(word)
+            | Url(word)
+            | Hashtag(word)
+            | Emoji(word)
+            | Number(word) => result + " " + word,
             Punctuation(punct)
                 if remove_question_mark && punct.eq(&"?") && tokens.len() - 1 == index =>
             {
@@ -45,6 +140,91 @@ pub fn normalize_text(text: String) -> String {
     text.to_lowercase().replace('ё', "е")
 }
 
+static RUSSIAN_STEMMER: Lazy<Stemmer> = Lazy::new(|| Stemmer::create(Algorithm::Russian));
+
+// Same character set is used by the stemmed keys backfill migration
+fn is_stem_char(x: char) -> bool {
+    x.is_ascii_digit() || x.is_ascii_lowercase() || ('а'..='я').contains(&x) || x == 'ё' || x == '_'
+}
+
+// Reduces every word of normalized text to its stem: "холодным пивом" -> "холодн пив".
+// Text without words is returned as is
+pub fn stem_text(text: &str) -> String {
+    let stemmed = text
+        .split(|x| !is_stem_char(x))
+        .filter(|x| !x.is_empty())
+        .map(|x| RUSSIAN_STEMMER.stem(x))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if stemmed.is_empty() {
+        text.to_string()
+    } else {
+        stemmed
+    }
+}
+
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    text.split(|x: char| !(x.is_alphanumeric() || x == '_'))
+        .filter(|x| !x.is_empty())
+        .flat_map(|word| {
+            // Words are padded like in pg_trgm: two spaces before and one after
+            let chars = [' ', ' ']
+                .into_iter()
+                .chain(word.chars())
+                .chain([' '])
+                .collect::<Vec<char>>();
+            chars
+                .windows(3)
+                .map(|x| [x[0], x[1], x[2]])
+                .collect::<Vec<[char; 3]>>()
+        })
+        .collect()
+}
+
+// Share of common trigrams of normalized texts from 0 to 1, same as pg_trgm similarity
+pub fn trigram_similarity(left: &str, right: &str) -> f64 {
+    let (left, right) = (trigrams(left), trigrams(right));
+    let common = left.intersection(&right).count();
+    match left.len() + right.len() - common {
+        0 => 0.0,
+        total => common as f64 / total as f64,
+    }
+}
+
+pub fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(char) = chars.next() {
+        match chars.peek() {
+            Some(next) if char == ESCAPE && ESCAPABLE.contains(next) => {
+                result.push(*next);
+                chars.next();
+            }
+            _ => result.push(char),
+        }
+    }
+    result
+}
+
+// Text inside quotes without quotes and escape symbols
+pub fn unquote(quoted: &str) -> String {
+    let mut chars = quoted.chars();
+    chars.next();
+    chars.next_back();
+    unescape(chars.as_str())
+}
+
+fn join_to_last(result: &mut Vec<String>, value: String) {
+    match result.len() {
+        0 => result.push(value),
+        n => {
+            result[n - 1] = normalize_text(result[n - 1].to_string())
+                + if result[n - 1].is_empty() { "" } else { " " }
+                + &value
+        }
+    }
+}
+
 pub fn joined_string<'a>(tokens: &'a [Token<'a>]) -> Vec<String> {
     let mut result: Vec<String> = vec![];
     tokens.iter().for_each(|x| match x {
@@ -52,50 +232,237 @@ pub fn joined_string<'a>(tokens: &'a [Token<'a>]) -> Vec<String> {
             0 => result.push(String::from("\n")),
             n => result[n - 1] = String::from(&result[n - 1]) + "\n",
         },
-        Punctuation(",") | Symbol(",") => result.push(String::from("")),
-        Symbol(x) | Word(x) => match result.len() {
-            0 => result.push(normalize_text(x.to_string())),
-            n => {
-                result[n - 1] = normalize_text(result[n - 1].to_string())
-                    + if result[n - 1].is_empty() { "" } else { " " }
-                    + &normalize_text(x.to_string())
+        Symbol(",") => result.push(String::from("")),
+        Quoted(quoted) => join_to_last(&mut result, normalize_text(unquote(quoted))),
+        // Comma glued to other punctuation, like in "(\\w+),", separates values too
+        Punctuation(x) => x.split(',').enumerate().for_each(|(index, part)| {
+            if index > 0 {
+                result.push(String::from(""));
             }
-        },
-        Punctuation(x) => match result.len() {
-            0 => result.push(String::from(*x)),
-            n => result[n - 1] = String::from(&result[n - 1]) + x,
-        },
+            match result.len() {
+                _ if part.is_empty() => {}
+                0 => result.push(String::from(part)),
+                n => result[n - 1] = String::from(&result[n - 1]) + part,
+            }
+        }),
+        Symbol(x)
+        | Word(x)
+        | Mention(x)
+        | TextMention(x, _)
+        | BotCommand(x)
+        | Url(x)
+        | Hashtag(x)
+        | Emoji(x)
+        | Number(x) => join_to_last(&mut result, normalize_text(unescape(x))),
     });
     result
 }
 
-pub fn tokenize(text: &str) -> Vec<Token> {
-    use Token::*;
-    let mut token_list = vec![];
-    text.split_inclusive('\n')
-        .flat_map(|line| line.split([' ', '\r']))
-        .filter(|words| !words.is_empty())
-        .for_each(|word| match word {
-            "\n" => token_list.push(Newline),
-            x if x.chars().all(|x| !x.is_alphanumeric()) => {
-                token_list.push(Symbol(&word[word.len() - 1..]));
+fn closing_quote(open: char) -> Option<char> {
+    match open {
+        '"' => Some('"'),
+        '«' => Some('»'),
+        _ => None,
+    }
+}
+
+// Quoted text from the start of the text, if it's opened and closed by quotes
+fn quoted_prefix(text: &str) -> Option<&str> {
+    let open = text.chars().next()?;
+    let close = closing_quote(open)?;
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, char)) = chars.next() {
+        match char {
+            ESCAPE => {
+                chars.next();
             }
-            x if x.ends_with('\n') => {
-                token_list.push(Word(&word[..word.len() - 1]));
-                token_list.push(Newline);
+            x if x == close => return Some(&text[..index + x.len_utf8()]),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_emoji(grapheme: &str) -> bool {
+    // Keycap sequences start with a digit or symbol: 1️⃣, #️⃣
+    grapheme.contains('\u{20E3}')
+        || grapheme.chars().next().is_some_and(|x| {
+            matches!(
+                x as u32,
+                0x1F000..=0x1FAFF
+                    | 0x2600..=0x27BF
+                    | 0x2300..=0x23FF
+                    | 0x2B00..=0x2BFF
+                    | 0x2190..=0x21FF
+                    | 0x3030
+                    | 0x303D
+                    | 0x3297
+                    | 0x3299
+                    | 0x00A9
+                    | 0x00AE
+                    | 0x203C
+                    | 0x2049
+                    | 0x2122
+                    | 0x2139
+            )
+        })
+}
+
+fn is_punctuation(grapheme: &str) -> bool {
+    !grapheme.chars().any(|x| x.is_alphanumeric() || x == '_') && !is_emoji(grapheme)
+}
+
+fn is_tagged(word: &str, tag: char) -> bool {
+    word.strip_prefix(tag)
+        .is_some_and(|x| !x.is_empty() && x.chars().all(|x| x.is_alphanumeric() || x == '_'))
+}
+
+fn is_number(word: &str) -> bool {
+    word.strip_prefix(['-', '+'])
+        .unwrap_or(word)
+        .splitn(2, ['.', ','])
+        .all(|x| !x.is_empty() && x.chars().all(|x| x.is_ascii_digit()))
+}
+
+fn is_url(word: &str) -> bool {
+    let lowercase = word.to_lowercase();
+    ["http://", "https://", "www."]
+        .iter()
+        .any(|x| lowercase.len() > x.len() && lowercase.starts_with(x))
+}
+
+fn word_token(word: &str) -> Token<'_> {
+    match word {
+        x if x.graphemes(true).all(is_emoji) => Emoji(x),
+        x if is_number(x) => Number(x),
+        x if is_url(x) => Url(x),
+        x if is_tagged(x, '@') && x.is_ascii() => Mention(x),
+        x if is_tagged(x, '#') => Hashtag(x),
+        x => Word(x),
+    }
+}
+
+// Splits punctuation at the end of word by graphemes: "привет?!" -> ("привет", "?!")
+fn split_punctuation(word: &str) -> (&str, &str) {
+    let end = word
+        .grapheme_indices(true)
+        .rev()
+        .take_while(|(_, grapheme)| is_punctuation(grapheme))
+        .last()
+        .map_or(word.len(), |(index, _)| index);
+    // Escaped punctuation is a part of word: "ну\,"
+    if word[end..].contains(ESCAPE) {
+        (word, "")
+    } else {
+        word.split_at(end)
+    }
+}
+
+fn push_word<'a>(token_list: &mut Vec<Token<'a>>, chunk: &'a str) {
+    let (word, has_newline) = match chunk.strip_suffix('\n') {
+        Some(word) => (word, true),
+        None => (chunk, false),
+    };
+    match word {
+        "" => {}
+        x if x.graphemes(true).all(is_punctuation) => token_list.push(Symbol(x)),
+        x => {
+            let (word, punctuation) = split_punctuation(x);
+            token_list.push(word_token(word));
+            if !punctuation.is_empty() {
+                token_list.push(Punctuation(punctuation));
             }
-            x if !x.chars().last().unwrap().is_alphanumeric() => {
-                token_list.push(Word(&word[..word.len() - 1]));
-                token_list.push(Punctuation(&word[word.len() - 1..]));
+        }
+    }
+    if has_newline {
+        token_list.push(Newline);
+    }
+}
+
+pub fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut token_list = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find(|x| x != ' ' && x != '\r') {
+        rest = &rest[start..];
+        if let Some(quoted) = quoted_prefix(rest) {
+            token_list.push(Quoted(quoted));
+            rest = &rest[quoted.len()..];
+            // Punctuation attached to closing quote: "привет",
+            let end = rest.find([' ', '\r', '\n']).unwrap_or(rest.len());
+            if end > 0 && rest[..end].chars().all(|x| !x.is_alphanumeric()) {
+                token_list.push(Punctuation(&rest[..end]));
+                rest = &rest[end..];
             }
-            _ => token_list.push(Word(word)),
-        });
+            continue;
+        }
+        let end = match rest.find([' ', '\r', '\n']) {
+            Some(index) if rest[index..].starts_with('\n') => index + 1,
+            Some(index) => index,
+            None => rest.len(),
+        };
+        push_word(&mut token_list, &rest[..end]);
+        rest = &rest[end..];
+    }
+    token_list
+}
+
+fn entity_token<'a>(entity: &MessageEntity, text: &'a str) -> Option<Token<'a>> {
+    match (&entity.kind, &entity.user) {
+        (MessageEntityKind::TextMention, Some(user)) => Some(TextMention(text, user.id)),
+        (MessageEntityKind::Mention, _) => Some(Mention(text)),
+        (MessageEntityKind::BotCommand, _) => Some(BotCommand(text)),
+        (MessageEntityKind::Url, _) => Some(Url(text)),
+        (MessageEntityKind::Hashtag, _) => Some(Hashtag(text)),
+        _ => None,
+    }
+}
+
+fn push_entity_gap<'a>(token_list: &mut Vec<Token<'a>>, gap: &'a str, after_entity: bool) {
+    let mut gap_tokens = tokenize(gap);
+    // Punctuation attached to entity: @username,
+    if let (true, Some(&Symbol(x))) = (after_entity, gap_tokens.first()) {
+        if !gap.starts_with([' ', '\r', '\n']) {
+            gap_tokens[0] = Punctuation(x);
+        }
+    }
+    token_list.append(&mut gap_tokens);
+}
+
+// Text covered by Telegram entities becomes a single token of the entity kind,
+// the rest of text is tokenized as usual
+pub fn tokenize_with_entities<'a>(text: &'a str, entities: &[MessageEntity]) -> Vec<Token<'a>> {
+    let mut spans = entities
+        .iter()
+        .filter_map(|entity| {
+            let range = entity.byte_range(text)?;
+            entity_token(entity, &text[range.clone()]).map(|token| (range, token))
+        })
+        .collect::<Vec<_>>();
+    spans.sort_by_key(|(range, _)| range.start);
+    let mut token_list = vec![];
+    let mut position = 0;
+    for (range, token) in spans {
+        // Overlapped entities are ignored
+        if range.start < position {
+            continue;
+        }
+        push_entity_gap(&mut token_list, &text[position..range.start], position > 0);
+        token_list.push(token);
+        position = range.end;
+    }
+    push_entity_gap(&mut token_list, &text[position..], position > 0);
     token_list
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::common::lexer::{tokenize, Token::*};
+    use proptest::prelude::*;
+
+    use crate::common::lexer::{
+        joined_string, source_text, stem_text, tokenize, tokenize_with_entities,
+        tokens_to_string, trigram_similarity, Token::*,
+    };
+    use crate::common::request::{MessageEntity, MessageEntityKind, User};
 
     #[test]
     fn test_text_equals() {
@@ -145,8 +512,220 @@ mod tests {
                 ],
             ),
             ("", vec![]),
+            (
+                "\"ну,  привет\", пока",
+                vec![Quoted("\"ну,  привет\""), Punctuation(","), Word("пока")],
+            ),
+            (
+                "«ну, привет» пока",
+                vec![Quoted("«ну, привет»"), Word("пока")],
+            ),
+            (
+                "\"ну, \\\"привет\\\"\"",
+                vec![Quoted("\"ну, \\\"привет\\\"\"")],
+            ),
+            (
+                "\"незакрытая кавычка",
+                vec![Word("\"незакрытая"), Word("кавычка")],
+            ),
+            ("ну\\, привет", vec![Word("ну\\,"), Word("привет")]),
+            (
+                "@Bread_Bot, #хлеб!",
+                vec![
+                    Mention("@bread_bot"),
+                    Punctuation(","),
+                    Hashtag("#ХЛЕБ"),
+                    Punctuation("!"),
+                ],
+            ),
+            (
+                "https://example.com/?q=1. www.example.com",
+                vec![
+                    Url("https://example.com/?q=1"),
+                    Punctuation("."),
+                    Url("www.example.com"),
+                ],
+            ),
+            (
+                "-20 2.5 3,14 1.",
+                vec![
+                    Number("-20"),
+                    Number("2.5"),
+                    Number("3,14"),
+                    Number("1"),
+                    Punctuation("."),
+                ],
+            ),
+            (
+                "привет🍞🍞 👍🏻🇷🇺!",
+                vec![Word("привет🍞🍞"), Emoji("👍🏻🇷🇺"), Punctuation("!")],
+            ),
+            ("1️⃣ ❤️", vec![Emoji("1️⃣"), Emoji("❤️")]),
+            ("—… ?!", vec![Symbol("—…"), Symbol("?!")]),
+            ("что?!…\n", vec![Word("что"), Punctuation("?!…"), Newline]),
         ]
         .iter()
         .for_each(|(input, output)| assert_eq!(tokenize(input), *output));
     }
+
+    #[test]
+    fn test_joined_string() {
+        [
+            ("раз, два три", vec!["раз", "два три"]),
+            ("\"Ну, привет\", пока", vec!["ну, привет", "пока"]),
+            ("«ну, привет», пока", vec!["ну, привет", "пока"]),
+            ("ну\\, привет, пока", vec!["ну, привет", "пока"]),
+            ("\"скажи \\\"ну, да\\\"\"", vec!["скажи \"ну, да\""]),
+        ]
+        .iter()
+        .for_each(|(input, output)| assert_eq!(joined_string(&tokenize(input)), *output));
+    }
+
+    #[test]
+    fn test_tokens_to_string() {
+        [
+            (
+                "он сказал:  \"ну,   привет\"",
+                "он сказал: \"ну,   привет\"",
+            ),
+            ("«ну,  привет»?", "«ну,  привет»?"),
+        ]
+        .iter()
+        .for_each(|(input, output)| assert_eq!(tokens_to_string(&tokenize(input), false), *output));
+    }
+
+    #[test]
+    fn test_stem_text() {
+        [
+            ("пиво", "пив"),
+            ("пива", "пив"),
+            ("пивом", "пив"),
+            ("холодным пивом!", "холодн пив"),
+            ("ну, привет", "ну привет"),
+            ("beers 42", "beers 42"),
+            ("🍞", "🍞"),
+        ]
+        .iter()
+        .for_each(|(input, output)| assert_eq!(stem_text(input), *output));
+    }
+
+    #[test]
+    fn test_trigram_similarity() {
+        [
+            ("привет всем", "привет всем", 1.0),
+            ("привет всм", "привет всем", 9.0 / 14.0),
+            ("word", "two words", 4.0 / 11.0),
+            ("кот", "собака", 0.0),
+            ("", "", 0.0),
+        ]
+        .iter()
+        .for_each(|(left, right, output)| {
+            assert!((trigram_similarity(left, right) - output).abs() < f64::EPSILON)
+        });
+    }
+
+    #[test]
+    fn test_source_text() {
+        [
+            (
+                "хлеб запомни регулярку ^привет,  (\\w+)$",
+                3,
+                "^привет,  (\\w+)$",
+            ),
+            ("хлеб проверь  \"как  дела\"?\nну", 2, "\"как  дела\"?\nну"),
+            ("хлеб проверь", 2, ""),
+        ]
+        .iter()
+        .for_each(|(text, skip, output)| {
+            assert_eq!(source_text(text, &tokenize(text)[*skip..]), *output)
+        });
+    }
+
+    fn entity(kind: MessageEntityKind, offset: usize, length: usize) -> MessageEntity {
+        MessageEntity {
+            kind,
+            offset,
+            length,
+            url: None,
+            user: Some(User {
+                id: 42,
+                is_bot: false,
+                first_name: Some(String::from("Вася")),
+                last_name: None,
+                username: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_tokenize_with_entities() {
+        [
+            (
+                "хлеб парочка Вася Пупкин, привет",
+                vec![entity(MessageEntityKind::TextMention, 13, 11)],
+                vec![
+                    Word("хлеб"),
+                    Word("парочка"),
+                    TextMention("Вася Пупкин", 42),
+                    Punctuation(","),
+                    Word("привет"),
+                ],
+            ),
+            (
+                "/bread@bread_bot кто 🍞 Вася",
+                vec![
+                    entity(MessageEntityKind::BotCommand, 0, 16),
+                    entity(MessageEntityKind::TextMention, 24, 4),
+                ],
+                vec![
+                    BotCommand("/bread@bread_bot"),
+                    Word("кто"),
+                    Emoji("🍞"),
+                    TextMention("Вася", 42),
+                ],
+            ),
+            (
+                "жирный текст",
+                vec![entity(MessageEntityKind::Bold, 0, 6)],
+                vec![Word("жирный"), Word("текст")],
+            ),
+            (
+                "вне текста",
+                vec![entity(MessageEntityKind::Mention, 5, 20)],
+                vec![Word("вне"), Word("текста")],
+            ),
+        ]
+        .iter()
+        .for_each(|(text, entities, output)| {
+            assert_eq!(tokenize_with_entities(text, entities), *output)
+        });
+    }
+
+    fn canonical_word() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "[а-яёa-z_]{1,8}[?!.,:…]{0,2}",
+            "@[A-Za-z0-9_]{1,10}",
+            "#[а-яa-z0-9_]{1,8}",
+            "https://[a-z]{1,8}\\.[a-z]{2,3}/[a-z0-9?=&]{0,8}",
+            "[+-]?[0-9]{1,4}([.,][0-9]{1,3})?",
+            prop::sample::select(vec!["🍞", "👍🏻", "🇷🇺", "1️⃣", "❤️", "🍞🍞"]).prop_map(String::from),
+            "[-—*/=]{1,2}",
+            "«[а-я]{1,5}( [а-я]{1,5})?»",
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_tokenize_is_stable(text in "(\\PC|\n|\r){0,40}") {
+            let tokens = tokenize(&text);
+            let rendered = tokens_to_string(&tokens, false);
+            prop_assert_eq!(tokenize(&rendered), tokens);
+        }
+
+        #[test]
+        fn test_canonical_round_trip(words in prop::collection::vec(canonical_word(), 1..8)) {
+            let text = words.join(" ");
+            prop_assert_eq!(tokens_to_string(&tokenize(&text), false), text);
+        }
+    }
 }
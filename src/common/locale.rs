@@ -0,0 +1,522 @@
+use crate::common::command_parser::{Command, ARGUMENT_FEEDBACK_MAP, COMMAND_SETTING_MAP};
+use crate::common::db::Language;
+
+// Phrases are identified by stable keys, so a text can be changed
+// without losing its translations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phrase {
+    NeedControlItem,
+    InvalidControlItem,
+    NeedReply,
+    NeedValues,
+    UnsupportedControlItem,
+    UnsupportedContentType,
+    InvalidValue,
+    Done,
+    DidIt,
+    NothingFound,
+    NoKeysFound,
+    AddError,
+    NothingDeleted,
+    DeletedKeys,
+    NoMembersFound,
+    NoSubstringChance,
+    NoMorphChance,
+    SubstringChanceError,
+    MorphChanceError,
+    NoLanguage,
+    LanguageError,
+    ChatLanguage,
+    MemberNotFound,
+    WordForms,
+    NoWordForms,
+    WordFormsError,
+    TriggerSimilarity,
+    SimilarityToTrigger,
+    NoTriggerSimilarity,
+    TriggerSimilarityError,
+    InvalidRegex,
+    TooComplexRegex,
+    UnknownPlaceholder,
+    InvalidTemplateRange,
+    Weight,
+    WeightError,
+    SelectionError,
+    TrashEmpty,
+    NothingRestored,
+    RestoredKeys,
+    PurgedFromTrash,
+    PurgeError,
+    HistoryEmpty,
+    NotEnoughPermissions,
+    PermissionAboveOwn,
+    PermissionError,
+    Page,
+    UnlinkedKeys,
+    RenameError,
+    MoveError,
+    DictionaryEmpty,
+    DeletedWords,
+    FileReadError,
+    AddedWords,
+    Answers,
+    Words,
+    AddedAnswers,
+    ExistedAnswers,
+    ExistedWords,
+    SkippedMedia,
+    SkippedInvalidKeys,
+    SettingsNotChanged,
+    SubstringChance,
+    MorphChance,
+    InvalidArchive,
+    UnsupportedArchiveVersion,
+    ImportError,
+    NeedPackName,
+    ChatHasPack,
+    PackNameTaken,
+    PackNotFound,
+    NoChatPack,
+    PrivatePack,
+    OwnPackSubscription,
+    AlreadySubscribed,
+    NotSubscribed,
+    NoPacksFound,
+    PackAccess,
+    PackChance,
+    PackError,
+    NeedMediaReply,
+    NeedAnswerReply,
+    NeedStickerEmoji,
+    UnsupportedMessage,
+    Greeting,
+    NoWelcome,
+    WelcomeSaveError,
+    WelcomeUpdateError,
+    WelcomeDelay,
+    WelcomeBots,
+    // Feedback on an invalid argument of the command
+    InvalidArgument(Command),
+}
+
+impl Phrase {
+    // Texts of a phrase are kept together, so a missing translation fails the build
+    fn texts(&self) -> (&'static str, &'static str) {
+        match self {
+            Phrase::NeedControlItem => (
+                "Необходимо указать объект для редактирования",
+                "You need to specify an object to edit",
+            ),
+            Phrase::InvalidControlItem => (
+                "Указан недопустимый объект для редактирования",
+                "An invalid object to edit is specified",
+            ),
+            Phrase::NeedReply => (
+                "Необходимо выбрать сообщение в ответ",
+                "You need to reply to a message",
+            ),
+            Phrase::NeedValues => ("Необходимо указать значения", "You need to specify values"),
+            Phrase::UnsupportedControlItem => (
+                "Объект редактирования не поддерживается",
+                "The object to edit is not supported",
+            ),
+            Phrase::UnsupportedContentType => (
+                "Тип объекта редактирования не поддерживается",
+                "The type of the object to edit is not supported",
+            ),
+            Phrase::InvalidValue => ("Указано неверное значение", "Invalid value"),
+            Phrase::Done => ("Сделано", "Done"),
+            Phrase::DidIt => ("Сделал", "Done"),
+            Phrase::NothingFound => ("Ничего не было найдено", "Nothing was found"),
+            Phrase::NoKeysFound => ("Ключей не найдено", "No keys found"),
+            Phrase::AddError => ("Произошла ошибка добавления", "An error occurred while adding"),
+            Phrase::NothingDeleted => ("Ничего не было удалено", "Nothing was deleted"),
+            Phrase::DeletedKeys => ("Был удален контент на ключах", "Content was deleted on keys"),
+            Phrase::NoMembersFound => (
+                "Не найдено пользователей в чате",
+                "No members found in the chat",
+            ),
+            Phrase::NoSubstringChance => (
+                "Не заполнен процент срабатывания подстрок",
+                "The substring answer chance is not set",
+            ),
+            Phrase::NoMorphChance => (
+                "Не заполнен процент срабатывания бреда",
+                "The nonsense answer chance is not set",
+            ),
+            Phrase::SubstringChanceError => (
+                "Произошла ошибка обновления процента срабатывания подстрок",
+                "An error occurred while updating the substring answer chance",
+            ),
+            Phrase::MorphChanceError => (
+                "Произошла ошибка обновления процента срабатывания бреда",
+                "An error occurred while updating the nonsense answer chance",
+            ),
+            Phrase::NoLanguage => ("Не заполнен язык чата", "The chat language is not set"),
+            Phrase::LanguageError => (
+                "Произошла ошибка обновления языка чата",
+                "An error occurred while updating the chat language",
+            ),
+            Phrase::ChatLanguage => ("Язык чата", "Chat language"),
+            Phrase::MemberNotFound => ("Участник чата не найден", "The chat member was not found"),
+            Phrase::WordForms => ("Учет словоформ", "Word forms matching"),
+            Phrase::NoWordForms => (
+                "Не заполнен режим учета словоформ",
+                "The word forms matching mode is not set",
+            ),
+            Phrase::WordFormsError => (
+                "Произошла ошибка обновления режима учета словоформ",
+                "An error occurred while updating the word forms matching mode",
+            ),
+            Phrase::TriggerSimilarity => (
+                "Порог похожести триггеров",
+                "Trigger similarity threshold",
+            ),
+            Phrase::SimilarityToTrigger => ("Похожесть на триггер", "Similarity to trigger"),
+            Phrase::NoTriggerSimilarity => (
+                "Не заполнен порог похожести триггеров",
+                "The trigger similarity threshold is not set",
+            ),
+            Phrase::TriggerSimilarityError => (
+                "Произошла ошибка обновления порога похожести триггеров",
+                "An error occurred while updating the trigger similarity threshold",
+            ),
+            Phrase::InvalidRegex => (
+                "Некорректное регулярное выражение",
+                "Invalid regular expression",
+            ),
+            Phrase::TooComplexRegex => (
+                "Слишком сложное регулярное выражение",
+                "The regular expression is too complex",
+            ),
+            Phrase::UnknownPlaceholder => (
+                "Неизвестная подстановка в шаблоне",
+                "Unknown placeholder in the template",
+            ),
+            Phrase::InvalidTemplateRange => (
+                "Некорректный диапазон в шаблоне",
+                "Invalid range in the template",
+            ),
+            Phrase::Weight => ("Вес", "Weight"),
+            Phrase::WeightError => (
+                "Произошла ошибка обновления веса",
+                "An error occurred while updating the weight",
+            ),
+            Phrase::SelectionError => (
+                "Произошла ошибка обновления режима выбора",
+                "An error occurred while updating the selection mode",
+            ),
+            Phrase::TrashEmpty => ("Корзина пуста", "The trash is empty"),
+            Phrase::NothingRestored => ("Ничего не было восстановлено", "Nothing was restored"),
+            Phrase::RestoredKeys => (
+                "Был восстановлен контент на ключах",
+                "Content was restored on keys",
+            ),
+            Phrase::PurgedFromTrash => ("Удалено из корзины", "Deleted from the trash"),
+            Phrase::PurgeError => (
+                "Произошла ошибка очистки корзины",
+                "An error occurred while emptying the trash",
+            ),
+            Phrase::HistoryEmpty => ("История изменений пуста", "The history of changes is empty"),
+            Phrase::NotEnoughPermissions => (
+                "Недостаточно прав для команды",
+                "Not enough permissions for the command",
+            ),
+            Phrase::PermissionAboveOwn => (
+                "Нельзя назначить права выше своих",
+                "You cannot set permissions higher than your own",
+            ),
+            Phrase::PermissionError => (
+                "Произошла ошибка обновления прав",
+                "An error occurred while updating the permissions",
+            ),
+            Phrase::Page => ("Страница", "Page"),
+            Phrase::UnlinkedKeys => ("Были отвязаны ключи", "Keys were unlinked"),
+            Phrase::RenameError => (
+                "Произошла ошибка переименования ключа",
+                "An error occurred while renaming the key",
+            ),
+            Phrase::MoveError => (
+                "Произошла ошибка переноса контента",
+                "An error occurred while moving the content",
+            ),
+            Phrase::DictionaryEmpty => ("Словарь пуст", "The dictionary is empty"),
+            Phrase::DeletedWords => ("Были удалены слова", "Words were deleted"),
+            Phrase::FileReadError => ("Не удалось прочитать файл", "Failed to read the file"),
+            Phrase::AddedWords => ("Добавлено слов", "Words added"),
+            Phrase::Answers => ("Ответов", "Answers"),
+            Phrase::Words => ("Слов", "Words"),
+            Phrase::AddedAnswers => ("Добавлено ответов", "Answers added"),
+            Phrase::ExistedAnswers => ("Ответов уже было в чате", "Answers already in the chat"),
+            Phrase::ExistedWords => ("Слов уже было в чате", "Words already in the chat"),
+            Phrase::SkippedMedia => (
+                "Пропущено медиа другого бота",
+                "Skipped media of another bot",
+            ),
+            Phrase::SkippedInvalidKeys => ("Пропущено некорректных ключей", "Skipped invalid keys"),
+            Phrase::SettingsNotChanged => (
+                "Настройки чата не изменены",
+                "Chat settings are not changed",
+            ),
+            Phrase::SubstringChance => ("Процент срабатывания подстрок", "Substring answer chance"),
+            Phrase::MorphChance => ("Процент срабатывания бреда", "Nonsense answer chance"),
+            Phrase::InvalidArchive => ("Некорректный файл архива", "Invalid archive file"),
+            Phrase::UnsupportedArchiveVersion => (
+                "Неподдерживаемая версия архива",
+                "Unsupported archive version",
+            ),
+            Phrase::ImportError => (
+                "Произошла ошибка импорта",
+                "An error occurred while importing",
+            ),
+            Phrase::NeedPackName => (
+                "Необходимо указать название пака",
+                "You need to specify the pack name",
+            ),
+            Phrase::ChatHasPack => ("У чата уже есть пак", "The chat already has a pack"),
+            Phrase::PackNameTaken => (
+                "Пак с таким названием уже существует",
+                "A pack with this name already exists",
+            ),
+            Phrase::PackNotFound => ("Пак не найден", "The pack is not found"),
+            Phrase::NoChatPack => ("У чата нет пака", "The chat has no pack"),
+            Phrase::PrivatePack => (
+                "Нет доступа к приватному паку",
+                "No access to the private pack",
+            ),
+            Phrase::OwnPackSubscription => (
+                "Нельзя подписаться на пак своего чата",
+                "The chat can't subscribe to its own pack",
+            ),
+            Phrase::AlreadySubscribed => (
+                "Чат уже подписан на пак",
+                "The chat is already subscribed to the pack",
+            ),
+            Phrase::NotSubscribed => (
+                "Чат не подписан на пак",
+                "The chat is not subscribed to the pack",
+            ),
+            Phrase::NoPacksFound => ("Паков не найдено", "No packs found"),
+            Phrase::PackAccess => ("Доступ пака", "Pack access"),
+            Phrase::PackChance => ("Процент срабатывания пака", "Pack answer chance"),
+            Phrase::PackError => (
+                "Произошла ошибка обновления пака",
+                "An error occurred while updating the pack",
+            ),
+            Phrase::NeedMediaReply => (
+                "Необходимо выбрать в ответ стикер, гифку или картинку",
+                "You need to reply to a sticker, a gif or a picture",
+            ),
+            Phrase::NeedAnswerReply => (
+                "Теперь выбери в ответ сообщение с ответом и повтори команду",
+                "Now reply to the message with the answer and repeat the command",
+            ),
+            Phrase::NeedStickerEmoji => (
+                "Необходимо указать эмодзи стикера",
+                "You need to specify the sticker emoji",
+            ),
+            Phrase::UnsupportedMessage => (
+                "Такие сообщения не поддерживаются",
+                "Such messages are not supported",
+            ),
+            Phrase::Greeting => (
+                "Привет! Я отвечаю на сообщения чата запомненными ответами. Список команд: хлеб хелп",
+                "Hi! I answer chat messages with remembered answers. List of commands: bread help",
+            ),
+            Phrase::NoWelcome => ("Приветствие не задано", "The welcome is not set"),
+            Phrase::WelcomeSaveError => (
+                "Произошла ошибка сохранения приветствия",
+                "An error occurred while saving the welcome",
+            ),
+            Phrase::WelcomeUpdateError => (
+                "Произошла ошибка обновления приветствия",
+                "An error occurred while updating the welcome",
+            ),
+            Phrase::WelcomeDelay => (
+                "Задержка удаления приветствия в секундах",
+                "Welcome deletion delay in seconds",
+            ),
+            Phrase::WelcomeBots => ("Приветствие ботов", "Welcome of bots"),
+            Phrase::InvalidArgument(command) => (
+                ARGUMENT_FEEDBACK_MAP.get(&(*command, Language::Ru)).unwrap(),
+                ARGUMENT_FEEDBACK_MAP.get(&(*command, Language::En)).unwrap(),
+            ),
+        }
+    }
+}
+
+fn en_command_description(command: &Command) -> &'static str {
+    match command {
+        Command::Help => "Get information on how to use the commands and the bot in general.",
+        Command::Who => {
+            "Randomly picks a member of the group \
+        and attributes the given value to their name"
+        }
+        Command::AnswerChance => {
+            "Show and set the chance of automatic reaction \
+        to `nonsense` and `substrings`. To show the value, \
+        call the command with one of the available objects and without numeric parameters. \
+        To set the value, add an integer parameter from 0 to 100"
+        }
+        Command::Show => "Show the keys of the content selected as a reply",
+        Command::Keys => {
+            "Show all keys of the chat page by page. \
+        If a text is given, the keys starting with it are shown, \
+        and * in the text matches any text. \
+        The button with the key number sends its values"
+        }
+        Command::Add => "Add a word to the nonsense dictionary",
+        Command::Dictionary => "Show the words of the nonsense dictionary page by page",
+        Command::DeleteWords => "Delete words from the nonsense dictionary",
+        Command::ImportWords => {
+            "Add words to the nonsense dictionary from the text or the file \
+        of the replied message. Words are separated by new lines or \",\""
+        }
+        Command::Export => {
+            "Export the content, the dictionary and the settings of the chat to a file. \
+        Media from the file can be sent only by the bot, which exported it"
+        }
+        Command::Import => {
+            "Import the content and the dictionary from the export file of the replied message. \
+        Existing content and chat settings are kept"
+        }
+        Command::CreatePack => {
+            "Create a pack with the given name from the chat content. \
+        Other chats can subscribe to the pack and reply with its content"
+        }
+        Command::Packs => "Show available packs, the packs the chat is subscribed to are marked",
+        Command::PackAccess => {
+            "Show and set the access to the chat pack. \
+        Only the pack author can subscribe a chat to a private pack. \
+        To show the value, call the command without parameters"
+        }
+        Command::PackChance => {
+            "Show and set the answer chance of the chat pack content in subscribed chats. \
+        To show the value, call the command without parameters"
+        }
+        Command::Subscribe => "Subscribe the chat to the pack with the given name",
+        Command::Unsubscribe => "Unsubscribe the chat from the pack with the given name",
+        Command::Remember => "Save the content of the replied message on the given keys",
+        Command::RememberForward => {
+            "Save the replied message on the given keys. \
+        The bot answers by forwarding the message with its author"
+        }
+        Command::RememberCopy => {
+            "Save the replied message on the given keys. \
+        The bot answers with a copy of the message keeping its formatting"
+        }
+        Command::RememberMedia => {
+            "Save an answer on a sticker, a gif or a picture. \
+        First the command is called as a reply to the media, then as a reply to the message \
+        with the answer content. If an emoji is given, the content of the replied message \
+        is saved on any sticker with this emoji"
+        }
+        Command::Delete => "Delete the content of the replied message",
+        Command::Unlink => {
+            "Delete the given keys of the content of the replied message. \
+        Other keys of the content are kept"
+        }
+        Command::Rename => {
+            "Rename a key for all of its content. \
+        Content, which already exists on the new key, is deleted from the old key"
+        }
+        Command::Move => "Move the content of the replied message from the old key to the new one",
+        Command::Check => "Check whether there is content for the keys given in parameters",
+        Command::Say => "Repeat the written text (will become a voice converter soon)",
+        Command::Couple => {
+            "Show a couple of random chat members. \
+        If a member is mentioned, the couple is picked for them"
+        }
+        Command::Top => "Show the list of chat members in random order",
+        Command::Channel => "Mention all active chat members with tags",
+        Command::RandomChance => "A random percentage",
+        Command::RandomChoose => "Choose one of several options",
+        Command::GenerateNonsense => {
+            "Distort the message by replacing its words \
+        with the words saved in the dictionary"
+        }
+        Command::Morph => "Inflect the given word",
+        Command::MorphDebug => "Inflect the given word with details",
+        Command::Quote => "Get a wise quote",
+        Command::Joke => "Get a joke",
+        Command::Advice => "Get a piece of advice",
+        Command::Template => {
+            "Show how a text reply renders by the template. \
+        Placeholders can be used in the template: {author} - the message author, \
+        {random_member} - a random chat member, {date} - the date, {time} - the time, \
+        {text} - the message text, {rand:1-100} - a random number from the range. \
+        Curly braces without a placeholder are written as {{ and }}"
+        }
+        Command::Weight => {
+            "Show and set the weight of the content of the replied message. \
+        The greater the weight, the more often the content is chosen among the key answers. \
+        To show the value, call the command without parameters"
+        }
+        Command::Selection => {
+            "Show and set the selection mode of the key answers: \
+        random with weights, in a cycle or as a story, which advances \
+        on every hit and stops on the last answer. \
+        To show the value, specify only the keys"
+        }
+        Command::Trash => "Show recently deleted content",
+        Command::Restore => {
+            "Restore deleted content of the replied message. \
+        Without a replied message the content of the last deletion is restored"
+        }
+        Command::Purge => {
+            "Permanently delete content from the trash, \
+        which was deleted earlier than the given period. The default period is 30 days, \
+        content older than this period is also deleted automatically"
+        }
+        Command::History => "Show the recent changes of the chat content and settings",
+        Command::Permission => {
+            "Show and set the permissions required for a command: \
+        a member, the content author, a chat admin or the bot owner. \
+        To show the permissions, specify only the command"
+        }
+        Command::TriggerSimilarity => {
+            "Show and set the trigger similarity threshold in percent. \
+        If it is less than 100, a trigger also fires on messages with typos, \
+        for example \"helo all\" on the \"hello all\" trigger. \
+        To show the value, call the command without parameters"
+        }
+        Command::Welcome => {
+            "Save the content of the replied message as the welcome of new chat members. \
+        In the welcome text {author} is replaced with the name of the new member"
+        }
+        Command::DeleteWelcome => "Delete the welcome of new chat members",
+        Command::WelcomeDelay => {
+            "Show and set the delay in seconds, after which the welcome is deleted. \
+        With 0 the welcome is not deleted. \
+        To show the value, call the command without parameters"
+        }
+        Command::WelcomeBots => {
+            "Show and set whether added bots are welcomed. \
+        To show the value, call the command without parameters"
+        }
+        Command::WordForms => {
+            "Show and set word forms matching for keys. \
+        If it is on, the key \"beer\" also fires on \"beers\". \
+        To show the value, call the command without parameters"
+        }
+        Command::ChatLanguage => {
+            "Show and set the language of the bot replies in the chat. \
+        To show the value, call the command without parameters"
+        }
+    }
+}
+
+pub fn command_description(command: &Command, language: &Language) -> &'static str {
+    match language {
+        Language::Ru => COMMAND_SETTING_MAP.get(command).unwrap().description,
+        Language::En => en_command_description(command),
+    }
+}
+
+pub fn translate(language: &Language, phrase: Phrase) -> String {
+    let (ru, en) = phrase.texts();
+    match language {
+        Language::Ru => ru.to_string(),
+        Language::En => en.to_string(),
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 use std::iter::Iterator;
 
+use crate::common::album_service::buffer_album_part;
+use crate::common::answer_entity_service::{media, select_answer_entity};
 use crate::common::callback_service::process_callback;
 use log::{info};
 use sqlx::PgPool;
@@ -8,29 +10,70 @@ use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::common::command_service::process_command;
-use crate::common::db::{ChatId, ChatToMemberId, MemberId};
+use crate::common::db::{Chat as ChatDB, ChatId, ChatToMemberId, EntityContentType, MemberId};
 use crate::common::error::ProcessError;
-use crate::common::lexer::{tokenize, Token};
-use crate::common::request::RequestPayload;
+use crate::common::lexer::{tokenize_with_entities, Token};
+use crate::common::locale::translate;
+use crate::common::request::{ChatMemberUpdated, RequestPayload};
+use crate::common::template::render_template;
 use crate::common::response::{text_message, ResponseMessage};
 use crate::common::telegram_client::send_message;
-use crate::common::user_service::process_user_and_chat;
+use crate::common::user_service::{
+    process_members_message, process_migration_message, process_my_chat_member,
+    process_user_and_chat,
+};
+use crate::common::welcome_service::send_welcomes;
 
 enum AutoEntityRegime {
     Trigger,
     Substring,
 }
 
-async fn process_auto_entity<'a>(
+async fn process_auto_entity(
     regime: AutoEntityRegime,
-) -> Result<ResponseMessage, ProcessError<'a>> {
+) -> Result<ResponseMessage, ProcessError> {
     match regime {
         AutoEntityRegime::Trigger => Err(ProcessError::Next),
         AutoEntityRegime::Substring => Err(ProcessError::Next),
     }
 }
 
-async fn process_auto_morph<'a>() -> Result<ResponseMessage, ProcessError<'a>> {
+// Reaction on sticker, gif or photo saved by unique id of its file or by emoji of sticker
+async fn process_auto_media(
+    request_payload: &RequestPayload,
+    pool: &PgPool,
+    member_db_id: &MemberId,
+    chat_db_id: &ChatId,
+) -> Result<ResponseMessage, ProcessError> {
+    let RequestPayload::Origin { message, .. } = request_payload else {
+        return Err(ProcessError::Next);
+    };
+    let keys = message.direct().ext.media_keys();
+    if keys.is_empty() {
+        return Err(ProcessError::Next);
+    }
+    let answer_entities = media(pool, &keys, chat_db_id).await;
+    let Some(mut answer_entity) = select_answer_entity(pool, chat_db_id, answer_entities).await
+    else {
+        return Err(ProcessError::Next);
+    };
+    if answer_entity.content_type == EntityContentType::Text {
+        if let Ok(value) =
+            render_template(pool, &answer_entity.value, chat_db_id, member_db_id, "").await
+        {
+            answer_entity.value = value;
+        }
+    }
+    ResponseMessage::from_answer_entity(
+        answer_entity,
+        message.direct().base.chat.id,
+        message.direct().base.message_id,
+        None,
+    )
+    .ok_or(ProcessError::Stop)
+}
+
+async fn process_auto_morph() -> Result<ResponseMessage, ProcessError> {
     Err(ProcessError::Next)
 }
 
@@ -38,6 +81,7 @@ async fn process_auto_morph<'a>() -> Result<ResponseMessage, ProcessError<'a>> {
 pub enum Processor {
     Callback,
     Command,
+    AutoMedia,
     AutoTrigger,
     AutoSubstring,
     AutoMorph,
@@ -51,7 +95,7 @@ pub async fn handle_processor<'a>(
     member_db_id: &MemberId,
     chat_db_id: &ChatId,
     chat_to_member_db_id: &ChatToMemberId,
-) -> Result<ResponseMessage, ProcessError<'a>> {
+) -> Result<ResponseMessage, ProcessError> {
     match processor {
         Processor::Command => {
             process_command(
@@ -65,6 +109,9 @@ pub async fn handle_processor<'a>(
             )
             .await
         }
+        Processor::AutoMedia => {
+            process_auto_media(request_payload, pool, member_db_id, chat_db_id).await
+        }
         Processor::AutoTrigger => process_auto_entity(AutoEntityRegime::Trigger).await,
         Processor::AutoSubstring => process_auto_entity(AutoEntityRegime::Substring).await,
         Processor::AutoMorph => process_auto_morph().await,
@@ -80,7 +127,10 @@ pub async fn handle_processor<'a>(
         }
     }
 }
-pub async fn process_message<'a>(pool: &PgPool, request_payload: &RequestPayload) {
+pub async fn process_message(pool: &PgPool, request_payload: &RequestPayload) {
+    if process_migration_message(pool, request_payload.any_message().direct()).await {
+        return;
+    }
     let (member_db_id, chat_db_id, chat_to_member_db_id) = match process_user_and_chat(
         pool,
         &request_payload.any_message().direct().base.from,
@@ -94,12 +144,20 @@ pub async fn process_message<'a>(pool: &PgPool, request_payload: &RequestPayload
             return;
         }
     };
-    let tokens = &request_payload
-        .any_message()
-        .direct()
-        .ext
+    if process_members_message(pool, request_payload.any_message().direct(), &chat_db_id).await {
+        send_welcomes(pool, request_payload.any_message().direct(), &chat_db_id).await;
+        return;
+    }
+    if let RequestPayload::Origin { message, .. } = request_payload {
+        buffer_album_part(pool, &chat_db_id, message.direct());
+        if let Some(reply) = message.reply() {
+            buffer_album_part(pool, &chat_db_id, reply);
+        }
+    }
+    let message_ext = &request_payload.any_message().direct().ext;
+    let tokens = &message_ext
         .raw_text()
-        .map(tokenize);
+        .map(|text| tokenize_with_entities(text, message_ext.entities()));
     for processor in Processor::iter() {
         let response_message = handle_processor(
             &processor,
@@ -127,9 +185,12 @@ pub async fn process_message<'a>(pool: &PgPool, request_payload: &RequestPayload
                         "User error, sends feedback: {:?} for {:?}",
                         message, chat_db_id
                     );
+                    let language = ChatDB::language(pool, &chat_db_id)
+                        .await
+                        .unwrap_or_default();
                     send_message(
                         &text_message(
-                            message.to_string(),
+                            translate(&language, message),
                             request_payload.any_message().direct().base.chat.id,
                             request_payload.any_message().direct().base.message_id,
                         ),
@@ -146,3 +207,9 @@ pub async fn process_message<'a>(pool: &PgPool, request_payload: &RequestPayload
         }
     }
 }
+
+pub async fn process_chat_member_update(pool: &PgPool, chat_member_updated: &ChatMemberUpdated) {
+    if let Some((chat_db_id, greeting)) = process_my_chat_member(pool, chat_member_updated).await {
+        send_message(&greeting, &chat_db_id).await;
+    }
+}
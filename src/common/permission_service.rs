@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+
+use crate::common::chat_cache::{cache_key, CacheKey};
+use crate::common::command_parser::{Argument, Command, CommandContainer, COMMAND_SETTING_MAP};
+use crate::common::command_service::saved_reply_content;
+use crate::common::db::{AnswerEntity, ChatId, CommandPermission, Member, MemberId, Role};
+use crate::common::error::ProcessError;
+use crate::common::locale::Phrase;
+use crate::common::request::Message;
+use crate::common::telegram_client::chat_administrators;
+use crate::config::BOT_OWNER_IDS;
+
+// Administrators are requested from telegram not more often than this time
+const CHAT_ADMINISTRATORS_TTL: Duration = Duration::from_secs(10 * 60);
+
+struct CachedAdministrators {
+    cached_at: Instant,
+    administrators: Vec<i64>,
+}
+
+static CHAT_ADMINISTRATORS_CACHE: Lazy<RwLock<HashMap<CacheKey, CachedAdministrators>>> =
+    Lazy::new(Default::default);
+
+pub fn cache_chat_administrators(pool: &PgPool, chat_db_id: &ChatId, administrators: Vec<i64>) {
+    CHAT_ADMINISTRATORS_CACHE.write().unwrap().insert(
+        cache_key(pool, chat_db_id),
+        CachedAdministrators {
+            cached_at: Instant::now(),
+            administrators,
+        },
+    );
+}
+
+async fn is_chat_administrator(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    chat_id: i64,
+    member_id: i64,
+) -> bool {
+    {
+        // Administrators of chats, which are not active anymore, don't stay in memory
+        let mut cache = CHAT_ADMINISTRATORS_CACHE.write().unwrap();
+        cache.retain(|_, x| x.cached_at.elapsed() < CHAT_ADMINISTRATORS_TTL);
+        if let Some(cached) = cache.get(&cache_key(pool, chat_db_id)) {
+            return cached.administrators.contains(&member_id);
+        }
+    }
+    match chat_administrators(chat_id).await {
+        Some(administrators) => {
+            let is_administrator = administrators.contains(&member_id);
+            cache_chat_administrators(pool, chat_db_id, administrators);
+            is_administrator
+        }
+        // Permissions are not given while telegram doesn't answer
+        None => false,
+    }
+}
+
+pub fn command_name(command: &Command) -> &'static str {
+    command.into()
+}
+
+pub async fn member_role(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    chat_id: i64,
+) -> Role {
+    let Some(member) = Member::one_by_id(pool, member_db_id).await else {
+        return Role::Member;
+    };
+    if BOT_OWNER_IDS.contains(&member.member_id) {
+        return Role::Owner;
+    }
+    // Private chat is managed by its only member
+    match chat_id > 0 || is_chat_administrator(pool, chat_db_id, chat_id, member.member_id).await {
+        true => Role::Admin,
+        false => Role::Member,
+    }
+}
+
+pub async fn required_role(pool: &PgPool, chat_db_id: &ChatId, command: &Command) -> Role {
+    match CommandPermission::role(pool, chat_db_id, command_name(command)).await {
+        Some(role) => role,
+        None => COMMAND_SETTING_MAP.get(command).unwrap().required_role,
+    }
+}
+
+// Any member can show a value of command, which gets or sets it
+fn is_showing(command_container: &CommandContainer) -> bool {
+    COMMAND_SETTING_MAP
+        .get(command_container.command)
+        .unwrap()
+        .get_or_set_value
+        && matches!(
+            command_container.argument,
+            Argument::Empty | Argument::EnumWordWithList(None, _)
+        )
+}
+
+// Member is the author if all content of the replied message was saved by them
+async fn is_content_author(
+    pool: &PgPool,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+) -> bool {
+    let Some(reply_message_body) = message.reply() else {
+        return false;
+    };
+    let Ok((entity_content_type, (value, file_unique_id, _))) =
+        saved_reply_content(pool, chat_db_id, reply_message_body).await
+    else {
+        return false;
+    };
+    let authors = AnswerEntity::authors(
+        pool,
+        chat_db_id,
+        &value,
+        &file_unique_id,
+        &entity_content_type,
+    )
+    .await;
+    !authors.is_empty() && authors.iter().all(|x| x.as_ref() == Some(member_db_id))
+}
+
+pub async fn check_permission<'a>(
+    pool: &PgPool,
+    command_container: &CommandContainer<'a>,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    message: &Message,
+) -> Result<(), ProcessError> {
+    let required_role = required_role(pool, chat_db_id, command_container.command).await;
+    if required_role == Role::Member || is_showing(command_container) {
+        return Ok(());
+    }
+    let role = member_role(
+        pool,
+        chat_db_id,
+        member_db_id,
+        message.direct().base.chat.id,
+    )
+    .await;
+    if role >= required_role
+        || (required_role == Role::Author
+            && is_content_author(pool, chat_db_id, member_db_id, message).await)
+    {
+        Ok(())
+    } else {
+        Err(ProcessError::Feedback {
+            message: Phrase::NotEnoughPermissions,
+        })
+    }
+}
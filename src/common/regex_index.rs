@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use log::warn;
+use once_cell::sync::Lazy;
+use regex::{Captures, Error, Regex, RegexBuilder};
+use sqlx::PgPool;
+
+use crate::common::chat_cache::{ChatCache, ChatSources};
+use crate::common::db::{AnswerEntity, AnswerPack, ChatId};
+use crate::common::locale::Phrase;
+
+// Pattern is saved as a key, so it's limited by length of key column.
+// Default size limits of regex crate are too generous for patterns of chat members
+const MAX_PATTERN_LENGTH: usize = 255;
+const MAX_COMPILED_SIZE: usize = 1 << 18;
+
+// Patterns are matched case-insensitively, as other keys are
+pub fn compile_pattern(pattern: &str) -> Result<Regex, Phrase> {
+    if pattern.chars().count() > MAX_PATTERN_LENGTH {
+        return Err(Phrase::TooComplexRegex);
+    }
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(MAX_COMPILED_SIZE)
+        .dfa_size_limit(MAX_COMPILED_SIZE)
+        .build()
+        .map_err(|err| match err {
+            Error::CompiledTooBig(_) => Phrase::TooComplexRegex,
+            _ => Phrase::InvalidRegex,
+        })
+}
+
+pub struct RegexIndex {
+    // Chats whose patterns are indexed, so the index is outdated on change of any of them
+    sources: Vec<ChatId>,
+    patterns: Vec<(String, Regex)>,
+}
+
+impl RegexIndex {
+    fn new(sources: Vec<ChatId>, patterns: Vec<String>) -> Self {
+        RegexIndex {
+            sources,
+            patterns: patterns
+                .into_iter()
+                .filter_map(|pattern| match compile_pattern(&pattern) {
+                    Ok(regex) => Some((pattern, regex)),
+                    Err(err) => {
+                        warn!("Pattern {:?} is skipped: {:?}", pattern, err);
+                        None
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    // Patterns matching text with captures of their first match
+    pub fn find_matches<'t>(&self, text: &'t str) -> Vec<(&str, &Regex, Captures<'t>)> {
+        self.patterns
+            .iter()
+            .filter_map(|(pattern, regex)| {
+                regex.captures(text).map(|x| (pattern.as_str(), regex, x))
+            })
+            .collect()
+    }
+}
+
+static CAPTURE_REFERENCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$(?:\{(\w+)\}|(\w+))").unwrap());
+
+// References "$1", "${1}", "$name" and "${name}" to groups of the pattern are replaced with
+// captures. Other text is kept as is, so a value like "costs $5" is not blanked
+pub fn expand_captures(regex: &Regex, captures: &Captures, value: &str) -> String {
+    CAPTURE_REFERENCE
+        .replace_all(value, |reference: &Captures| {
+            let group = reference.get(1).or(reference.get(2)).unwrap().as_str();
+            // Group, which is not in the pattern, is None, and group, which didn't match, is empty
+            let capture = match group.parse::<usize>() {
+                Ok(index) if index < regex.captures_len() => Some(captures.get(index)),
+                Err(_) if regex.capture_names().any(|x| x == Some(group)) => {
+                    Some(captures.name(group))
+                }
+                _ => None,
+            };
+            match capture {
+                Some(capture) => capture.map_or("", |x| x.as_str()).to_string(),
+                None => reference[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+impl ChatSources for RegexIndex {
+    fn sources(&self) -> &[ChatId] {
+        &self.sources
+    }
+}
+
+static REGEX_INDEX_CACHE: Lazy<ChatCache<RegexIndex>> = Lazy::new(Default::default);
+
+async fn build_regex_index(pool: &PgPool, chat_id: &ChatId) -> RegexIndex {
+    let sources = AnswerPack::source_chats(pool, chat_id).await;
+    let patterns = AnswerEntity::regex_keys(pool, &sources).await;
+    RegexIndex::new(sources, patterns)
+}
+
+pub async fn regex_index(pool: &PgPool, chat_id: &ChatId) -> Arc<RegexIndex> {
+    REGEX_INDEX_CACHE
+        .get_or_build(pool, chat_id, build_regex_index(pool, chat_id))
+        .await
+}
+
+// Indexes of chats subscribed to the pack of the chat are invalidated too
+pub fn invalidate_regex_index(pool: &PgPool, chat_id: &ChatId) {
+    REGEX_INDEX_CACHE.invalidate(pool, chat_id);
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -22,6 +24,114 @@ pub struct Chat {
 pub struct Content {
     pub file_id: String,
     pub file_unique_id: String,
+    // Emoji associated with sticker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Dice {
+    pub emoji: String,
+    pub value: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PollOption {
+    pub text: String,
+}
+
+// Only the parts, which are needed to resend the poll as a regular one
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Poll {
+    pub question: String,
+    pub options: Vec<PollOption>,
+    #[serde(default)]
+    pub is_anonymous: bool,
+    #[serde(default)]
+    pub allows_multiple_answers: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Contact {
+    pub phone_number: String,
+    pub first_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Venue {
+    pub location: Location,
+    pub title: String,
+    pub address: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageEntityKind {
+    Mention,
+    Hashtag,
+    Cashtag,
+    BotCommand,
+    Url,
+    Email,
+    PhoneNumber,
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Spoiler,
+    Blockquote,
+    ExpandableBlockquote,
+    Code,
+    Pre,
+    TextLink,
+    TextMention,
+    CustomEmoji,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MessageEntity {
+    #[serde(rename = "type")]
+    pub kind: MessageEntityKind,
+    // Offset and length are measured in UTF-16 code units
+    pub offset: usize,
+    pub length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<User>,
+}
+
+impl MessageEntity {
+    // Byte range of entity in text, None if entity is out of text bounds
+    pub fn byte_range(&self, text: &str) -> Option<Range<usize>> {
+        let byte_index = |utf16_offset: usize| {
+            let mut position = 0;
+            for (index, char) in text.char_indices() {
+                if position == utf16_offset {
+                    return Some(index);
+                }
+                position += char.len_utf16();
+            }
+            (position == utf16_offset).then_some(text.len())
+        };
+        match (
+            byte_index(self.offset),
+            byte_index(self.offset + self.length),
+        ) {
+            (Some(start), Some(end)) if start < end => Some(start..end),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -31,6 +141,9 @@ pub struct MessageBase {
     pub chat: Chat,
     pub forward_from: Option<User>,
     pub forward_from_chat: Option<Chat>,
+    // Parts of one album share the id, every part arrives as a separate message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_group_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -39,17 +152,25 @@ pub enum MessageExt {
     Photo {
         photo: Vec<Content>,
         caption: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        caption_entities: Vec<MessageEntity>,
     },
     Text {
         text: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        entities: Vec<MessageEntity>,
     },
     Video {
         video: Content,
         caption: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        caption_entities: Vec<MessageEntity>,
     },
     Voice {
         voice: Content,
         caption: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        caption_entities: Vec<MessageEntity>,
     },
     VideoNote {
         video_note: Content,
@@ -60,15 +181,54 @@ pub enum MessageExt {
     Animation {
         animation: Content,
         caption: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        caption_entities: Vec<MessageEntity>,
     },
     Document {
         document: Content,
         caption: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        caption_entities: Vec<MessageEntity>,
     },
     Audio {
         audio: Content,
         caption: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        caption_entities: Vec<MessageEntity>,
     },
+    Dice {
+        dice: Dice,
+    },
+    Poll {
+        poll: Poll,
+    },
+    // Venue is sent with its location too, so it's matched before location
+    Venue {
+        venue: Venue,
+    },
+    Location {
+        location: Location,
+    },
+    Contact {
+        contact: Contact,
+    },
+    // Service messages about members of chat
+    NewChatMembers {
+        new_chat_members: Vec<User>,
+    },
+    LeftChatMember {
+        left_chat_member: User,
+    },
+    // Group is upgraded to supergroup, the message is sent to the old chat
+    MigrateToChat {
+        migrate_to_chat_id: i64,
+    },
+    // Group is upgraded to supergroup, the message is sent to the new chat
+    MigrateFromChat {
+        migrate_from_chat_id: i64,
+    },
+    // Messages of other kinds as stories, so their members and chats are still updated
+    Unknown {},
 }
 
 impl MessageExt {
@@ -82,7 +242,74 @@ impl MessageExt {
             | Audio { caption, .. }
             | Document { caption, .. } => caption.as_deref(),
             Text { text, .. } => Some(text),
-            VideoNote { .. } | Sticker { .. } => None,
+            VideoNote { .. }
+            | Sticker { .. }
+            | Dice { .. }
+            | Poll { .. }
+            | Venue { .. }
+            | Location { .. }
+            | Contact { .. }
+            | NewChatMembers { .. }
+            | LeftChatMember { .. }
+            | MigrateToChat { .. }
+            | MigrateFromChat { .. }
+            | Unknown {} => None,
+        }
+    }
+
+    pub fn entities(&self) -> &[MessageEntity] {
+        use MessageExt::*;
+        match &self {
+            Photo {
+                caption_entities, ..
+            }
+            | Video {
+                caption_entities, ..
+            }
+            | Voice {
+                caption_entities, ..
+            }
+            | Animation {
+                caption_entities, ..
+            }
+            | Audio {
+                caption_entities, ..
+            }
+            | Document {
+                caption_entities, ..
+            } => caption_entities,
+            Text { entities, .. } => entities,
+            VideoNote { .. }
+            | Sticker { .. }
+            | Dice { .. }
+            | Poll { .. }
+            | Venue { .. }
+            | Location { .. }
+            | Contact { .. }
+            | NewChatMembers { .. }
+            | LeftChatMember { .. }
+            | MigrateToChat { .. }
+            | MigrateFromChat { .. }
+            | Unknown {} => &[],
+        }
+    }
+
+    // Keys of media reactions: unique id of sticker, gif or photo file and emoji of sticker
+    pub fn media_keys(&self) -> Vec<String> {
+        match self {
+            MessageExt::Sticker { sticker } => {
+                [Some(&sticker.file_unique_id), sticker.emoji.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .collect()
+            }
+            MessageExt::Animation { animation, .. } => vec![animation.file_unique_id.clone()],
+            MessageExt::Photo { photo, .. } => photo
+                .first()
+                .map(|x| vec![x.file_unique_id.clone()])
+                .unwrap_or_default(),
+            _ => vec![],
         }
     }
 
@@ -93,7 +320,7 @@ impl MessageExt {
                 Some(photo[0].file_unique_id.clone()),
                 caption.clone(),
             ),
-            MessageExt::Text { text } => (text.clone(), None, None),
+            MessageExt::Text { text, .. } => (text.clone(), None, None),
             MessageExt::Audio {
                 audio: content,
                 caption,
@@ -132,8 +359,35 @@ impl MessageExt {
                 Some(content.file_unique_id.clone()),
                 None,
             ),
+            // Dice is thrown again, so only its kind is kept
+            MessageExt::Dice { dice } => (dice.emoji.clone(), None, None),
+            MessageExt::Poll { poll } => (serde_json::to_string(poll).unwrap(), None, None),
+            MessageExt::Venue { venue } => (serde_json::to_string(venue).unwrap(), None, None),
+            MessageExt::Location { location } => {
+                (serde_json::to_string(location).unwrap(), None, None)
+            }
+            MessageExt::Contact { contact } => {
+                (serde_json::to_string(contact).unwrap(), None, None)
+            }
+            MessageExt::NewChatMembers { .. }
+            | MessageExt::LeftChatMember { .. }
+            | MessageExt::MigrateToChat { .. }
+            | MessageExt::MigrateFromChat { .. }
+            | MessageExt::Unknown {} => (String::new(), None, None),
         }
     }
+
+    // Service and unknown messages have no content to remember
+    pub fn can_be_remembered(&self) -> bool {
+        !matches!(
+            self,
+            MessageExt::NewChatMembers { .. }
+                | MessageExt::LeftChatMember { .. }
+                | MessageExt::MigrateToChat { .. }
+                | MessageExt::MigrateFromChat { .. }
+                | MessageExt::Unknown {}
+        )
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -200,6 +454,7 @@ pub struct CallbackQuery {
     pub id: String,
     pub from: User,
     pub message: Message,
+    pub data: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -227,4 +482,56 @@ impl RequestPayload {
             RequestPayload::Callback { callback_query, .. } => &callback_query.message,
         }
     }
+
+    pub fn callback_data(&self) -> Option<&str> {
+        match self {
+            RequestPayload::Callback { callback_query, .. } => callback_query.data.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatMemberStatus {
+    Creator,
+    Administrator,
+    Member,
+    Restricted,
+    Left,
+    Kicked,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChatMember {
+    pub user: User,
+    pub status: ChatMemberStatus,
+}
+
+impl ChatMember {
+    pub fn is_present(&self) -> bool {
+        !matches!(
+            self.status,
+            ChatMemberStatus::Left | ChatMemberStatus::Kicked
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChatMemberUpdated {
+    pub chat: Chat,
+    pub from: User,
+    pub old_chat_member: ChatMember,
+    pub new_chat_member: ChatMember,
+}
+
+// Updates, which are not messages, are processed apart from them
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum TelegramUpdate {
+    Message(Box<RequestPayload>),
+    MyChatMember {
+        update_id: u32,
+        my_chat_member: Box<ChatMemberUpdated>,
+    },
 }
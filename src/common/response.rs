@@ -1,5 +1,9 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
 
+use crate::common::db::{AnswerEntity, EntityContentType};
+use crate::common::request::{Contact, Location, Poll, Venue};
+
 #[derive(Debug, Serialize, PartialEq)]
 pub struct LinkPreviewOption {
     pub is_disabled: bool,
@@ -16,6 +20,25 @@ pub struct ReplyMarkupResponse {
     pub inline_keyboard: Vec<Vec<ReplyMarkupButtonResponse>>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InputMediaKind {
+    Photo,
+    Video,
+    Document,
+    Audio,
+}
+
+// Part of album, albums are saved as lists of parts
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct InputMedia {
+    #[serde(rename = "type")]
+    pub kind: InputMediaKind,
+    pub media: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+}
+
 #[derive(Debug, Serialize, PartialEq)]
 pub struct BaseBody {
     pub chat_id: i64,
@@ -85,6 +108,272 @@ pub enum ResponseMessage {
         base_body: BaseBody,
         sticker: String,
     },
+    // Album, telegram doesn't allow keyboards under albums
+    MediaGroup {
+        #[serde(flatten)]
+        base_body: BaseBody,
+        media: Vec<InputMedia>,
+    },
+    // Forward of the message with its author, telegram doesn't allow replies for forwards
+    Forward {
+        chat_id: i64,
+        from_chat_id: i64,
+        message_id: i64,
+    },
+    // Copy of the message without link to the original one
+    Copy {
+        #[serde(flatten)]
+        base_body: BaseBody,
+        from_chat_id: i64,
+        message_id: i64,
+    },
+    Dice {
+        #[serde(flatten)]
+        base_body: BaseBody,
+        emoji: String,
+    },
+    Poll {
+        #[serde(flatten)]
+        base_body: BaseBody,
+        #[serde(flatten)]
+        poll: Poll,
+    },
+    Location {
+        #[serde(flatten)]
+        base_body: BaseBody,
+        #[serde(flatten)]
+        location: Location,
+    },
+    Contact {
+        #[serde(flatten)]
+        base_body: BaseBody,
+        #[serde(flatten)]
+        contact: Contact,
+    },
+    Venue {
+        #[serde(flatten)]
+        base_body: BaseBody,
+        latitude: f64,
+        longitude: f64,
+        title: String,
+        address: String,
+    },
+    // Document, which is uploaded as a new file instead of a file id
+    NewDocument {
+        #[serde(flatten)]
+        base_body: BaseBody,
+        file_name: String,
+        content: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        caption: Option<String>,
+    },
+    // Deletes the sent message
+    Delete {
+        chat_id: i64,
+        message_id: i64,
+    },
+    // Replaces text and keyboard of the sent message
+    EditText {
+        chat_id: i64,
+        message_id: i64,
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reply_markup: Option<ReplyMarkupResponse>,
+    },
+}
+
+impl ResponseMessage {
+    pub fn from_answer_entity(
+        answer_entity: AnswerEntity,
+        chat_id: i64,
+        reply_to_message_id: i64,
+        reply_markup: Option<ReplyMarkupResponse>,
+    ) -> Option<Self> {
+        ResponseMessage::from_content(
+            answer_entity.content_type,
+            answer_entity.value,
+            answer_entity.description,
+            chat_id,
+            reply_to_message_id,
+            reply_markup,
+        )
+    }
+
+    // Content is saved as value and description of the content type as answer entities.
+    // None if the content can't be sent, as forward or copy without a valid source
+    pub fn from_content(
+        content_type: EntityContentType,
+        value: String,
+        caption: Option<String>,
+        chat_id: i64,
+        reply_to_message_id: i64,
+        reply_markup: Option<ReplyMarkupResponse>,
+    ) -> Option<Self> {
+        let base_body = BaseBody {
+            chat_id,
+            reply_to_message_id: Some(reply_to_message_id),
+            reply_markup,
+        };
+        Some(match content_type {
+            EntityContentType::Text => ResponseMessage::Text {
+                base_body,
+                text: value,
+                link_preview_options: LinkPreviewOption { is_disabled: false },
+            },
+            EntityContentType::Voice => ResponseMessage::Voice {
+                base_body,
+                voice: value,
+                caption,
+            },
+            EntityContentType::Picture => ResponseMessage::Photo {
+                base_body,
+                photo: value,
+                caption,
+            },
+            EntityContentType::Animation => ResponseMessage::Animation {
+                base_body,
+                animation: value,
+                caption,
+            },
+            EntityContentType::Video => ResponseMessage::Video {
+                base_body,
+                video: value,
+                caption,
+            },
+            EntityContentType::VideoNote => ResponseMessage::VideoNote {
+                base_body,
+                video_note: value,
+            },
+            EntityContentType::Sticker => ResponseMessage::Sticker {
+                base_body,
+                sticker: value,
+            },
+            EntityContentType::Audio => ResponseMessage::Audio {
+                base_body,
+                audio: value,
+                caption,
+            },
+            EntityContentType::Document => ResponseMessage::Document {
+                base_body,
+                document: value,
+                caption,
+            },
+            EntityContentType::Forward => {
+                let (from_chat_id, message_id) = message_source(&value)?;
+                ResponseMessage::Forward {
+                    chat_id,
+                    from_chat_id,
+                    message_id,
+                }
+            }
+            EntityContentType::Copy => {
+                let (from_chat_id, message_id) = message_source(&value)?;
+                ResponseMessage::Copy {
+                    base_body,
+                    from_chat_id,
+                    message_id,
+                }
+            }
+            EntityContentType::MediaGroup => ResponseMessage::MediaGroup {
+                base_body: BaseBody {
+                    reply_markup: None,
+                    ..base_body
+                },
+                media: serde_json::from_str(&value).unwrap_or_default(),
+            },
+            EntityContentType::Dice => ResponseMessage::Dice {
+                base_body,
+                emoji: value,
+            },
+            EntityContentType::Poll => match serde_json::from_str(&value) {
+                Ok(poll) => ResponseMessage::Poll { base_body, poll },
+                Err(_) => ResponseMessage::raw_text(base_body, value),
+            },
+            EntityContentType::Location => match serde_json::from_str(&value) {
+                Ok(location) => ResponseMessage::Location {
+                    base_body,
+                    location,
+                },
+                Err(_) => ResponseMessage::raw_text(base_body, value),
+            },
+            EntityContentType::Contact => match serde_json::from_str(&value) {
+                Ok(contact) => ResponseMessage::Contact { base_body, contact },
+                Err(_) => ResponseMessage::raw_text(base_body, value),
+            },
+            EntityContentType::Venue => match serde_json::from_str::<Venue>(&value) {
+                Ok(venue) => ResponseMessage::Venue {
+                    base_body,
+                    latitude: venue.location.latitude,
+                    longitude: venue.location.longitude,
+                    title: venue.title,
+                    address: venue.address,
+                },
+                Err(_) => ResponseMessage::raw_text(base_body, value),
+            },
+        })
+    }
+
+    // Saved value is sent as is, if it can't be read as its content type
+    fn raw_text(base_body: BaseBody, text: String) -> Self {
+        ResponseMessage::Text {
+            base_body,
+            text,
+            link_preview_options: LinkPreviewOption { is_disabled: false },
+        }
+    }
+
+    // Appends note to text or caption, messages without caption are left as is
+    pub fn with_note(mut self, note: String) -> Self {
+        match &mut self {
+            ResponseMessage::Text { text, .. } | ResponseMessage::EditText { text, .. } => {
+                *text = format!("{text}\n\n{note}")
+            }
+            ResponseMessage::Photo { caption, .. }
+            | ResponseMessage::Video { caption, .. }
+            | ResponseMessage::Voice { caption, .. }
+            | ResponseMessage::Audio { caption, .. }
+            | ResponseMessage::Document { caption, .. }
+            | ResponseMessage::NewDocument { caption, .. }
+            | ResponseMessage::Animation { caption, .. } => {
+                *caption = Some(match caption.take() {
+                    Some(caption) if !caption.is_empty() => format!("{caption}\n\n{note}"),
+                    _ => note,
+                })
+            }
+            // Caption of the first part is shown as caption of album
+            ResponseMessage::MediaGroup { media, .. } => {
+                if let Some(first) = media.first_mut() {
+                    first.caption = Some(match first.caption.take() {
+                        Some(caption) if !caption.is_empty() => format!("{caption}\n\n{note}"),
+                        _ => note,
+                    })
+                }
+            }
+            // Caption of copy would replace the original one
+            ResponseMessage::VideoNote { .. }
+            | ResponseMessage::Sticker { .. }
+            | ResponseMessage::Forward { .. }
+            | ResponseMessage::Copy { .. }
+            | ResponseMessage::Dice { .. }
+            | ResponseMessage::Poll { .. }
+            | ResponseMessage::Location { .. }
+            | ResponseMessage::Contact { .. }
+            | ResponseMessage::Venue { .. }
+            | ResponseMessage::Delete { .. } => {}
+        }
+        self
+    }
+}
+
+// Source of forward or copy saved as "<chat id>:<message id>"
+fn message_source(value: &str) -> Option<(i64, i64)> {
+    let source = value
+        .split_once(':')
+        .and_then(|(chat_id, message_id)| Some((chat_id.parse().ok()?, message_id.parse().ok()?)));
+    if source.is_none() {
+        warn!("Source of saved message is malformed: {}", value);
+    }
+    source
 }
 
 pub fn roll_reply_markup() -> Option<ReplyMarkupResponse> {
@@ -112,6 +401,23 @@ pub fn text_message(
     }
 }
 
+pub fn text_message_with_keyboard(
+    value: String,
+    chat_id: i64,
+    reply_to_message_id: i64,
+    reply_markup: ReplyMarkupResponse,
+) -> ResponseMessage {
+    ResponseMessage::Text {
+        base_body: BaseBody {
+            chat_id,
+            reply_to_message_id: Some(reply_to_message_id),
+            reply_markup: Some(reply_markup),
+        },
+        text: value,
+        link_preview_options: LinkPreviewOption { is_disabled: false },
+    }
+}
+
 pub fn text_message_with_roll(
     value: String,
     chat_id: i64,
@@ -126,4 +432,4 @@ pub fn text_message_with_roll(
         text: value,
         link_preview_options: LinkPreviewOption { is_disabled: false },
     }
-}
\ No newline at end of file
+}
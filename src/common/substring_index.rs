@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use aho_corasick::AhoCorasick;
+use log::warn;
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+
+use crate::common::chat_cache::{ChatCache, ChatSources};
+use crate::common::db::{AnswerEntity, AnswerPack, Chat as ChatDB, ChatId, KeyNormalization};
+use crate::common::lexer::stem_text;
+
+pub struct SubstringIndex {
+    pub key_normalization: KeyNormalization,
+    // Chats whose keys are indexed, so the index is outdated on change of any of them
+    sources: Vec<ChatId>,
+    keys: Vec<String>,
+    automaton: Option<AhoCorasick>,
+}
+
+fn is_word_char(x: char) -> bool {
+    x.is_alphanumeric() || x == '_'
+}
+
+fn is_joined(left: Option<char>, right: Option<char>) -> bool {
+    left.is_some_and(is_word_char) && right.is_some_and(is_word_char)
+}
+
+impl SubstringIndex {
+    fn new(key_normalization: KeyNormalization, sources: Vec<ChatId>, keys: Vec<String>) -> Self {
+        let automaton = match keys.is_empty() {
+            true => None,
+            false => AhoCorasick::new(&keys)
+                .map_err(|err| warn!("Substring index build failed with error: {}", err))
+                .ok(),
+        };
+        SubstringIndex {
+            key_normalization,
+            sources,
+            keys,
+            automaton,
+        }
+    }
+
+    // Keys occurring in normalized text on word boundaries
+    pub fn find_keys(&self, text: &str) -> Vec<String> {
+        let Some(automaton) = &self.automaton else {
+            return vec![];
+        };
+        let haystack = match self.key_normalization {
+            KeyNormalization::Lowercase => text.to_string(),
+            KeyNormalization::Stem => stem_text(text),
+        };
+        let mut keys = automaton
+            .find_overlapping_iter(&haystack)
+            .filter(|x| {
+                let key = &haystack[x.start()..x.end()];
+                !is_joined(
+                    haystack[..x.start()].chars().next_back(),
+                    key.chars().next(),
+                ) && !is_joined(key.chars().next_back(), haystack[x.end()..].chars().next())
+            })
+            .map(|x| self.keys[x.pattern().as_usize()].clone())
+            .collect::<Vec<String>>();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+impl ChatSources for SubstringIndex {
+    fn sources(&self) -> &[ChatId] {
+        &self.sources
+    }
+}
+
+static SUBSTRING_INDEX_CACHE: Lazy<ChatCache<SubstringIndex>> = Lazy::new(Default::default);
+
+async fn build_substring_index(pool: &PgPool, chat_id: &ChatId) -> SubstringIndex {
+    let key_normalization = ChatDB::key_normalization(pool, chat_id)
+        .await
+        .unwrap_or_default();
+    let sources = AnswerPack::source_chats(pool, chat_id).await;
+    let keys = AnswerEntity::substring_keys(pool, &sources)
+        .await
+        .into_iter()
+        .map(|(key, stemmed_key)| match key_normalization {
+            KeyNormalization::Lowercase => key,
+            KeyNormalization::Stem => stemmed_key.unwrap_or_else(|| stem_text(&key)),
+        })
+        .collect::<Vec<String>>();
+    SubstringIndex::new(key_normalization, sources, keys)
+}
+
+pub async fn substring_index(pool: &PgPool, chat_id: &ChatId) -> Arc<SubstringIndex> {
+    SUBSTRING_INDEX_CACHE
+        .get_or_build(pool, chat_id, build_substring_index(pool, chat_id))
+        .await
+}
+
+// Indexes of chats subscribed to the pack of the chat are invalidated too
+pub fn invalidate_substring_index(pool: &PgPool, chat_id: &ChatId) {
+    SUBSTRING_INDEX_CACHE.invalidate(pool, chat_id);
+}
@@ -1,39 +1,94 @@
 use crate::common::db::ChatId;
 use log::{info, warn};
 use once_cell::sync::Lazy;
-use reqwest::Client;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
 
+use crate::common::request::User;
 use crate::common::response::ResponseMessage;
 use crate::config::TELEGRAM_URL;
 
 static TELEGRAM_CLIENT: Lazy<Client> = Lazy::new(Client::new);
 
-pub async fn send_message<'a>(response_message: &ResponseMessage, chat_db_id: &ChatId) {
-    match TELEGRAM_CLIENT
-        .post(
-            TELEGRAM_URL
-                .get()
-                .unwrap()
-                .join(match response_message {
-                    ResponseMessage::Text { .. } => "sendMessage",
-                    ResponseMessage::Photo { .. } => "sendPhoto",
-                    ResponseMessage::Sticker { .. } => "sendSticker",
-                    ResponseMessage::Video { .. } => "sendVideo",
-                    ResponseMessage::VideoNote { .. } => "sendVideoNote",
-                    ResponseMessage::Voice { .. } => "sendVoice",
-                    ResponseMessage::Audio { .. } => "sendAudio",
-                    ResponseMessage::Document { .. } => "sendDocument",
-                    ResponseMessage::Animation { .. } => "sendAnimation",
-                })
-                .unwrap(),
-        )
-        .json(&response_message)
-        .send()
-        .await
-    {
+// New files are uploaded as multipart form, other messages are sent as json
+fn with_body(request: RequestBuilder, response_message: &ResponseMessage) -> RequestBuilder {
+    match response_message {
+        ResponseMessage::NewDocument {
+            base_body,
+            file_name,
+            content,
+            caption,
+        } => {
+            let mut form = Form::new().text("chat_id", base_body.chat_id.to_string());
+            if let Some(reply_to_message_id) = base_body.reply_to_message_id {
+                form = form.text("reply_to_message_id", reply_to_message_id.to_string());
+            }
+            if let Some(caption) = caption {
+                form = form.text("caption", caption.to_string());
+            }
+            request.multipart(form.part(
+                "document",
+                Part::text(content.to_string()).file_name(file_name.to_string()),
+            ))
+        }
+        _ => request.json(response_message),
+    }
+}
+
+pub async fn send_message(response_message: &ResponseMessage, chat_db_id: &ChatId) {
+    send_message_with_id(response_message, chat_db_id).await;
+}
+
+// Id of the sent message, for album it's id of its first part
+pub async fn send_message_with_id(
+    response_message: &ResponseMessage,
+    chat_db_id: &ChatId,
+) -> Option<i64> {
+    let Some(telegram_url) = TELEGRAM_URL.get() else {
+        warn!(
+            "Telegram url is not set, message isn't sent for {:?}",
+            chat_db_id
+        );
+        return None;
+    };
+    let request = TELEGRAM_CLIENT.post(
+        telegram_url
+            .join(match response_message {
+                ResponseMessage::Text { .. } => "sendMessage",
+                ResponseMessage::Photo { .. } => "sendPhoto",
+                ResponseMessage::Sticker { .. } => "sendSticker",
+                ResponseMessage::Video { .. } => "sendVideo",
+                ResponseMessage::VideoNote { .. } => "sendVideoNote",
+                ResponseMessage::Voice { .. } => "sendVoice",
+                ResponseMessage::Audio { .. } => "sendAudio",
+                ResponseMessage::Document { .. } | ResponseMessage::NewDocument { .. } => {
+                    "sendDocument"
+                }
+                ResponseMessage::Animation { .. } => "sendAnimation",
+                ResponseMessage::MediaGroup { .. } => "sendMediaGroup",
+                ResponseMessage::Forward { .. } => "forwardMessage",
+                ResponseMessage::Copy { .. } => "copyMessage",
+                ResponseMessage::Dice { .. } => "sendDice",
+                ResponseMessage::Poll { .. } => "sendPoll",
+                ResponseMessage::Location { .. } => "sendLocation",
+                ResponseMessage::Contact { .. } => "sendContact",
+                ResponseMessage::Venue { .. } => "sendVenue",
+                ResponseMessage::EditText { .. } => "editMessageText",
+                ResponseMessage::Delete { .. } => "deleteMessage",
+            })
+            .unwrap(),
+    );
+    match with_body(request, response_message).send().await {
         Ok(response) => match response.status() {
             status_code if status_code == 200 => {
-                info!("Message success sent for {:?}", chat_db_id)
+                info!("Message success sent for {:?}", chat_db_id);
+                let body = response.json::<Value>().await.ok()?;
+                let result = &body["result"];
+                result["message_id"]
+                    .as_i64()
+                    .or_else(|| result[0]["message_id"].as_i64())
             }
             _ => {
                 warn!(
@@ -42,13 +97,132 @@ pub async fn send_message<'a>(response_message: &ResponseMessage, chat_db_id: &C
                     chat_db_id,
                     response_message
                 );
+                None
             }
         },
         Err(err) => {
             warn!(
                 "Send message failed: {} for {:?}. response_message: {:?}",
                 err, chat_db_id, response_message
-            )
+            );
+            None
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatMember {
+    user: User,
+}
+
+#[derive(Deserialize)]
+struct ChatAdministratorsResponse {
+    result: Vec<ChatMember>,
+}
+
+// Telegram ids of chat administrators, None if they are not available
+pub async fn chat_administrators(chat_id: i64) -> Option<Vec<i64>> {
+    let url = TELEGRAM_URL.get()?.join("getChatAdministrators").ok()?;
+    match TELEGRAM_CLIENT
+        .post(url)
+        .json(&json!({ "chat_id": chat_id }))
+        .send()
+        .await
+    {
+        Ok(response) if response.status() == 200 => {
+            match response.json::<ChatAdministratorsResponse>().await {
+                Ok(administrators) => {
+                    Some(administrators.result.iter().map(|x| x.user.id).collect())
+                }
+                Err(err) => {
+                    warn!(
+                        "Chat administrators parsing failed: {} for {}",
+                        err, chat_id
+                    );
+                    None
+                }
+            }
+        }
+        Ok(response) => {
+            warn!(
+                "Get chat administrators failed: {:?} for {}",
+                response.text().await,
+                chat_id
+            );
+            None
+        }
+        Err(err) => {
+            warn!("Get chat administrators failed: {} for {}", err, chat_id);
+            None
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct File {
+    file_size: Option<u64>,
+    file_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FileResponse {
+    result: File,
+}
+
+// Text of the file, None if it is not available, too large or not a text
+pub async fn download_text_file(file_id: &str, max_size: u64) -> Option<String> {
+    let telegram_url = TELEGRAM_URL.get()?;
+    let file = match TELEGRAM_CLIENT
+        .post(telegram_url.join("getFile").ok()?)
+        .json(&json!({ "file_id": file_id }))
+        .send()
+        .await
+    {
+        Ok(response) if response.status() == 200 => match response.json::<FileResponse>().await {
+            Ok(file) => file.result,
+            Err(err) => {
+                warn!("File parsing failed: {} for {}", err, file_id);
+                return None;
+            }
+        },
+        Ok(response) => {
+            warn!(
+                "Get file failed: {:?} for {}",
+                response.text().await,
+                file_id
+            );
+            return None;
+        }
+        Err(err) => {
+            warn!("Get file failed: {} for {}", err, file_id);
+            return None;
+        }
+    };
+    if file.file_size.unwrap_or_default() > max_size {
+        return None;
+    }
+    // Files are downloaded from the same host with "/file" before the bot token
+    let mut url = telegram_url.clone();
+    url.set_path(&format!("/file{}{}", telegram_url.path(), file.file_path?));
+    match TELEGRAM_CLIENT.get(url).send().await {
+        Ok(response) if response.status() == 200 => {
+            let bytes = response.bytes().await.ok()?;
+            match bytes.len() as u64 > max_size {
+                true => None,
+                false => String::from_utf8(bytes.to_vec()).ok(),
+            }
+        }
+        Ok(response) => {
+            warn!(
+                "Download file failed: {} for {}",
+                response.status(),
+                file_id
+            );
+            None
+        }
+        Err(err) => {
+            warn!("Download file failed: {} for {}", err, file_id);
+            None
         }
     }
 }
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sqlx::PgPool;
+
+use crate::common::db::{ChatId, Member as MemberDB, MemberId};
+use crate::common::error::ProcessError;
+use crate::common::locale::Phrase;
+use crate::common::user_service::{pretty_username, random_user_from_chat};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Placeholder {
+    // Sender of the triggering message
+    Author,
+    RandomMember,
+    Date,
+    Time,
+    // Text of the triggering message
+    Text,
+    // Random integer from range including bounds: {rand:1-100}
+    Random { min: i64, max: i64 },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TemplatePart<'a> {
+    Literal(&'a str),
+    Placeholder(Placeholder),
+}
+
+pub struct TemplateContext {
+    pub author: String,
+    pub random_member: Option<String>,
+    pub text: String,
+    pub now: DateTime<Utc>,
+}
+
+impl Placeholder {
+    fn from_name(name: &str) -> Result<Self, Phrase> {
+        match name {
+            "author" => Ok(Placeholder::Author),
+            "random_member" => Ok(Placeholder::RandomMember),
+            "date" => Ok(Placeholder::Date),
+            "time" => Ok(Placeholder::Time),
+            "text" => Ok(Placeholder::Text),
+            x => match x.strip_prefix("rand:").and_then(|x| x.split_once('-')) {
+                Some((min, max)) => match (min.parse::<i64>(), max.parse::<i64>()) {
+                    (Ok(min), Ok(max)) if min <= max => Ok(Placeholder::Random { min, max }),
+                    _ => Err(Phrase::InvalidTemplateRange),
+                },
+                None => Err(Phrase::UnknownPlaceholder),
+            },
+        }
+    }
+
+    fn render(&self, context: &TemplateContext) -> String {
+        match self {
+            Placeholder::Author => context.author.to_string(),
+            Placeholder::RandomMember => context.random_member.clone().unwrap_or_default(),
+            Placeholder::Date => context.now.format("%d.%m.%Y").to_string(),
+            Placeholder::Time => context.now.format("%H:%M").to_string(),
+            Placeholder::Text => context.text.to_string(),
+            Placeholder::Random { min, max } => {
+                rand::thread_rng().gen_range(*min..=*max).to_string()
+            }
+        }
+    }
+}
+
+// Name of placeholder at the start of text: "{rand:1-100}..." -> "rand:1-100"
+fn placeholder_name(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix('{')?;
+    let end = inner.find(|x: char| !(x.is_ascii_alphanumeric() || ['_', ':', '-'].contains(&x)))?;
+    match end > 0 && inner[end..].starts_with('}') {
+        true => Some(&inner[..end]),
+        false => None,
+    }
+}
+
+// Braces which don't form a placeholder are kept as is, "{{" and "}}" are escaped braces
+pub fn parse_template(text: &str) -> Result<Vec<TemplatePart<'_>>, Phrase> {
+    let mut parts = vec![];
+    let mut rest = text;
+    while let Some(index) = rest.find(['{', '}']) {
+        let (literal, tail) = rest.split_at(index);
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            parts.push(TemplatePart::Literal(&tail[..1]));
+            rest = &tail[2..];
+            continue;
+        }
+        match placeholder_name(tail) {
+            Some(name) => {
+                parts.push(TemplatePart::Placeholder(Placeholder::from_name(name)?));
+                rest = &tail[name.len() + 2..];
+            }
+            None => {
+                parts.push(TemplatePart::Literal(&tail[..1]));
+                rest = &tail[1..];
+            }
+        }
+    }
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Literal(rest));
+    }
+    Ok(parts)
+}
+
+pub fn render_parts(parts: &[TemplatePart], context: &TemplateContext) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            TemplatePart::Literal(literal) => literal.to_string(),
+            TemplatePart::Placeholder(placeholder) => placeholder.render(context),
+        })
+        .collect()
+}
+
+pub async fn render_template(
+    pool: &PgPool,
+    template: &str,
+    chat_db_id: &ChatId,
+    member_db_id: &MemberId,
+    text: &str,
+) -> Result<String, ProcessError> {
+    let parts = parse_template(template).map_err(|message| ProcessError::Feedback { message })?;
+    // Members are requested only if they are used in template
+    let author = match parts.contains(&TemplatePart::Placeholder(Placeholder::Author)) {
+        true => MemberDB::one_by_id(pool, member_db_id)
+            .await
+            .map(|x| pretty_username(&x))
+            .unwrap_or_default(),
+        false => String::new(),
+    };
+    render_with_author(pool, &parts, chat_db_id, author, text).await
+}
+
+// Author is given by name, because it can be not a member of chat as a bot
+pub async fn render_template_by_author(
+    pool: &PgPool,
+    template: &str,
+    chat_db_id: &ChatId,
+    author: String,
+) -> Result<String, ProcessError> {
+    let parts = parse_template(template).map_err(|message| ProcessError::Feedback { message })?;
+    render_with_author(pool, &parts, chat_db_id, author, "").await
+}
+
+async fn render_with_author(
+    pool: &PgPool,
+    parts: &[TemplatePart<'_>],
+    chat_db_id: &ChatId,
+    author: String,
+    text: &str,
+) -> Result<String, ProcessError> {
+    let random_member = match parts.contains(&TemplatePart::Placeholder(Placeholder::RandomMember))
+    {
+        true => Some(pretty_username(
+            &random_user_from_chat(pool, chat_db_id).await?,
+        )),
+        false => None,
+    };
+    Ok(render_parts(
+        parts,
+        &TemplateContext {
+            author,
+            random_member,
+            text: text.to_string(),
+            now: Utc::now(),
+        },
+    ))
+}
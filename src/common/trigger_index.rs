@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+
+use crate::common::chat_cache::{ChatCache, ChatSources};
+use crate::common::db::{AnswerEntity, AnswerPack, Chat as ChatDB, ChatId, KeyNormalization};
+use crate::common::lexer::{stem_text, trigram_similarity};
+
+pub struct TriggerIndex {
+    pub key_normalization: KeyNormalization,
+    // Chats whose keys are indexed, so the index is outdated on change of any of them
+    sources: Vec<ChatId>,
+    keys: Vec<String>,
+}
+
+impl TriggerIndex {
+    // Key most similar to normalized text and its similarity in percent. With word forms
+    // matching stemmed text is compared to stemmed keys
+    pub fn most_similar(&self, text: &str, min_similarity: i16) -> Option<(String, i16)> {
+        let text = match self.key_normalization {
+            KeyNormalization::Lowercase => text.to_string(),
+            KeyNormalization::Stem => stem_text(text),
+        };
+        self.keys
+            .iter()
+            .map(|key| ((trigram_similarity(&text, key) * 100.0) as i16, key))
+            .filter(|(similarity, _)| *similarity >= min_similarity)
+            .max_by_key(|(similarity, _)| *similarity)
+            .map(|(similarity, key)| (key.clone(), similarity))
+    }
+}
+
+impl ChatSources for TriggerIndex {
+    fn sources(&self) -> &[ChatId] {
+        &self.sources
+    }
+}
+
+static TRIGGER_INDEX_CACHE: Lazy<ChatCache<TriggerIndex>> = Lazy::new(Default::default);
+
+async fn build_trigger_index(pool: &PgPool, chat_id: &ChatId) -> TriggerIndex {
+    let key_normalization = ChatDB::key_normalization(pool, chat_id)
+        .await
+        .unwrap_or_default();
+    let sources = AnswerPack::source_chats(pool, chat_id).await;
+    let mut keys = AnswerEntity::trigger_keys(pool, &sources)
+        .await
+        .into_iter()
+        .map(|(key, stemmed_key)| match key_normalization {
+            KeyNormalization::Lowercase => key,
+            KeyNormalization::Stem => stemmed_key.unwrap_or_else(|| stem_text(&key)),
+        })
+        .collect::<Vec<String>>();
+    keys.sort();
+    keys.dedup();
+    TriggerIndex {
+        key_normalization,
+        sources,
+        keys,
+    }
+}
+
+pub async fn trigger_index(pool: &PgPool, chat_id: &ChatId) -> Arc<TriggerIndex> {
+    TRIGGER_INDEX_CACHE
+        .get_or_build(pool, chat_id, build_trigger_index(pool, chat_id))
+        .await
+}
+
+// Indexes of chats subscribed to the pack of the chat are invalidated too
+pub fn invalidate_trigger_index(pool: &PgPool, chat_id: &ChatId) {
+    TRIGGER_INDEX_CACHE.invalidate(pool, chat_id);
+}
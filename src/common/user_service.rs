@@ -3,9 +3,19 @@ use rand::Rng;
 use sqlx::{PgPool, Pool, Postgres};
 use tokio::try_join;
 
-use crate::common::db::{Chat as ChatDB, ChatId, ChatToMemberId, Member as MemberDB, MemberId};
+use crate::common::answer_entity_service::invalidate_indexes;
+use crate::common::command_parser::MemberArgument;
+use crate::common::db::{
+    Chat as ChatDB, ChatId, ChatToMemberId, KeyNormalization, Language, Member as MemberDB,
+    MemberId,
+};
 use crate::common::error::ProcessError;
-use crate::common::request::{Chat as ChatRequest, User as UserRequest};
+use crate::common::locale::{translate, Phrase};
+use crate::common::request::{
+    Chat as ChatRequest, ChatMemberUpdated, MessageBody, MessageExt, User as UserRequest,
+};
+use crate::common::response::{BaseBody, LinkPreviewOption, ResponseMessage};
+use crate::config::BOT_GREETING;
 
 fn chat_title(chat: &ChatRequest) -> String {
     match chat {
@@ -41,10 +51,20 @@ pub fn pretty_username(member_db: &MemberDB) -> String {
     }
 }
 
-async fn process_chat<'a>(
-    pool: &Pool<Postgres>,
-    chat: &ChatRequest,
-) -> Result<ChatId, ProcessError<'a>> {
+// Name of telegram user, who can be not a member in database yet
+pub fn pretty_user_name(user: &UserRequest) -> String {
+    let first_name = user.first_name.as_deref().unwrap_or_default();
+    let last_name = user.last_name.as_deref().unwrap_or_default();
+    match user.username.as_deref() {
+        _ if !first_name.is_empty() || !last_name.is_empty() => {
+            format!("{first_name} {last_name}").trim().to_string()
+        }
+        Some(username) if !username.is_empty() => username.to_string(),
+        _ => user.id.to_string(),
+    }
+}
+
+async fn process_chat(pool: &Pool<Postgres>, chat: &ChatRequest) -> Result<ChatId, ProcessError> {
     let new_chat_name = chat_title(chat);
     if let Some((db_id, chat_name)) = ChatDB::id_and_name(pool, chat.id).await {
         if new_chat_name != chat_name {
@@ -81,11 +101,11 @@ async fn process_chat<'a>(
     }
 }
 
-async fn process_user<'a>(
+async fn process_user(
     pool: &Pool<Postgres>,
     user: &UserRequest,
     is_bot_callback: &bool,
-) -> Result<MemberId, ProcessError<'a>> {
+) -> Result<MemberId, ProcessError> {
     if user.is_bot && !is_bot_callback {
         info!("User {user_id} is bot, Stop process", user_id = user.id);
         return Err(ProcessError::Stop);
@@ -135,11 +155,11 @@ async fn process_user<'a>(
     }
 }
 
-pub async fn bind_user_to_chat<'a>(
+pub async fn bind_user_to_chat(
     pool: &Pool<Postgres>,
     member_id: MemberId,
     chat_id: ChatId,
-) -> Result<(MemberId, ChatId, ChatToMemberId), ProcessError<'a>> {
+) -> Result<(MemberId, ChatId, ChatToMemberId), ProcessError> {
     match MemberDB::update_chat_to_member_bind(pool, &member_id, &chat_id).await {
         Some(chat_to_member_id) => Ok((member_id, chat_id, chat_to_member_id)),
         None => match MemberDB::bind_to_chat(pool, &member_id, &chat_id).await {
@@ -163,12 +183,12 @@ pub async fn bind_user_to_chat<'a>(
     }
 }
 
-pub async fn process_user_and_chat<'a>(
+pub async fn process_user_and_chat(
     pool: &Pool<Postgres>,
     user: &UserRequest,
     chat: &ChatRequest,
     is_bot_callback: &bool,
-) -> Result<(MemberId, ChatId, ChatToMemberId), ProcessError<'a>> {
+) -> Result<(MemberId, ChatId, ChatToMemberId), ProcessError> {
     match try_join!(
         process_user(pool, user, is_bot_callback),
         process_chat(pool, chat)
@@ -178,55 +198,186 @@ pub async fn process_user_and_chat<'a>(
     }
 }
 
-pub async fn random_user_from_chat<'a>(
+// Chat is active while the bot is in it. Greeting is returned, when the bot is added
+pub async fn process_my_chat_member(
+    pool: &PgPool,
+    chat_member_updated: &ChatMemberUpdated,
+) -> Option<(ChatId, ResponseMessage)> {
+    let chat_db_id = process_chat(pool, &chat_member_updated.chat).await.ok()?;
+    let is_present = chat_member_updated.new_chat_member.is_present();
+    if let Err(err) = ChatDB::update_is_active(pool, &chat_db_id, is_present).await {
+        warn!("Chat {:?} activity update error: {}", chat_db_id, err);
+        return None;
+    }
+    info!(
+        "Chat {:?} activity updated to: {} by status: {:?}",
+        chat_db_id, is_present, chat_member_updated.new_chat_member.status
+    );
+    if !is_present || chat_member_updated.old_chat_member.is_present() {
+        return None;
+    }
+    if let Ok(member_db_id) = process_user(pool, &chat_member_updated.from, &false).await {
+        bind_user_to_chat(pool, member_db_id, chat_db_id.clone())
+            .await
+            .ok();
+    }
+    if !*BOT_GREETING {
+        return None;
+    }
+    let language = ChatDB::language(pool, &chat_db_id)
+        .await
+        .unwrap_or_default();
+    let greeting = ResponseMessage::Text {
+        base_body: BaseBody {
+            chat_id: chat_member_updated.chat.id,
+            reply_to_message_id: None,
+            reply_markup: None,
+        },
+        text: translate(&language, Phrase::Greeting),
+        link_preview_options: LinkPreviewOption { is_disabled: false },
+    };
+    Some((chat_db_id, greeting))
+}
+
+// Service messages about members of chat, true if the message is one of them
+pub async fn process_members_message(
+    pool: &PgPool,
+    message_body: &MessageBody,
+    chat_db_id: &ChatId,
+) -> bool {
+    match &message_body.ext {
+        MessageExt::NewChatMembers { new_chat_members } => {
+            for user in new_chat_members {
+                if let Ok(member_db_id) = process_user(pool, user, &false).await {
+                    bind_user_to_chat(pool, member_db_id, chat_db_id.clone())
+                        .await
+                        .ok();
+                }
+            }
+        }
+        MessageExt::LeftChatMember { left_chat_member } => {
+            if let Some(member) = MemberDB::one_by_member_id(pool, left_chat_member.id).await {
+                match MemberDB::leave_chat(pool, &member.id, chat_db_id).await {
+                    Ok(_) => info!("Member {:?} left Chat {:?}", member.id, chat_db_id),
+                    Err(err) => warn!(
+                        "Member {:?} leaving Chat {:?} error: {}",
+                        member.id, chat_db_id, err
+                    ),
+                }
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+// Service messages about migration of chat to supergroup, true if the message is one of them.
+// It's processed before chat of the message is created, because the old chat can be already
+// migrated. Both chats send such message, so the second one finds nothing to migrate
+pub async fn process_migration_message(pool: &PgPool, message_body: &MessageBody) -> bool {
+    let chat_id = message_body.base.chat.id;
+    let (old_chat_id, new_chat_id) = match &message_body.ext {
+        MessageExt::MigrateToChat { migrate_to_chat_id } => (chat_id, *migrate_to_chat_id),
+        MessageExt::MigrateFromChat {
+            migrate_from_chat_id,
+        } => (*migrate_from_chat_id, chat_id),
+        _ => return false,
+    };
+    match ChatDB::migrate(pool, old_chat_id, new_chat_id).await {
+        Ok(Some((chat_db_id, replaced_chat_db_id))) => {
+            for chat_db_id in [Some(&chat_db_id), replaced_chat_db_id.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                invalidate_indexes(pool, chat_db_id);
+            }
+            info!(
+                "Chat {} migrated to: {}. Record: {:?}",
+                old_chat_id, new_chat_id, chat_db_id
+            )
+        }
+        Ok(None) => info!("Chat {} is already migrated", old_chat_id),
+        Err(err) => warn!(
+            "Chat {} migration to: {} error: {}",
+            old_chat_id, new_chat_id, err
+        ),
+    }
+    true
+}
+
+pub async fn random_user_from_chat(
+    pool: &PgPool,
+    chat_id: &ChatId,
+) -> Result<MemberDB, ProcessError> {
+    random_user_from_chat_except(pool, chat_id, &[]).await
+}
+
+pub async fn random_user_from_chat_except(
     pool: &PgPool,
     chat_id: &ChatId,
-) -> Result<MemberDB, ProcessError<'a>> {
-    let member_ids: Vec<MemberId> = MemberDB::chat_members(&pool.clone(), chat_id).await;
+    excluded_ids: &[MemberId],
+) -> Result<MemberDB, ProcessError> {
+    let member_ids: Vec<MemberId> = MemberDB::chat_members(&pool.clone(), chat_id)
+        .await
+        .into_iter()
+        .filter(|x| !excluded_ids.contains(x))
+        .collect();
     if member_ids.is_empty() {
         Err(ProcessError::Feedback {
-            message: "Не найдено пользователей в чате",
+            message: Phrase::NoMembersFound,
         })
     } else {
         let member_id = &member_ids[rand::thread_rng().gen_range(0..member_ids.len())];
         match MemberDB::one_by_id(pool, member_id).await {
             None => Err(ProcessError::Feedback {
-                message: "Не найдено пользователей в чате",
+                message: Phrase::NoMembersFound,
             }),
             Some(member) => Ok(member),
         }
     }
 }
 
-pub async fn substring_answer_chance<'a>(
+pub async fn member_from_argument(
     pool: &PgPool,
     chat_id: &ChatId,
-) -> Result<i16, ProcessError<'a>> {
+    member_argument: &MemberArgument<'_>,
+) -> Result<MemberDB, ProcessError> {
+    match member_argument {
+        MemberArgument::Username(username) => {
+            MemberDB::one_in_chat_by_username(pool, chat_id, username).await
+        }
+        MemberArgument::UserId(user_id) => {
+            MemberDB::one_in_chat_by_member_id(pool, chat_id, *user_id).await
+        }
+    }
+    .ok_or(ProcessError::Feedback {
+        message: Phrase::MemberNotFound,
+    })
+}
+
+pub async fn substring_answer_chance(pool: &PgPool, chat_id: &ChatId) -> Result<i16, ProcessError> {
     match ChatDB::substring_answer_chance(pool, chat_id).await {
         None => Err(ProcessError::Feedback {
-            message: "Не заполнен процент срабатывания подстрок",
+            message: Phrase::NoSubstringChance,
         }),
         Some(substring_answer_chance) => Ok(substring_answer_chance),
     }
 }
 
-pub async fn morph_answer_chance<'a>(
-    pool: &PgPool,
-    chat_id: &ChatId,
-) -> Result<i16, ProcessError<'a>> {
+pub async fn morph_answer_chance(pool: &PgPool, chat_id: &ChatId) -> Result<i16, ProcessError> {
     match ChatDB::morph_answer_chance(pool, chat_id).await {
         None => Err(ProcessError::Feedback {
-            message: "Не заполнен процент срабатывания бреда",
+            message: Phrase::NoMorphChance,
         }),
         Some(morph_answer_chance) => Ok(morph_answer_chance),
     }
 }
 
-pub async fn set_substring_answer_chance<'a>(
+pub async fn set_substring_answer_chance(
     pool: &PgPool,
     chat_id: &ChatId,
     answer_chance: i16,
-) -> Result<(), ProcessError<'a>> {
+) -> Result<(), ProcessError> {
     match ChatDB::update_substring_answer_chance(pool, chat_id, answer_chance).await {
         Ok(_) => {
             info!(
@@ -241,17 +392,17 @@ pub async fn set_substring_answer_chance<'a>(
                 err, chat_id
             );
             Err(ProcessError::Feedback {
-                message: "Произошла ошибка обновления процента срабатывания подстрок",
+                message: Phrase::SubstringChanceError,
             })
         }
     }
 }
 
-pub async fn set_morph_answer_chance<'a>(
+pub async fn set_morph_answer_chance(
     pool: &PgPool,
     chat_id: &ChatId,
     answer_chance: i16,
-) -> Result<(), ProcessError<'a>> {
+) -> Result<(), ProcessError> {
     match ChatDB::update_morph_answer_chance(pool, chat_id, answer_chance).await {
         Ok(_) => {
             info!(
@@ -266,7 +417,113 @@ pub async fn set_morph_answer_chance<'a>(
                 err, chat_id
             );
             Err(ProcessError::Feedback {
-                message: "Произошла ошибка обновления процента срабатывания бреда",
+                message: Phrase::MorphChanceError,
+            })
+        }
+    }
+}
+
+pub async fn chat_language(pool: &PgPool, chat_id: &ChatId) -> Result<Language, ProcessError> {
+    match ChatDB::language(pool, chat_id).await {
+        None => Err(ProcessError::Feedback {
+            message: Phrase::NoLanguage,
+        }),
+        Some(language) => Ok(language),
+    }
+}
+
+pub async fn set_chat_language(
+    pool: &PgPool,
+    chat_id: &ChatId,
+    language: &Language,
+) -> Result<(), ProcessError> {
+    match ChatDB::update_language(pool, chat_id, language).await {
+        Ok(_) => {
+            info!(
+                "update_language success to value: {:?} for {:?}",
+                language, chat_id
+            );
+            Ok(())
+        }
+        Err(err) => {
+            warn!(
+                "update_language failed with error: {} for {:?} ",
+                err, chat_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::LanguageError,
+            })
+        }
+    }
+}
+
+pub async fn key_normalization(
+    pool: &PgPool,
+    chat_id: &ChatId,
+) -> Result<KeyNormalization, ProcessError> {
+    match ChatDB::key_normalization(pool, chat_id).await {
+        None => Err(ProcessError::Feedback {
+            message: Phrase::NoWordForms,
+        }),
+        Some(key_normalization) => Ok(key_normalization),
+    }
+}
+
+pub async fn set_key_normalization(
+    pool: &PgPool,
+    chat_id: &ChatId,
+    key_normalization: &KeyNormalization,
+) -> Result<(), ProcessError> {
+    match ChatDB::update_key_normalization(pool, chat_id, key_normalization).await {
+        Ok(_) => {
+            invalidate_indexes(pool, chat_id);
+            info!(
+                "update_key_normalization success to value: {:?} for {:?}",
+                key_normalization, chat_id
+            );
+            Ok(())
+        }
+        Err(err) => {
+            warn!(
+                "update_key_normalization failed with error: {} for {:?} ",
+                err, chat_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::WordFormsError,
+            })
+        }
+    }
+}
+
+pub async fn trigger_similarity(pool: &PgPool, chat_id: &ChatId) -> Result<i16, ProcessError> {
+    match ChatDB::trigger_similarity(pool, chat_id).await {
+        None => Err(ProcessError::Feedback {
+            message: Phrase::NoTriggerSimilarity,
+        }),
+        Some(trigger_similarity) => Ok(trigger_similarity),
+    }
+}
+
+pub async fn set_trigger_similarity(
+    pool: &PgPool,
+    chat_id: &ChatId,
+    trigger_similarity: i16,
+) -> Result<(), ProcessError> {
+    match ChatDB::update_trigger_similarity(pool, chat_id, trigger_similarity).await {
+        Ok(_) => {
+            info!(
+                "update_trigger_similarity success to value: {} for {:?}",
+                trigger_similarity, chat_id
+            );
+            Ok(())
+        }
+        Err(err) => {
+            warn!(
+                "update_trigger_similarity failed with error: {} for {:?} ",
+                err, chat_id
+            );
+            Err(ProcessError::Feedback {
+                message: Phrase::TriggerSimilarityError,
             })
         }
     }
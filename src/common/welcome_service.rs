@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use log::info;
+use sqlx::PgPool;
+
+use crate::common::db::{Chat as ChatDB, ChatId, ChatWelcome, EntityContentType};
+use crate::common::request::{MessageBody, MessageExt, User};
+use crate::common::response::ResponseMessage;
+use crate::common::telegram_client::{send_message, send_message_with_id};
+use crate::common::template::render_template_by_author;
+use crate::common::user_service::pretty_user_name;
+
+// Welcome for the user, text welcome is rendered with the user as author of template
+pub async fn welcome_message(
+    pool: &PgPool,
+    chat_welcome: &ChatWelcome,
+    user: &User,
+    chat_id: i64,
+    message_id: i64,
+) -> Option<ResponseMessage> {
+    let mut value = chat_welcome.value.clone();
+    if chat_welcome.content_type == EntityContentType::Text {
+        if let Ok(rendered) = render_template_by_author(
+            pool,
+            &chat_welcome.value,
+            &chat_welcome.chat_id,
+            pretty_user_name(user),
+        )
+        .await
+        {
+            value = rendered;
+        }
+    }
+    ResponseMessage::from_content(
+        chat_welcome.content_type.clone(),
+        value,
+        chat_welcome.description.clone(),
+        chat_id,
+        message_id,
+        None,
+    )
+}
+
+// Every new member of the message is greeted separately
+pub async fn welcome_messages(
+    pool: &PgPool,
+    message_body: &MessageBody,
+    chat_db_id: &ChatId,
+) -> Vec<ResponseMessage> {
+    let MessageExt::NewChatMembers { new_chat_members } = &message_body.ext else {
+        return vec![];
+    };
+    let Some(chat_welcome) = ChatWelcome::by_chat(pool, chat_db_id).await else {
+        return vec![];
+    };
+    let is_welcome_for_bots = ChatDB::is_welcome_for_bots(pool, chat_db_id)
+        .await
+        .unwrap_or_default();
+    let mut messages = vec![];
+    for user in new_chat_members
+        .iter()
+        .filter(|x| is_welcome_for_bots || !x.is_bot)
+    {
+        messages.extend(
+            welcome_message(
+                pool,
+                &chat_welcome,
+                user,
+                message_body.base.chat.id,
+                message_body.base.message_id,
+            )
+            .await,
+        );
+    }
+    messages
+}
+
+// Welcomes are deleted after the delay of chat, if it's set
+pub async fn send_welcomes(pool: &PgPool, message_body: &MessageBody, chat_db_id: &ChatId) {
+    let delete_after = ChatDB::welcome_delete_after(pool, chat_db_id)
+        .await
+        .unwrap_or_default();
+    for welcome in welcome_messages(pool, message_body, chat_db_id).await {
+        let Some(message_id) = send_message_with_id(&welcome, chat_db_id).await else {
+            continue;
+        };
+        if delete_after > 0 {
+            let delete = ResponseMessage::Delete {
+                chat_id: message_body.base.chat.id,
+                message_id,
+            };
+            let chat_db_id = chat_db_id.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(delete_after as u64)).await;
+                info!("Welcome {} is deleted for {:?}", message_id, chat_db_id);
+                send_message(&delete, &chat_db_id).await;
+            });
+        }
+    }
+}
@@ -17,6 +17,25 @@ pub static DATABASE_URL: Lazy<String> = Lazy::new(|| {
 
 pub static TELEGRAM_URL: OnceCell<Url> = OnceCell::new();
 
+// Telegram ids of the bot owners separated by ",", owners have permissions in all chats
+pub static BOT_OWNER_IDS: Lazy<Vec<i64>> = Lazy::new(|| {
+    std::env::var("TELEGRAM_BOT_OWNER_IDS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|x| x.trim().parse::<i64>().ok())
+        .collect()
+});
+
+// Telegram file ids can be sent only by the bot, which received them
+pub static BOT_ID: Lazy<String> =
+    Lazy::new(|| std::env::var("TELEGRAM_BOT_ID").unwrap_or_default());
+
+// Bot greets chat, when it's added to it, unless TELEGRAM_BOT_GREETING is "false"
+pub static BOT_GREETING: Lazy<bool> = Lazy::new(|| {
+    std::env::var("TELEGRAM_BOT_GREETING")
+        .map(|x| x.trim() != "false")
+        .unwrap_or(true)
+});
 
 pub fn init_telegram_url(_override_url: Option<String>) {
     #[cfg(test)]
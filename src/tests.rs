@@ -2,3 +2,4 @@ mod helpers;
 mod test_command_parser;
 mod test_command_call;
 mod test_user_service;
+mod test_template;
@@ -3,10 +3,10 @@ pub mod functions {
     use axum::body::Body;
     use axum::response::Response;
     use http::Request;
+    use serde::Serialize;
     use sqlx::{query_as, PgPool, Pool, Postgres};
     use tower::ServiceExt;
 
-    use crate::common::request::RequestPayload;
     use crate::web_app;
 
     pub async fn chat_by_chat_id(
@@ -21,7 +21,7 @@ pub mod functions {
         .ok()
     }
 
-    pub async fn api_telegram_request(pool: PgPool, message: &RequestPayload) -> Response<Body> {
+    pub async fn api_telegram_request(pool: PgPool, message: &impl Serialize) -> Response<Body> {
         web_app(pool.clone())
             .await
             .oneshot(
@@ -42,6 +42,7 @@ pub mod fixtures {
     use sqlx::PgPool;
 
     use crate::common::db::{ChatId, ChatToMemberId, Member, MemberId};
+    use crate::common::permission_service::cache_chat_administrators;
     use crate::common::request::{
         CallbackQuery, Chat, Message, MessageBase, MessageBody, MessageExt, ReplyMarkup,
         ReplyMarkupButton, RequestPayload, User,
@@ -89,14 +90,17 @@ pub mod fixtures {
         (user, chat)
     }
 
+    // Existed member is the chat administrator, because telegram is not requested in tests
     pub async fn db_existed_chat_member(pool: &PgPool) -> (MemberId, ChatId, ChatToMemberId) {
+        let chat_db_id = chat_by_chat_id(pool, EXISTED_CHAT_ID).await.unwrap().id;
+        cache_chat_administrators(pool, &chat_db_id, vec![EXISTED_USER_ID]);
         bind_user_to_chat(
             pool,
             Member::one_by_member_id(pool, EXISTED_USER_ID)
                 .await
                 .unwrap()
                 .id,
-            chat_by_chat_id(pool, EXISTED_CHAT_ID).await.unwrap().id,
+            chat_db_id,
         )
         .await
         .unwrap()
@@ -117,9 +121,11 @@ pub mod fixtures {
                         chat: chat.clone(),
                         forward_from: None,
                         forward_from_chat: None,
+                        media_group_id: None,
                     },
                     ext: MessageExt::Text {
                         text: String::from(text),
+                        entities: vec![],
                     },
                 },
                 reply_markup: None,
@@ -143,9 +149,11 @@ pub mod fixtures {
                         chat: chat.clone(),
                         forward_from: None,
                         forward_from_chat: None,
+                        media_group_id: None,
                     },
                     ext: MessageExt::Text {
                         text: String::from(text),
+                        entities: vec![],
                     },
                 },
                 reply: Box::new(MessageBody {
@@ -155,9 +163,11 @@ pub mod fixtures {
                         chat: chat.clone(),
                         forward_from: None,
                         forward_from_chat: None,
+                        media_group_id: None,
                     },
                     ext: MessageExt::Text {
                         text: reply_text.to_string(),
+                        entities: vec![],
                     },
                 }),
                 reply_markup: None,
@@ -165,6 +175,65 @@ pub mod fixtures {
         }
     }
     
+    pub fn media_message(user: &User, chat: &Chat, ext: MessageExt) -> RequestPayload {
+        RequestPayload::Origin {
+            update_id: 6,
+            message: Message::Common {
+                direct: MessageBody {
+                    base: MessageBase {
+                        message_id: 6666,
+                        from: user.clone(),
+                        chat: chat.clone(),
+                        forward_from: None,
+                        forward_from_chat: None,
+                        media_group_id: None,
+                    },
+                    ext,
+                },
+                reply_markup: None,
+            },
+        }
+    }
+
+    pub fn replied_media_message(
+        user: &User,
+        chat: &Chat,
+        text: &str,
+        reply_ext: MessageExt,
+    ) -> RequestPayload {
+        RequestPayload::Origin {
+            update_id: 7,
+            message: Message::Replied {
+                direct: MessageBody {
+                    base: MessageBase {
+                        message_id: 2,
+                        from: user.clone(),
+                        chat: chat.clone(),
+                        forward_from: None,
+                        forward_from_chat: None,
+                        media_group_id: None,
+                    },
+                    ext: MessageExt::Text {
+                        text: String::from(text),
+                        entities: vec![],
+                    },
+                },
+                reply: Box::new(MessageBody {
+                    base: MessageBase {
+                        message_id: 1,
+                        from: user.clone(),
+                        chat: chat.clone(),
+                        forward_from: None,
+                        forward_from_chat: None,
+                        media_group_id: None,
+                    },
+                    ext: reply_ext,
+                }),
+                reply_markup: None,
+            },
+        }
+    }
+
     pub fn roll_callback_message(user: &User, chat: &Chat, reply_text: &str) -> RequestPayload {
         RequestPayload::Callback {
             update_id: 4,
@@ -179,9 +248,11 @@ pub mod fixtures {
                             chat: chat.clone(),
                             forward_from: None,
                             forward_from_chat: None,
+                            media_group_id: None,
                         },
                         ext: MessageExt::Text {
                             text: "Some Text".to_string(),
+                            entities: vec![],
                         },
                     },
                     reply: Box::new(MessageBody {
@@ -191,9 +262,11 @@ pub mod fixtures {
                             chat: chat.clone(),
                             forward_from: None,
                             forward_from_chat: None,
+                            media_group_id: None,
                         },
                         ext: MessageExt::Text {
                             text: reply_text.to_string(),
+                            entities: vec![],
                         },
                     }),
                     reply_markup: Some(ReplyMarkup {
@@ -203,6 +276,60 @@ pub mod fixtures {
                         }]],
                     }),
                 },
+                data: Some("".to_string()),
+            },
+        }
+    }
+
+    // Press of button with the data under bot answer on the command
+    pub fn button_callback_message(
+        user: &User,
+        chat: &Chat,
+        command_text: &str,
+        data: &str,
+    ) -> RequestPayload {
+        RequestPayload::Callback {
+            update_id: 5,
+            callback_query: CallbackQuery {
+                id: "2".to_string(),
+                from: user.clone(),
+                message: Message::Replied {
+                    direct: MessageBody {
+                        base: MessageBase {
+                            message_id: 3,
+                            from: bot_user(),
+                            chat: chat.clone(),
+                            forward_from: None,
+                            forward_from_chat: None,
+                            media_group_id: None,
+                        },
+                        ext: MessageExt::Text {
+                            text: "Some Text".to_string(),
+                            entities: vec![],
+                        },
+                    },
+                    reply: Box::new(MessageBody {
+                        base: MessageBase {
+                            message_id: 2,
+                            from: user.clone(),
+                            chat: chat.clone(),
+                            forward_from: None,
+                            forward_from_chat: None,
+                            media_group_id: None,
+                        },
+                        ext: MessageExt::Text {
+                            text: command_text.to_string(),
+                            entities: vec![],
+                        },
+                    }),
+                    reply_markup: Some(ReplyMarkup {
+                        inline_keyboard: vec![vec![ReplyMarkupButton {
+                            text: "1".to_string(),
+                            callback_data: data.to_string(),
+                        }]],
+                    }),
+                },
+                data: Some(data.to_string()),
             },
         }
     }
@@ -1,26 +1,75 @@
 #[cfg(test)]
 mod tests {
-    use crate::common::db::{AnswerEntity, Chat as ChatDB, ChatId, DictionaryEntity, EntityReactionType, MemberId};
+    use crate::common::archive_service::{
+        export_archive, import_archive, ChatArchive, ImportReport,
+    };
+    use crate::common::command_parser::Command;
+    use crate::common::db::{
+        AnswerEntity, AnswerPack, ArchivedAnswerEntity, Chat as ChatDB, ChatId, DictionaryEntity,
+        EntityContentType, EntityReactionType, Language, Member, MemberId,
+    };
     use crate::common::error::ProcessError;
     use assert_json_diff::assert_json_include;
+    use chrono::Utc;
     use serde_json::json;
-    use sqlx::PgPool;
+    use sqlx::{query, PgPool};
 
-    use crate::common::lexer::tokenize;
+    use crate::common::lexer::{tokenize, tokenize_with_entities, Token};
+    use crate::common::locale::{translate, Phrase};
     use crate::common::message_service::{handle_processor, Processor};
+    use crate::common::permission_service::cache_chat_administrators;
+    use crate::common::album_service::buffer_album_part;
+    use crate::common::request::{
+        Chat, Content, Dice, Location, Message, MessageBase, MessageBody, MessageEntity,
+        MessageEntityKind, MessageExt, Poll, PollOption, RequestPayload, User, Venue,
+    };
     use crate::common::response::ResponseMessage;
+    use crate::common::welcome_service::welcome_messages;
     use crate::tests::helpers::fixtures::{
-        db_existed_chat_member, default_origin_direct_text_message, replied_text_message,
-        request_existed_chat_user, roll_callback_message,
+        button_callback_message, db_existed_chat_member, default_origin_direct_text_message,
+        default_user, media_message, replied_media_message, replied_text_message,
+        request_existed_chat_user, roll_callback_message, EXISTED_USER_ID,
     };
 
+    // Tokens are made of message text as in message processing
+    fn payload_tokens(request_payload: &RequestPayload) -> Option<Vec<Token<'_>>> {
+        request_payload
+            .any_message()
+            .direct()
+            .ext
+            .raw_text()
+            .map(tokenize)
+    }
+
     async fn call_command_direct(pool: &PgPool, input_text: &str) -> ResponseMessage {
         let (user, chat) = request_existed_chat_user().await;
         let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(pool).await;
+        let request_payload = default_origin_direct_text_message(&user, &chat, input_text);
+        handle_processor(
+            &Processor::Command,
+            &payload_tokens(&request_payload),
+            &request_payload,
+            pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn call_command_replied(
+        pool: &PgPool,
+        input_text: &str,
+        reply_text: &str,
+    ) -> ResponseMessage {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(pool).await;
+        let request_payload = replied_text_message(&user, &chat, input_text, reply_text);
         handle_processor(
             &Processor::Command,
-            &Some(tokenize(input_text)),
-            &default_origin_direct_text_message(&user, &chat, input_text),
+            &payload_tokens(&request_payload),
+            &request_payload,
             pool,
             &member_db_id,
             &chat_db_id,
@@ -30,6 +79,67 @@ mod tests {
         .unwrap()
     }
 
+    // Text of response or of feedback on user error
+    async fn call_command_text(
+        pool: &PgPool,
+        input_text: &str,
+        reply_text: Option<&str>,
+    ) -> String {
+        let (user, _) = request_existed_chat_user().await;
+        let (member_db_id, _, _) = db_existed_chat_member(pool).await;
+        call_member_command_text(pool, &user, &member_db_id, input_text, reply_text).await
+    }
+
+    async fn call_member_command_text(
+        pool: &PgPool,
+        user: &User,
+        member_db_id: &MemberId,
+        input_text: &str,
+        reply_text: Option<&str>,
+    ) -> String {
+        let (_, chat) = request_existed_chat_user().await;
+        let (_, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(pool).await;
+        let request_payload = match reply_text {
+            Some(reply_text) => replied_text_message(user, &chat, input_text, reply_text),
+            None => default_origin_direct_text_message(user, &chat, input_text),
+        };
+        match handle_processor(
+            &Processor::Command,
+            &payload_tokens(&request_payload),
+            &request_payload,
+            pool,
+            member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        {
+            Ok(response_message) => json!(response_message)["text"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            Err(ProcessError::Feedback { message }) => translate(&Language::Ru, message),
+            Err(error) => panic!("Unexpected error: {error:?}"),
+        }
+    }
+
+    async fn is_replied_command_succeeded(pool: &PgPool, input_text: &str) -> bool {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(pool).await;
+        let request_payload = replied_text_message(&user, &chat, input_text, "replied_text_value");
+        handle_processor(
+            &Processor::Command,
+            &payload_tokens(&request_payload),
+            &request_payload,
+            pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        .is_ok()
+    }
+
     #[sqlx::test(
         migrations = "./migrations",
         fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
@@ -78,6 +188,8 @@ mod tests {
                 "хлеб про кого песня?",
                 "песня про него(неё): FirstName LastName",
             ),
+            ("bread who is cool?", "FirstName LastName is cool"),
+            ("bread about whom song?", "song about: FirstName LastName"),
         ] {
             assert_json_include!(
                 actual: json!(call_command_direct(&pool, input).await),
@@ -110,6 +222,8 @@ mod tests {
     async fn test_answer_chance_failure<'a>(pool: PgPool) {
         let (user, chat) = request_existed_chat_user().await;
         let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(&pool).await;
+        // Missing chat passes the permission check to fail on update
+        cache_chat_administrators(&pool, &ChatId::new(10550), vec![EXISTED_USER_ID]);
         for (input, user_id, chat_id, output) in [
             (
                 "хлеб процент string",
@@ -143,7 +257,7 @@ mod tests {
             ),
             (
                 "хлеб процент 10",
-                &MemberId::new(100500),
+                &member_db_id,
                 &ChatId::new(10550),
                 "Произошла ошибка обновления процента срабатывания подстрок",
             ),
@@ -162,7 +276,7 @@ mod tests {
             .await
             .unwrap_err()
             {
-                assert_eq!(msg, output);
+                assert_eq!(translate(&Language::Ru, msg), output);
             } else {
                 panic!("Assertion error: {}", output);
             }
@@ -236,7 +350,7 @@ mod tests {
         .await
         .unwrap_err()
         {
-            assert_eq!(msg, "Ничего не было найдено");
+            assert_eq!(msg, Phrase::NothingFound);
         } else {
             panic!("Assertion error");
         }
@@ -296,7 +410,7 @@ mod tests {
         .await
         .unwrap_err()
         {
-            assert_eq!(msg, "Необходимо выбрать сообщение в ответ");
+            assert_eq!(msg, Phrase::NeedReply);
         } else {
             panic!("Assertion error");
         }
@@ -327,7 +441,7 @@ mod tests {
         .await
         .unwrap_err()
         {
-            assert_eq!(msg, "Ключей не найдено");
+            assert_eq!(msg, Phrase::NoKeysFound);
         } else {
             panic!("Assertion error");
         }
@@ -503,9 +617,1544 @@ mod tests {
                 "text": "Сделал"
             })
         );
-        let existed_values = DictionaryEntity::existed_values(&pool, &chat_db_id).await;
+        let existed_values = DictionaryEntity::values(&pool, &chat_db_id, 100, 0).await;
         for expected in ["булочка", "елочка", "батончик"] {
             assert!(existed_values.contains(&expected.to_string()))
         }
     }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_chat_language(pool: PgPool) {
+        let (_, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        for (input, output) in [
+            ("хлеб язык", "Язык чата: ru"),
+            ("хлеб процент 20", "Сделано"),
+            ("bread language EN", "Done"),
+            ("bread language", "Chat language: en"),
+            ("хлеб процент 20", "Done"),
+            ("хлеб язык ru", "Сделано"),
+        ] {
+            assert_json_include!(
+                actual: json!(call_command_direct(&pool, input).await),
+                expected: json!({"text": output})
+            );
+        }
+        assert_eq!(
+            Some(Language::Ru),
+            ChatDB::language(&pool, &chat_db_id).await
+        );
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_help_english(pool: PgPool) {
+        call_command_direct(&pool, "bread language en").await;
+        for (input, output) in [
+            ("bread help", "Hi. I am a bot and my name is Bread."),
+            ("bread help commands", "quote:"),
+            ("хлеб хелп механика", "Main elements"),
+            ("bread help nonsense", r#"Command: \"nonsense\""#),
+        ] {
+            assert!(json!(call_command_direct(&pool, input).await)
+                .get("text")
+                .unwrap()
+                .to_string()
+                .contains(output));
+        }
+    }
+
+    #[test]
+    fn test_translate_feedback() {
+        for (phrase, ru, en) in [
+            (Phrase::NothingFound, "Ничего не было найдено", "Nothing was found"),
+            (
+                Phrase::InvalidArgument(Command::AnswerChance),
+                "Указано неверное значение. Должно быть целое число от 0 до 100",
+                "Invalid value. Expected an integer from 0 to 100",
+            ),
+            (
+                Phrase::InvalidArgument(Command::ChatLanguage),
+                "Указано неверное значение. Должно быть одно из значений: ru, en",
+                "Invalid value. Expected one of: ru, en",
+            ),
+        ] {
+            assert_eq!(translate(&Language::Ru, phrase), ru);
+            assert_eq!(translate(&Language::En, phrase), en);
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(
+            path = "sqlx_fixtures",
+            scripts("default_chat", "default_user", "text_substring")
+        )
+    )]
+    async fn test_remember_quoted_keys(pool: PgPool) {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(&pool).await;
+        let input = "хлеб запомни \"Ну, привет\", «да, нет», ой\\, всё";
+        let request_payload = replied_text_message(&user, &chat, input, "substring_text_value");
+        let tokens = &Some(tokenize(input));
+        let result = handle_processor(
+            &Processor::Command,
+            tokens,
+            &request_payload,
+            &pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        .unwrap();
+        assert_json_include!(
+            actual: json!(result),
+            expected: json!({
+                "text": "Сделал"
+            })
+        );
+        for expected in ["ну, привет", "да, нет", "ой, все"] {
+            let existed_entities = AnswerEntity::find_values_by_keys(
+                &pool,
+                &chat_db_id,
+                &[expected.to_string()],
+                &EntityReactionType::Substring,
+            )
+            .await;
+            assert_eq!(existed_entities.len(), 1);
+            assert_eq!(existed_entities[0].value, "substring_text_value")
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_add_quoted_to_dictionary(pool: PgPool) {
+        let (_, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб добавь бред «ну, да», батон").await),
+            expected: json!({"text": "Сделал"})
+        );
+        let existed_values = DictionaryEntity::values(&pool, &chat_db_id, 100, 0).await;
+        for expected in ["ну, да", "батон"] {
+            assert!(existed_values.contains(&expected.to_string()))
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_couple(pool: PgPool) {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(&pool).await;
+        let second_member_db_id =
+            Member::create_member(&pool, 444555666, "", "Second", "Member", false)
+                .await
+                .unwrap();
+        Member::bind_to_chat(&pool, &second_member_db_id, &chat_db_id)
+            .await
+            .unwrap();
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб парочка @username").await),
+            expected: json!({"text": "FirstName LastName ❤️ Second Member"})
+        );
+        let text = "хлеб парочка Second Member";
+        let entities = vec![MessageEntity {
+            kind: MessageEntityKind::TextMention,
+            offset: 13,
+            length: 13,
+            url: None,
+            user: Some(User {
+                id: 444555666,
+                is_bot: false,
+                first_name: Some(String::from("Second")),
+                last_name: Some(String::from("Member")),
+                username: None,
+            }),
+        }];
+        for (input, tokens, output) in [
+            (
+                text,
+                tokenize_with_entities(text, &entities),
+                Ok("Second Member ❤️ FirstName LastName"),
+            ),
+            (
+                "хлеб парочка @nobody",
+                tokenize("хлеб парочка @nobody"),
+                Err(Phrase::MemberNotFound),
+            ),
+        ] {
+            let tokens = &Some(tokens);
+            let request_payload = default_origin_direct_text_message(&user, &chat, input);
+            let result = handle_processor(
+                &Processor::Command,
+                tokens,
+                &request_payload,
+                &pool,
+                &member_db_id,
+                &chat_db_id,
+                &chat_to_member_db_id,
+            )
+            .await;
+            match (result, output) {
+                (Ok(response), Ok(output)) => assert_json_include!(
+                    actual: json!(response),
+                    expected: json!({"text": output})
+                ),
+                (Err(ProcessError::Feedback { message }), Err(output)) => {
+                    assert_eq!(message, output)
+                }
+                (result, _) => panic!("Unexpected result for {input}: {result:?}"),
+            }
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_word_forms(pool: PgPool) {
+        assert!(is_replied_command_succeeded(&pool, "хлеб запомни пиво").await);
+        assert!(is_replied_command_succeeded(&pool, "хлеб запомни триггер холодное пиво").await);
+        for (input, output, is_found) in [
+            ("хлеб словоформы", "Учет словоформ: выкл", false),
+            ("хлеб словоформы вкл", "Сделано", true),
+            ("bread word forms", "Учет словоформ: вкл", true),
+            ("хлеб словоформы off", "Сделано", false),
+        ] {
+            assert_json_include!(
+                actual: json!(call_command_direct(&pool, input).await),
+                expected: json!({"text": output})
+            );
+            for (check_input, is_expected) in [
+                ("хлеб проверь подстроку пиво", true),
+                ("хлеб проверь подстроку пивом", is_found),
+                ("хлеб проверь триггер холодным пивом!", is_found),
+            ] {
+                assert_eq!(
+                    is_replied_command_succeeded(&pool, check_input).await,
+                    is_expected
+                );
+            }
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_phrase_substrings(pool: PgPool) {
+        let check_input = "хлеб проверь подстроку всем доброе утро, народ";
+        for (input, is_found) in [
+            (check_input, false),
+            ("хлеб запомни доброе утро, народ", true),
+            (check_input, true),
+            ("хлеб проверь подстроку недоброе утро", false),
+            ("хлеб проверь подстроку Народу", false),
+            ("хлеб проверь подстроку НАРОД!", true),
+            ("хлеб удали", true),
+            (check_input, false),
+        ] {
+            assert_eq!(
+                is_replied_command_succeeded(&pool, input).await,
+                is_found,
+                "{input}"
+            );
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_trigger_similarity(pool: PgPool) {
+        assert!(is_replied_command_succeeded(&pool, "хлеб запомни триггер привет всем").await);
+        assert!(!is_replied_command_succeeded(&pool, "хлеб проверь триггер привет всм").await);
+        for (input, output) in [
+            ("хлеб похожесть", "Порог похожести триггеров: 100%"),
+            ("хлеб похожесть 60", "Сделано"),
+            ("bread similarity", "Порог похожести триггеров: 60%"),
+            (
+                "хлеб проверь триггер привет всм",
+                "replied_text_value\n\nПохожесть на триггер «привет всем»: 64%",
+            ),
+            ("хлеб проверь триггер Привет всем", "replied_text_value"),
+        ] {
+            assert_json_include!(
+                actual: json!(call_command_direct(&pool, input).await),
+                expected: json!({"text": output})
+            );
+        }
+        assert!(!is_replied_command_succeeded(&pool, "хлеб проверь триггер пока всем").await);
+        // Cached keys are rebuilt after a new trigger is remembered
+        assert!(is_replied_command_succeeded(&pool, "хлеб запомни триггер пока всех").await);
+        assert!(is_replied_command_succeeded(&pool, "хлеб проверь триггер пока всем").await);
+        // With word forms matching stemmed text is compared to stemmed keys
+        assert!(is_replied_command_succeeded(&pool, "хлеб запомни триггер холодное пиво").await);
+        assert!(!is_replied_command_succeeded(&pool, "хлеб проверь триггер холодного пивка").await);
+        call_command_direct(&pool, "хлеб словоформы вкл").await;
+        assert!(is_replied_command_succeeded(&pool, "хлеб проверь триггер холодного пивка").await);
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_regex(pool: PgPool) {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(&pool).await;
+        let request_payload = replied_text_message(
+            &user,
+            &chat,
+            "хлеб запомни регулярку ^меня зовут (\\w+),? (?:и )?мне (\\d+)",
+            "Привет, $1! Тебе точно $2? Держи $5",
+        );
+        assert!(handle_processor(
+            &Processor::Command,
+            &payload_tokens(&request_payload),
+            &request_payload,
+            &pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        .is_ok());
+        assert_json_include!(
+            actual: json!(
+                call_command_direct(&pool, "хлеб проверь регулярку Меня зовут Вася, мне 30").await
+            ),
+            expected: json!({"text": "Привет, Вася! Тебе точно 30? Держи $5"})
+        );
+        assert!(!is_replied_command_succeeded(&pool, "хлеб проверь регулярку зовут Вася").await);
+        for (input, output) in [
+            ("хлеб запомни регулярку (привет", Phrase::InvalidRegex),
+            ("хлеб запомни регулярку \\w{1000}", Phrase::TooComplexRegex),
+        ] {
+            let request_payload = replied_text_message(&user, &chat, input, "replied_text_value");
+            assert_eq!(
+                handle_processor(
+                    &Processor::Command,
+                    &payload_tokens(&request_payload),
+                    &request_payload,
+                    &pool,
+                    &member_db_id,
+                    &chat_db_id,
+                    &chat_to_member_db_id,
+                )
+                .await
+                .err(),
+                Some(ProcessError::Feedback { message: output })
+            );
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_rename_regex(pool: PgPool) {
+        for (input, reply_text, output) in [
+            ("хлеб запомни триггер привет", Some("ответ триггера"), "Сделал"),
+            (
+                "хлеб запомни регулярку Привет\\s+(\\w+)",
+                Some("ответ регулярки"),
+                "Сделал",
+            ),
+            // Pattern is found by its source text and the new one is validated
+            (
+                "хлеб переименуй регулярку Привет\\s+(\\w+), (",
+                None,
+                "Некорректное регулярное выражение",
+            ),
+            (
+                "хлеб переименуй регулярку Привет\\s+(\\w+), Пока\\s+(\\w+)",
+                None,
+                "Сделано",
+            ),
+            ("хлеб проверь регулярку пока всем", None, "ответ регулярки"),
+            // Trigger on the same key is not renamed with the pattern
+            ("хлеб проверь триггер привет", None, "ответ триггера"),
+            ("хлеб переименуй триггер привет, здравствуй", None, "Сделано"),
+            ("хлеб проверь триггер здравствуй", None, "ответ триггера"),
+            ("хлеб проверь регулярку пока всем", None, "ответ регулярки"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, reply_text).await, output, "{input}");
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_template(pool: PgPool) {
+        assert_json_include!(
+            actual: json!(
+                call_command_direct(&pool, "хлеб шаблон {author}: {rand:5-5}, {{x}}").await
+            ),
+            expected: json!({"text": "FirstName LastName: 5, {x}"})
+        );
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(&pool).await;
+        for (reply_text, output) in [
+            (
+                "{random_member}, {x}",
+                Err(ProcessError::Feedback {
+                    message: Phrase::UnknownPlaceholder,
+                }),
+            ),
+            ("{random_member} сказал {text}", Ok(())),
+        ] {
+            let request_payload =
+                replied_text_message(&user, &chat, "хлеб запомни триггер привет", reply_text);
+            assert_eq!(
+                handle_processor(
+                    &Processor::Command,
+                    &payload_tokens(&request_payload),
+                    &request_payload,
+                    &pool,
+                    &member_db_id,
+                    &chat_db_id,
+                    &chat_to_member_db_id,
+                )
+                .await
+                .map(|_| ()),
+                output
+            );
+        }
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб проверь триггер Привет").await),
+            expected: json!({"text": "FirstName LastName сказал Привет"})
+        );
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_selection(pool: PgPool) {
+        for reply_text in ["первый", "второй", "третий"] {
+            call_command_replied(&pool, "хлеб запомни триггер сказка", reply_text).await;
+        }
+        for (input, outputs) in [
+            ("хлеб режим выбора триггер история сказка", vec!["Сделано"]),
+            (
+                "хлеб проверь триггер сказка",
+                vec!["первый", "второй", "третий", "третий"],
+            ),
+            ("хлеб режим выбора триггер цикл сказка", vec!["Сделано"]),
+            ("bread selection trigger сказка", vec!["сказка: цикл"]),
+            (
+                "хлеб проверь триггер сказка",
+                vec!["первый", "второй", "третий", "первый"],
+            ),
+            ("хлеб режим выбора триггер случайно сказка", vec!["Сделано"]),
+            (
+                "хлеб режим выбора триггер сказка, пока",
+                vec!["сказка: случайно\nпока: случайно"],
+            ),
+        ] {
+            for output in outputs {
+                assert_json_include!(
+                    actual: json!(call_command_direct(&pool, input).await),
+                    expected: json!({"text": output})
+                );
+            }
+        }
+        for (input, output) in [
+            ("хлеб вес", "Вес: сказка - 1"),
+            ("хлеб вес 50", "Сделано"),
+            ("хлеб вес", "Вес: сказка - 50"),
+        ] {
+            assert_json_include!(
+                actual: json!(call_command_replied(&pool, input, "второй").await),
+                expected: json!({"text": output})
+            );
+        }
+        assert!(!is_replied_command_succeeded(&pool, "хлеб вес 5").await);
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(
+            path = "sqlx_fixtures",
+            scripts("default_chat", "default_user", "text_substring")
+        )
+    )]
+    async fn test_trash(pool: PgPool) {
+        let (_, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        for (input, reply_text, output) in [
+            ("хлеб корзина", None, "Корзина пуста"),
+            (
+                "хлеб удали",
+                Some("substring_text_value"),
+                "Был удален контент на ключах: substring_key",
+            ),
+            ("хлеб проверь substring_key", None, "Ничего не было найдено"),
+            (
+                "хлеб верни",
+                None,
+                "Был восстановлен контент на ключах: substring_key",
+            ),
+            ("хлеб проверь substring_key", None, "substring_text_value"),
+            ("хлеб верни", None, "Ничего не было восстановлено"),
+            (
+                "хлеб удали",
+                Some("substring_text_value"),
+                "Был удален контент на ключах: substring_key",
+            ),
+            (
+                "хлеб верни",
+                Some("substring_text_value"),
+                "Был восстановлен контент на ключах: substring_key",
+            ),
+            (
+                "хлеб удали",
+                Some("substring_text_value"),
+                "Был удален контент на ключах: substring_key",
+            ),
+            ("хлеб очисти корзину 10д", None, "Удалено из корзины: 0"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, reply_text).await, output);
+        }
+        assert!(call_command_text(&pool, "bread trash", None)
+            .await
+            .starts_with(&format!(
+                "1) substring_key: «substring_text_value» - FirstName LastName, {}",
+                Utc::now().format("%d.%m.%Y")
+            )));
+        query("UPDATE answer_entities SET deleted_at = deleted_at - interval '11 days'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            call_command_text(&pool, "хлеб очисти корзину 10д", None).await,
+            "Удалено из корзины: 1"
+        );
+        assert!(AnswerEntity::deleted(&pool, &chat_db_id, 10)
+            .await
+            .is_empty());
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(
+            path = "sqlx_fixtures",
+            scripts("default_chat", "default_user", "text_substring")
+        )
+    )]
+    async fn test_history(pool: PgPool) {
+        for (input, reply_text, output) in [
+            ("хлеб история", None, "История изменений пуста"),
+            ("хлеб запомни батон, булочка", Some("хлебное"), "Сделал"),
+            (
+                "хлеб покажи ключи",
+                Some("хлебное"),
+                "батон (FirstName LastName), булочка (FirstName LastName)",
+            ),
+            (
+                "хлеб покажи ключи",
+                Some("substring_text_value"),
+                "substring_key",
+            ),
+            ("хлеб добавь бред батончик", None, "Сделал"),
+            ("хлеб процент подстрок 30", None, "Сделано"),
+            ("хлеб процент подстрок", None, "30"),
+            (
+                "хлеб удали",
+                Some("хлебное"),
+                "Был удален контент на ключах: батон, булочка",
+            ),
+        ] {
+            assert_eq!(call_command_text(&pool, input, reply_text).await, output);
+        }
+        let date = Utc::now().format("%d.%m.%Y").to_string();
+        let history = call_command_text(&pool, "хлеб история", None).await;
+        let lines = history
+            .lines()
+            .map(|x| x.split_once(' ').unwrap().1)
+            .collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 4);
+        for (line, action) in lines.iter().zip([
+            "FirstName LastName удалил: батон, булочка",
+            "FirstName LastName изменил настройку: процент подстрок 30",
+            "FirstName LastName добавил бред: батончик",
+            "FirstName LastName запомнил: батон, булочка",
+        ]) {
+            assert!(line.starts_with(&date));
+            assert!(line.ends_with(action), "{line}");
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_permissions(pool: PgPool) {
+        let (_, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        let mut user = default_user();
+        user.first_name = Some(String::from("Other"));
+        let member_db_id = Member::create_member(&pool, user.id, "other", "Other", "", false)
+            .await
+            .unwrap();
+        Member::bind_to_chat(&pool, &member_db_id, &chat_db_id)
+            .await
+            .unwrap();
+        for (input, reply_text, output, is_admin) in [
+            ("хлеб запомни булочка", Some("хлебное"), "Сделал", true),
+            ("хлеб запомни пирожок", Some("своё"), "Сделал", false),
+            (
+                "хлеб процент подстрок 30",
+                None,
+                "Недостаточно прав для команды",
+                false,
+            ),
+            ("хлеб процент подстрок", None, "12", false),
+            (
+                "хлеб удали",
+                Some("хлебное"),
+                "Недостаточно прав для команды",
+                false,
+            ),
+            (
+                "хлеб удали",
+                Some("своё"),
+                "Был удален контент на ключах: пирожок",
+                false,
+            ),
+            ("хлеб права", None, "Недостаточно прав для команды", false),
+            (
+                "хлеб права права владелец",
+                None,
+                "Нельзя назначить права выше своих",
+                true,
+            ),
+            ("хлеб права удали участник", None, "Сделано", true),
+            ("хлеб права процент админ", None, "Сделано", true),
+            ("хлеб права удали", None, "удали: участник", true),
+            (
+                "хлеб удали",
+                Some("хлебное"),
+                "Был удален контент на ключах: булочка",
+                false,
+            ),
+            ("хлеб права удали автор", None, "Сделано", true),
+            ("bread permissions delete", None, "удали: автор", true),
+        ] {
+            let text = match is_admin {
+                true => call_command_text(&pool, input, reply_text).await,
+                false => {
+                    call_member_command_text(&pool, &user, &member_db_id, input, reply_text).await
+                }
+            };
+            assert_eq!(text, output, "{input}");
+        }
+        let permissions = call_command_text(&pool, "хлеб права", None).await;
+        assert!(permissions.contains("верни: автор"));
+        assert!(permissions.contains("процент: админ"));
+        assert!(permissions.contains("удали: автор"));
+    }
+
+    async fn press_button(pool: &PgPool, command_text: &str, data: &str) -> ResponseMessage {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(pool).await;
+        handle_processor(
+            &Processor::Callback,
+            &None,
+            &button_callback_message(&user, &chat, command_text, data),
+            pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(
+            path = "sqlx_fixtures",
+            scripts("default_chat", "default_user", "text_substring", "text_trigger")
+        )
+    )]
+    async fn test_keys(pool: PgPool) {
+        let keys = (1..=11)
+            .map(|x| format!("ключ{x:02}"))
+            .collect::<Vec<String>>()
+            .join(", ");
+        call_command_replied(&pool, &format!("хлеб запомни триггер {keys}"), "значение").await;
+        let first_page = json!(call_command_direct(&pool, "хлеб ключи").await);
+        assert!(first_page["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("Страница: 1/2\n\nтриггер, текст:\n1. "));
+        let keyboard = first_page["reply_markup"]["inline_keyboard"]
+            .as_array()
+            .unwrap();
+        assert_eq!(keyboard.len(), 3);
+        assert_eq!(keyboard[0].as_array().unwrap().len(), 5);
+        assert_eq!(
+            keyboard[2],
+            json!([{"text": "→", "callback_data": "keys:1"}])
+        );
+        assert_json_include!(
+            actual: json!(press_button(&pool, "хлеб ключи", "keys:1").await),
+            expected: json!({
+                "message_id": 3,
+                "text": "Страница: 2/2\n\nтриггер, текст:\n1. ключ10\n2. ключ11\n\nподстрока, текст:\n3. substring_key",
+                "reply_markup": {"inline_keyboard": [
+                    [{"text": "1"}, {"text": "2"}, {"text": "3"}],
+                    [{"text": "←", "callback_data": "keys:0"}],
+                ]},
+            })
+        );
+        let search = json!(call_command_direct(&pool, "хлеб ключи *_key").await);
+        assert_eq!(
+            search["text"],
+            "Страница: 1/1\n\nтриггер, текст:\n1. trigger_key\n\nподстрока, текст:\n2. substring_key"
+        );
+        let data = search["reply_markup"]["inline_keyboard"][0][1]["callback_data"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_json_include!(
+            actual: json!(press_button(&pool, "хлеб ключи *_key", &data).await),
+            expected: json!({"reply_to_message_id": 3, "text": "substring_text_value"})
+        );
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб ключи ключ1").await),
+            expected: json!({"text": "Страница: 1/1\n\nтриггер, текст:\n1. ключ10\n2. ключ11"})
+        );
+        assert_eq!(
+            call_command_text(&pool, "хлеб ключи ключ_", None).await,
+            "Ключей не найдено"
+        );
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(
+            path = "sqlx_fixtures",
+            scripts("default_chat", "default_user", "text_trigger")
+        )
+    )]
+    async fn test_edit_keys(pool: PgPool) {
+        for (input, reply_text, output) in [
+            (
+                "хлеб запомни триггер булочка, батон, плюшка",
+                Some("хлебный ответ"),
+                "Сделал",
+            ),
+            (
+                "хлеб отвяжи булочка, сайка",
+                Some("хлебный ответ"),
+                "Были отвязаны ключи: булочка",
+            ),
+            ("хлеб проверь триггер булочка", None, "Ничего не было найдено"),
+            ("хлеб проверь триггер батон", None, "хлебный ответ"),
+            (
+                "хлеб отвяжи булочка",
+                Some("хлебный ответ"),
+                "Ничего не было удалено",
+            ),
+            ("хлеб переименуй триггер батон, багет", None, "Сделано"),
+            ("хлеб проверь триггер батон", None, "Ничего не было найдено"),
+            ("хлеб проверь триггер багет", None, "хлебный ответ"),
+            ("хлеб переименуй триггер батон, багет", None, "Ключей не найдено"),
+            (
+                "хлеб переименуй триггер батон",
+                None,
+                "Указано неверное значение. Должно быть старое и новое значения через \",\"",
+            ),
+            // Content already existing on the new key is not duplicated
+            ("хлеб переименуй триггер плюшка, багет", None, "Сделано"),
+            ("хлеб проверь триггер плюшка", None, "Ничего не было найдено"),
+            (
+                "хлеб перенеси триггер багет, trigger_key",
+                Some("хлебный ответ"),
+                "Сделано",
+            ),
+            ("хлеб проверь триггер багет", None, "Ничего не было найдено"),
+            (
+                "хлеб перенеси триггер багет, trigger_key",
+                Some("хлебный ответ"),
+                "Ключей не найдено",
+            ),
+        ] {
+            assert_eq!(call_command_text(&pool, input, reply_text).await, output);
+        }
+        let (_, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        let mut values = AnswerEntity::find_values_by_keys(
+            &pool,
+            &chat_db_id,
+            &["trigger_key".to_string()],
+            &EntityReactionType::Trigger,
+        )
+        .await
+        .into_iter()
+        .map(|x| x.value)
+        .collect::<Vec<String>>();
+        values.sort();
+        assert_eq!(values, vec!["trigger_text_value", "хлебный ответ"]);
+        assert!(call_command_text(&pool, "хлеб история", None)
+            .await
+            .contains("перенес контент: багет → trigger_key"));
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_dictionary(pool: PgPool) {
+        let words = (1..=25)
+            .map(|x| format!("Слово{x:02}"))
+            .collect::<Vec<String>>()
+            .join("\n");
+        for (input, reply_text, output) in [
+            ("хлеб словарь", None, "Словарь пуст"),
+            ("хлеб импорт бреда", Some(words.as_str()), "Добавлено слов: 25"),
+            (
+                "хлеб импорт бреда",
+                Some("слово01, хлебушек\n\nхлебушек"),
+                "Добавлено слов: 1",
+            ),
+            ("хлеб добавь бред слово02, батон", None, "Сделал"),
+            (
+                "хлеб удали бред слово01, сайка",
+                None,
+                "Были удалены слова: слово01",
+            ),
+            ("хлеб удали бред сайка", None, "Ничего не было удалено"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, reply_text).await, output);
+        }
+        let first_page = json!(call_command_direct(&pool, "хлеб словарь").await);
+        assert!(first_page["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("Страница: 1/2\n\n1. батон\n2. слово02\n"));
+        assert_eq!(
+            first_page["reply_markup"]["inline_keyboard"],
+            json!([[{"text": "→", "callback_data": "dictionary:1"}]])
+        );
+        assert_json_include!(
+            actual: json!(press_button(&pool, "хлеб словарь", "dictionary:1").await),
+            expected: json!({
+                "message_id": 3,
+                "text": "Страница: 2/2\n\n21. слово21\n22. слово22\n23. слово23\n24. слово24\n25. слово25\n26. хлебушек",
+                "reply_markup": {"inline_keyboard": [[{"text": "←", "callback_data": "dictionary:0"}]]},
+            })
+        );
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(
+            path = "sqlx_fixtures",
+            scripts("default_chat", "default_user", "text_trigger", "text_substring")
+        )
+    )]
+    async fn test_export_import(pool: PgPool) {
+        let (member_db_id, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        call_command_direct(&pool, "хлеб добавь бред батон").await;
+        let exported = json!(call_command_direct(&pool, "хлеб экспорт").await);
+        assert_eq!(exported["caption"], "Ответов: 2\nСлов: 1");
+        let mut archive =
+            serde_json::from_str::<ChatArchive>(exported["content"].as_str().unwrap()).unwrap();
+        assert_eq!(archive.version, 1);
+        assert_eq!(archive.dictionary, vec!["батон"]);
+        assert_eq!(archive.settings.language, Some(Language::Ru));
+        assert_eq!(
+            archive.answers[0],
+            ArchivedAnswerEntity {
+                key: "trigger_key".to_string(),
+                reaction_type: EntityReactionType::Trigger,
+                content_type: EntityContentType::Text,
+                value: "trigger_text_value".to_string(),
+                description: None,
+                file_unique_id: None,
+                weight: 1,
+                bot_specific: false,
+            }
+        );
+        let new_answer = ArchivedAnswerEntity {
+            key: "новый ключ".to_string(),
+            value: "новый ответ".to_string(),
+            ..archive.answers[0].clone()
+        };
+        archive.answers.push(new_answer.clone());
+        archive.answers.push(new_answer);
+        archive.answers.push(ArchivedAnswerEntity {
+            content_type: EntityContentType::Picture,
+            value: "file_id".to_string(),
+            file_unique_id: Some("file_unique_id".to_string()),
+            bot_specific: true,
+            ..archive.answers[0].clone()
+        });
+        archive.answers.push(ArchivedAnswerEntity {
+            key: "(".to_string(),
+            reaction_type: EntityReactionType::Regex,
+            ..archive.answers[0].clone()
+        });
+        archive.bot_id = "another_bot".to_string();
+        archive.dictionary.push("сайка".to_string());
+        archive.settings.trigger_similarity = Some(50);
+        let report = import_archive(&pool, &chat_db_id, &member_db_id, &archive)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                added_answers: 1,
+                existed_answers: 2,
+                added_words: 1,
+                existed_words: 1,
+                skipped_media: 1,
+                invalid_keys: 1,
+                conflicting_settings: vec![Phrase::TriggerSimilarity],
+            }
+        );
+        assert_eq!(
+            report.text(&Language::En),
+            "Answers added: 1\nWords added: 1\nAnswers already in the chat: 2\n\
+            Words already in the chat: 1\nSkipped media of another bot: 1\n\
+            Skipped invalid keys: 1\nChat settings are not changed: Trigger similarity threshold"
+        );
+        assert_eq!(
+            call_command_text(&pool, "хлеб проверь триггер новый ключ", None).await,
+            "новый ответ"
+        );
+        assert_eq!(
+            call_command_text(&pool, "хлеб импорт", Some("не архив")).await,
+            "Тип объекта редактирования не поддерживается"
+        );
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_import_content_of_another_bot(pool: PgPool) {
+        let (member_db_id, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        query(
+            "INSERT INTO answer_entities (is_active, created_at, updated_at, key, value, \
+            reaction_type, content_type, chat_id) \
+            VALUES (true, now(), now(), 'кубик', '🎲', 'TRIGGER', 'DICE', $1), \
+            (true, now(), now(), 'место', '{\"latitude\":1.0,\"longitude\":2.0}', \
+            'TRIGGER', 'LOCATION', $1), \
+            (true, now(), now(), 'пересылка', '-1001:2', 'TRIGGER', 'FORWARD', $1), \
+            (true, now(), now(), 'голос', 'file_id', 'TRIGGER', 'VOICE', $1)",
+        )
+        .bind(&chat_db_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let mut archive = export_archive(&pool, &chat_db_id).await;
+        assert_eq!(
+            archive
+                .answers
+                .iter()
+                .map(|x| (x.key.as_str(), x.bot_specific))
+                .collect::<Vec<_>>(),
+            vec![("кубик", false), ("место", false), ("пересылка", false), ("голос", true)]
+        );
+        archive.bot_id = "another_bot".to_string();
+        let new_chat_db_id = ChatDB::create_chat(&pool, -444433332221, "NewChat")
+            .await
+            .unwrap();
+        let report = import_archive(&pool, &new_chat_db_id, &member_db_id, &archive)
+            .await
+            .unwrap();
+        assert_eq!((report.added_answers, report.skipped_media), (3, 1));
+        let imported = AnswerEntity::archived(&pool, &new_chat_db_id).await;
+        assert_eq!(
+            imported
+                .iter()
+                .map(|x| (x.content_type.clone(), x.value.as_str()))
+                .collect::<Vec<_>>()[..2],
+            [
+                (EntityContentType::Dice, "🎲"),
+                (EntityContentType::Location, "{\"latitude\":1.0,\"longitude\":2.0}"),
+            ]
+        );
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_packs(pool: PgPool) {
+        let (member_db_id, _, _) = db_existed_chat_member(&pool).await;
+        let pack_chat_db_id = ChatDB::create_chat(&pool, -444433332221, "PackChat")
+            .await
+            .unwrap();
+        query(
+            "INSERT INTO answer_entities (is_active, created_at, updated_at, key, value, \
+            reaction_type, content_type, chat_id) \
+            VALUES (true, now(), now(), 'пакет', 'ответ пака', 'TRIGGER', 'TEXT', $1), \
+            (true, now(), now(), 'пакетик', 'подстрока пака', 'SUBSTRING', 'TEXT', $1)",
+        )
+        .bind(&pack_chat_db_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        AnswerPack::create(&pool, &pack_chat_db_id, "хлебный", &member_db_id)
+            .await
+            .unwrap();
+        for (input, output) in [
+            ("хлеб паки", "хлебный - публичный, 100%"),
+            ("хлеб проверь подстроку большой пакетик", "Ничего не было найдено"),
+            ("хлеб подпишись несуществующий", "Пак не найден"),
+            ("хлеб подпишись Хлебный", "Сделано"),
+            ("хлеб подпишись хлебный", "Чат уже подписан на пак"),
+            ("хлеб паки", "✓ хлебный - публичный, 100%"),
+            ("хлеб проверь триггер пакет", "ответ пака"),
+            // Cached substring index of the chat is rebuilt with keys of the pack
+            ("хлеб проверь подстроку большой пакетик", "подстрока пака"),
+            ("хлеб процент пака", "У чата нет пака"),
+            ("хлеб создай пак хлебный", "Пак с таким названием уже существует"),
+            ("хлеб создай пак Свой", "Сделано"),
+            ("хлеб создай пак Другой", "У чата уже есть пак"),
+            ("хлеб процент пака 50", "Сделано"),
+            ("хлеб процент пака", "Процент срабатывания пака: 50%"),
+            ("хлеб доступ пака приватный", "Сделано"),
+            ("хлеб доступ пака", "Доступ пака: приватный"),
+            ("хлеб подпишись свой", "Нельзя подписаться на пак своего чата"),
+            ("хлеб отпишись хлебный", "Сделано"),
+            ("хлеб отпишись хлебный", "Чат не подписан на пак"),
+            ("хлеб проверь триггер пакет", "Ничего не было найдено"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, None).await, output);
+        }
+        AnswerPack::update_answer_chance(&pool, &pack_chat_db_id, 0)
+            .await
+            .unwrap();
+        AnswerPack::update_is_private(&pool, &pack_chat_db_id, true)
+            .await
+            .unwrap();
+        // Author of the private pack subscribes the chat, other members have no access
+        for (input, output) in [
+            ("хлеб подпишись хлебный", "Сделано"),
+            ("хлеб проверь триггер пакет", "Ничего не было найдено"),
+            ("хлеб отпишись хлебный", "Сделано"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, None).await, output);
+        }
+        query("UPDATE answer_packs SET author_id = NULL")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            call_command_text(&pool, "хлеб подпишись хлебный", None).await,
+            "Нет доступа к приватному паку"
+        );
+        assert!(call_command_text(&pool, "хлеб история", None)
+            .await
+            .contains("отпишись хлебный"));
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_pack_selection(pool: PgPool) {
+        let (member_db_id, _, _) = db_existed_chat_member(&pool).await;
+        let pack_chat_db_id = ChatDB::create_chat(&pool, -444433332221, "PackChat")
+            .await
+            .unwrap();
+        call_command_replied(&pool, "хлеб запомни триггер сказка", "первый").await;
+        query(
+            "INSERT INTO answer_entities (is_active, created_at, updated_at, key, value, \
+            reaction_type, content_type, chat_id) \
+            VALUES (true, now(), now(), 'сказка', 'из пака', 'TRIGGER', 'TEXT', $1)",
+        )
+        .bind(&pack_chat_db_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        call_command_replied(&pool, "хлеб запомни триггер сказка", "третий").await;
+        AnswerPack::create(&pool, &pack_chat_db_id, "хлебный", &member_db_id)
+            .await
+            .unwrap();
+        AnswerPack::update_answer_chance(&pool, &pack_chat_db_id, 0)
+            .await
+            .unwrap();
+        // Skipped pack passes its turn, positions of the cycle stay the same
+        for (input, output) in [
+            ("хлеб подпишись хлебный", "Сделано"),
+            ("хлеб режим выбора триггер цикл сказка", "Сделано"),
+            ("хлеб проверь триггер сказка", "первый"),
+            ("хлеб проверь триггер сказка", "Ничего не было найдено"),
+            ("хлеб проверь триггер сказка", "третий"),
+            ("хлеб проверь триггер сказка", "первый"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, None).await, output);
+        }
+    }
+
+    fn sticker(file_unique_id: &str, emoji: &str) -> MessageExt {
+        MessageExt::Sticker {
+            sticker: Content {
+                file_id: format!("{file_unique_id}_file_id"),
+                file_unique_id: file_unique_id.to_string(),
+                emoji: Some(emoji.to_string()),
+            },
+        }
+    }
+
+    // Text of reaction on the media, None if there is no reaction
+    async fn send_media(pool: &PgPool, ext: MessageExt) -> Option<String> {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(pool).await;
+        let request_payload = media_message(&user, &chat, ext);
+        match handle_processor(
+            &Processor::AutoMedia,
+            &None,
+            &request_payload,
+            pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        {
+            Ok(response_message) => Some(json!(response_message)["text"].to_string()),
+            Err(ProcessError::Next) => None,
+            Err(error) => panic!("Unexpected error: {error:?}"),
+        }
+    }
+
+    async fn call_command_on_media(
+        pool: &PgPool,
+        input_text: &str,
+        reply_ext: MessageExt,
+    ) -> String {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(pool).await;
+        let request_payload = replied_media_message(&user, &chat, input_text, reply_ext);
+        match handle_processor(
+            &Processor::Command,
+            &payload_tokens(&request_payload),
+            &request_payload,
+            pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        {
+            Ok(response_message) => json!(response_message)["text"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            Err(ProcessError::Feedback { message }) => translate(&Language::Ru, message),
+            Err(error) => panic!("Unexpected error: {error:?}"),
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_media_reactions(pool: PgPool) {
+        assert_eq!(send_media(&pool, sticker("sticker_id", "🍞")).await, None);
+        assert_eq!(
+            call_command_on_media(&pool, "хлеб запомни на стикер", sticker("sticker_id", "🍞"))
+                .await,
+            "Теперь выбери в ответ сообщение с ответом и повтори команду"
+        );
+        for (input, reply_text, output) in [
+            ("хлеб запомни на стикер", "ответ на стикер", "Сделал"),
+            (
+                "хлеб запомни на стикер",
+                "ответ на стикер",
+                "Необходимо выбрать в ответ стикер, гифку или картинку",
+            ),
+            ("хлеб запомни на стикер 🥖", "ответ на батон", "Сделал"),
+            (
+                "хлеб запомни на стикер батон",
+                "ответ на батон",
+                "Необходимо указать эмодзи стикера",
+            ),
+        ] {
+            assert_eq!(call_command_text(&pool, input, Some(reply_text)).await, output);
+        }
+        assert_eq!(
+            send_media(&pool, sticker("sticker_id", "🍞")).await,
+            Some("\"ответ на стикер\"".to_string())
+        );
+        // Any sticker with the emoji gets the answer
+        assert_eq!(
+            send_media(&pool, sticker("other_sticker_id", "🥖")).await,
+            Some("\"ответ на батон\"".to_string())
+        );
+        assert_eq!(send_media(&pool, sticker("other_sticker_id", "🍞")).await, None);
+        // Media answer is saved on a gif
+        let animation = || MessageExt::Animation {
+            animation: Content {
+                file_id: "animation_file_id".to_string(),
+                file_unique_id: "animation_id".to_string(),
+                emoji: None,
+            },
+            caption: None,
+            caption_entities: vec![],
+        };
+        call_command_on_media(&pool, "хлеб запомни на гифку", animation()).await;
+        assert_eq!(
+            call_command_on_media(&pool, "хлеб запомни на гифку", sticker("sticker_id", "🍞"))
+                .await,
+            "Сделал"
+        );
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(&pool).await;
+        let response = handle_processor(
+            &Processor::AutoMedia,
+            &None,
+            &media_message(&user, &chat, animation()),
+            &pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        .unwrap();
+        assert_eq!(json!(response)["sticker"], "sticker_id_file_id");
+    }
+
+    fn album_part(user: &User, chat: &Chat, message_id: i64) -> MessageBody {
+        MessageBody {
+            base: MessageBase {
+                message_id,
+                from: user.clone(),
+                chat: chat.clone(),
+                forward_from: None,
+                forward_from_chat: None,
+                media_group_id: Some("album_id".to_string()),
+            },
+            ext: MessageExt::Photo {
+                photo: vec![Content {
+                    file_id: format!("photo_{message_id}_file_id"),
+                    file_unique_id: format!("photo_{message_id}"),
+                    emoji: None,
+                }],
+                caption: (message_id == 11).then(|| "подпись".to_string()),
+                caption_entities: vec![],
+            },
+        }
+    }
+
+    async fn call_command_on_album_part(
+        pool: &PgPool,
+        input_text: &str,
+        message_id: i64,
+    ) -> String {
+        let (user, chat) = request_existed_chat_user().await;
+        let (member_db_id, chat_db_id, chat_to_member_db_id) = db_existed_chat_member(pool).await;
+        let request_payload = RequestPayload::Origin {
+            update_id: 8,
+            message: Message::Replied {
+                direct: MessageBody {
+                    base: MessageBase {
+                        message_id: 20,
+                        from: user.clone(),
+                        chat: chat.clone(),
+                        forward_from: None,
+                        forward_from_chat: None,
+                        media_group_id: None,
+                    },
+                    ext: MessageExt::Text {
+                        text: input_text.to_string(),
+                        entities: vec![],
+                    },
+                },
+                reply: Box::new(album_part(&user, &chat, message_id)),
+                reply_markup: None,
+            },
+        };
+        match handle_processor(
+            &Processor::Command,
+            &payload_tokens(&request_payload),
+            &request_payload,
+            pool,
+            &member_db_id,
+            &chat_db_id,
+            &chat_to_member_db_id,
+        )
+        .await
+        {
+            Ok(response_message) => json!(response_message)["text"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            Err(ProcessError::Feedback { message }) => translate(&Language::Ru, message),
+            Err(error) => panic!("Unexpected error: {error:?}"),
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_albums(pool: PgPool) {
+        let (user, chat) = request_existed_chat_user().await;
+        let (_, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        // Parts may arrive in any order and more than once
+        for message_id in [12, 11, 12] {
+            buffer_album_part(&pool, &chat_db_id, &album_part(&user, &chat, message_id));
+        }
+        assert_eq!(
+            call_command_on_album_part(&pool, "хлеб запомни триггер альбом", 12).await,
+            "Сделал"
+        );
+        let response = json!(call_command_direct(&pool, "хлеб проверь триггер альбом").await);
+        assert_json_include!(
+            actual: &response,
+            expected: json!({
+                "reply_to_message_id": 5555,
+                "media": [
+                    {"type": "photo", "media": "photo_11_file_id", "caption": "подпись"},
+                    {"type": "photo", "media": "photo_12_file_id"},
+                ],
+            })
+        );
+        assert!(response.get("reply_markup").is_none());
+        assert!(call_command_on_album_part(&pool, "хлеб покажи", 11)
+            .await
+            .starts_with("альбом"));
+        assert_eq!(
+            call_command_on_album_part(&pool, "хлеб вес", 12).await,
+            "Вес: альбом - 1"
+        );
+        assert_eq!(
+            call_command_on_album_part(&pool, "хлеб удали", 11).await,
+            "Был удален контент на ключах: альбом"
+        );
+        assert_eq!(
+            call_command_on_album_part(&pool, "хлеб верни", 12).await,
+            "Был восстановлен контент на ключах: альбом"
+        );
+        assert_eq!(
+            call_command_on_album_part(&pool, "хлеб перенеси триггер альбом, фотки", 11).await,
+            "Сделано"
+        );
+        assert_eq!(
+            call_command_on_album_part(&pool, "хлеб отвяжи фотки", 12).await,
+            "Были отвязаны ключи: фотки"
+        );
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_forward_and_copy(pool: PgPool) {
+        for (input, output) in [
+            ("хлеб запомни пересылкой триггер переслать", "Сделал"),
+            ("хлеб запомни копией триггер скопировать", "Сделал"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, Some("**оригинал**")).await, output);
+        }
+        let (_, chat) = request_existed_chat_user().await;
+        assert_eq!(
+            json!(call_command_direct(&pool, "хлеб проверь триггер переслать").await),
+            json!({"chat_id": chat.id, "from_chat_id": chat.id, "message_id": 1})
+        );
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб проверь триггер скопировать").await),
+            expected: json!({
+                "chat_id": chat.id,
+                "reply_to_message_id": 5555,
+                "from_chat_id": chat.id,
+                "message_id": 1,
+            })
+        );
+        // Saved forwards and copies are found by reply to their source
+        for (input, output) in [
+            ("хлеб вес", "Вес: скопировать - 1"),
+            ("хлеб удали", "Был удален контент на ключах: скопировать"),
+            ("хлеб удали", "Был удален контент на ключах: переслать"),
+            ("хлеб верни", "Был восстановлен контент на ключах: скопировать"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, Some("**оригинал**")).await, output);
+        }
+        // Forward without a valid source is not sent
+        for content_type in [EntityContentType::Forward, EntityContentType::Copy] {
+            let value = String::from("оригинал");
+            assert!(ResponseMessage::from_content(content_type, value, None, chat.id, 1, None)
+                .is_none());
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_other_message_kinds(pool: PgPool) {
+        let location = Location {
+            latitude: 55.75,
+            longitude: 37.62,
+        };
+        for (input, reply_ext, output) in [
+            (
+                "хлеб запомни триггер кубик",
+                MessageExt::Dice {
+                    dice: Dice {
+                        emoji: String::from("🎲"),
+                        value: 4,
+                    },
+                },
+                "Сделал",
+            ),
+            (
+                "хлеб запомни триггер где",
+                MessageExt::Location {
+                    location: location.clone(),
+                },
+                "Сделал",
+            ),
+            (
+                "хлеб запомни триггер место",
+                MessageExt::Venue {
+                    venue: Venue {
+                        location: location.clone(),
+                        title: String::from("Булочная"),
+                        address: String::from("Тверская, 1"),
+                    },
+                },
+                "Сделал",
+            ),
+            (
+                "хлеб запомни триггер опрос",
+                MessageExt::Poll {
+                    poll: Poll {
+                        question: String::from("Батон или булка?"),
+                        options: vec![
+                            PollOption {
+                                text: String::from("Батон"),
+                            },
+                            PollOption {
+                                text: String::from("Булка"),
+                            },
+                        ],
+                        is_anonymous: false,
+                        allows_multiple_answers: false,
+                    },
+                },
+                "Сделал",
+            ),
+            (
+                "хлеб запомни триггер история",
+                MessageExt::Unknown {},
+                "Такие сообщения не поддерживаются",
+            ),
+            ("хлеб покажи", MessageExt::Unknown {}, "Такие сообщения не поддерживаются"),
+        ] {
+            assert_eq!(call_command_on_media(&pool, input, reply_ext).await, output);
+        }
+        let (_, chat) = request_existed_chat_user().await;
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб проверь триггер кубик").await),
+            expected: json!({"chat_id": chat.id, "reply_to_message_id": 5555, "emoji": "🎲"})
+        );
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб проверь триггер где").await),
+            expected: json!({"chat_id": chat.id, "latitude": 55.75, "longitude": 37.62})
+        );
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб проверь триггер место").await),
+            expected: json!({
+                "latitude": 55.75,
+                "longitude": 37.62,
+                "title": "Булочная",
+                "address": "Тверская, 1",
+            })
+        );
+        assert_json_include!(
+            actual: json!(call_command_direct(&pool, "хлеб проверь триггер опрос").await),
+            expected: json!({
+                "question": "Батон или булка?",
+                "options": [{"text": "Батон"}, {"text": "Булка"}],
+                "is_anonymous": false,
+            })
+        );
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_welcome(pool: PgPool) {
+        let (user, chat) = request_existed_chat_user().await;
+        let (_, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        let joined = User {
+            id: 211382615,
+            is_bot: false,
+            first_name: Some(String::from("Joined")),
+            last_name: None,
+            username: None,
+        };
+        let bot = User {
+            id: 211382616,
+            is_bot: true,
+            first_name: None,
+            last_name: None,
+            username: Some(String::from("some_bot")),
+        };
+        let join_message = MessageBody {
+            base: MessageBase {
+                message_id: 3,
+                from: user.clone(),
+                chat: chat.clone(),
+                forward_from: None,
+                forward_from_chat: None,
+                media_group_id: None,
+            },
+            ext: MessageExt::NewChatMembers {
+                new_chat_members: vec![joined, bot],
+            },
+        };
+        let welcome_texts = || async {
+            welcome_messages(&pool, &join_message, &chat_db_id)
+                .await
+                .iter()
+                .map(|x| json!(x)["text"].as_str().unwrap_or_default().to_string())
+                .collect::<Vec<String>>()
+        };
+        assert!(welcome_texts().await.is_empty());
+        for (input, reply_text, output) in [
+            ("хлеб приветствие", Some("Привет, {unknown}!"), "Неизвестная подстановка в шаблоне"),
+            ("хлеб приветствие", Some("Привет, {author}!"), "Сделал"),
+            ("хлеб удаление приветствия", None, "Задержка удаления приветствия в секундах: 0"),
+            ("хлеб удаление приветствия 30", None, "Сделано"),
+            ("хлеб удаление приветствия", None, "Задержка удаления приветствия в секундах: 30"),
+            ("хлеб приветствие ботов", None, "Приветствие ботов: пропускать"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, reply_text).await, output);
+        }
+        assert_eq!(welcome_texts().await, vec!["Привет, Joined!"]);
+        assert_eq!(
+            call_command_text(&pool, "хлеб приветствие ботов приветствовать", None).await,
+            "Сделано"
+        );
+        assert_eq!(welcome_texts().await, vec!["Привет, Joined!", "Привет, some_bot!"]);
+        assert_json_include!(
+            actual: json!(welcome_messages(&pool, &join_message, &chat_db_id).await[0]),
+            expected: json!({"chat_id": chat.id, "reply_to_message_id": 3})
+        );
+        for (input, output) in [
+            ("хлеб удали приветствие", "Сделано"),
+            ("хлеб удали приветствие", "Приветствие не задано"),
+        ] {
+            assert_eq!(call_command_text(&pool, input, None).await, output);
+        }
+        assert!(welcome_texts().await.is_empty());
+    }
 }
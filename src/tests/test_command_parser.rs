@@ -1,12 +1,17 @@
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use Command::*;
 
     use crate::common::command_parser::{
-        is_bot_call, parse_command, Command, CommandContainer, ControlItem,
+        is_bot_call, parse_command, Argument, ArgumentSchema, Command, CommandContainer,
+        ControlItem, MemberArgument,
     };
+    use crate::common::db::Language;
     use crate::common::error::ProcessError;
     use crate::common::lexer::{tokenize, Token};
+    use crate::common::locale::{translate, Phrase};
 
     #[test]
     fn test_is_bot_call() {
@@ -19,6 +24,10 @@ mod tests {
             (Token::Word("bread_bot"), true),
             (Token::Word("bread"), true),
             (Token::Word("BREAD_BOT"), true),
+            (Token::Mention("@bread_bot"), true),
+            (Token::BotCommand("/bread"), true),
+            (Token::BotCommand("/bread@bread_hleb_bot"), true),
+            (Token::BotCommand("/help"), false),
             (Token::Word("хлебушкек"), false),
             (Token::Newline, false),
             (Token::Punctuation("-"), false),
@@ -47,25 +56,25 @@ mod tests {
             (
                 "хлеб добавь",
                 Err(ProcessError::Feedback {
-                    message: "Необходимо указать объект для редактирования",
+                    message: Phrase::NeedControlItem,
                 }),
             ),
             (
                 "хлеб добавь бред",
                 Err(ProcessError::Feedback {
-                    message: "Необходимо указать значения",
+                    message: Phrase::NeedValues,
                 }),
             ),
             (
                 "хлеб добавь подстроку утка",
                 Err(ProcessError::Feedback {
-                    message: "Указан недопустимый объект для редактирования",
+                    message: Phrase::InvalidControlItem,
                 }),
             ),
             (
                 "хлеб добавь неподстроку ?",
                 Err(ProcessError::Feedback {
-                    message: "Необходимо указать объект для редактирования",
+                    message: Phrase::NeedControlItem,
                 }),
             ),
             (
@@ -74,7 +83,7 @@ mod tests {
                     command: &Add,
                     command_aliases: &[Token::Word("добавь")],
                     control_item: Some(&ControlItem::MorphWord),
-                    split_values: true,
+                    argument: Argument::List(vec!["утка".to_string()]),
                     rest: &[Token::Word("утка")],
                 }),
             ),
@@ -84,7 +93,7 @@ mod tests {
                     command: &Add,
                     command_aliases: &[Token::Word("добавь")],
                     control_item: Some(&ControlItem::MorphWord),
-                    split_values: true,
+                    argument: Argument::List(vec!["утка".to_string()]),
                     rest: &[Token::Word("утка")],
                 }),
             ),
@@ -102,7 +111,7 @@ mod tests {
                     command: &Help,
                     command_aliases: &[Token::Word("хелп")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Empty,
                     rest: &[],
                 }),
             ),
@@ -112,7 +121,7 @@ mod tests {
                     command: &Help,
                     command_aliases: &[Token::Word("хелп")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("бред")]),
                     rest: &[Token::Word("бред")],
                 }),
             ),
@@ -122,7 +131,7 @@ mod tests {
                     command: &Help,
                     command_aliases: &[Token::Word("хелп")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("бред"), Token::Word("бред")]),
                     rest: &[Token::Word("бред"), Token::Word("бред")],
                 }),
             ),
@@ -140,7 +149,7 @@ mod tests {
                     command: &Show,
                     command_aliases: &[Token::Word("покажи")],
                     control_item: Some(&ControlItem::KeyWord),
-                    split_values: false,
+                    argument: Argument::Empty,
                     rest: &[],
                 }),
                 true,
@@ -148,7 +157,7 @@ mod tests {
             (
                 "хлеб покажи",
                 Err(ProcessError::Feedback {
-                    message: "Необходимо выбрать сообщение в ответ",
+                    message: Phrase::NeedReply,
                 }),
                 false,
             ),
@@ -158,7 +167,7 @@ mod tests {
                     command: &Show,
                     command_aliases: &[Token::Word("покажи")],
                     control_item: Some(&ControlItem::KeyWord),
-                    split_values: false,
+                    argument: Argument::Empty,
                     rest: &[],
                 }),
                 true,
@@ -166,7 +175,7 @@ mod tests {
             (
                 "хлеб покажи ключи",
                 Err(ProcessError::Feedback {
-                    message: "Необходимо выбрать сообщение в ответ",
+                    message: Phrase::NeedReply,
                 }),
                 false,
             ),
@@ -176,7 +185,7 @@ mod tests {
                     command: &Show,
                     command_aliases: &[Token::Word("покажи")],
                     control_item: Some(&ControlItem::KeyWord),
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("обед")]),
                     rest: &[Token::Word("обед")],
                 }),
                 true,
@@ -187,7 +196,7 @@ mod tests {
                     command: &Show,
                     command_aliases: &[Token::Word("покажи")],
                     control_item: Some(&ControlItem::KeyWord),
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("обед")]),
                     rest: &[Token::Word("обед")],
                 }),
                 true,
@@ -208,7 +217,7 @@ mod tests {
                     command: &Who,
                     command_aliases: &[Token::Word("кто")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("булочка"), Token::Punctuation("?")]),
                     rest: &[Token::Word("булочка"), Token::Punctuation("?")],
                 }),
             ),
@@ -218,7 +227,7 @@ mod tests {
                     command: &Who,
                     command_aliases: &[Token::Word("КТО")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("булочка"), Token::Punctuation("?")]),
                     rest: &[Token::Word("булочка"), Token::Punctuation("?")],
                 }),
             ),
@@ -228,7 +237,7 @@ mod tests {
                     command: &Who,
                     command_aliases: &[Token::Word("кто")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("булочка"), Token::Punctuation("?")]),
                     rest: &[Token::Word("булочка"), Token::Punctuation("?")],
                 }),
             ),
@@ -238,7 +247,7 @@ mod tests {
                     command: &Who,
                     command_aliases: &[Token::Word("who")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("булочка"), Token::Punctuation("?")]),
                     rest: &[Token::Word("булочка"), Token::Punctuation("?")],
                 }),
             ),
@@ -248,7 +257,7 @@ mod tests {
                     command: &Who,
                     command_aliases: &[Token::Word("кто")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Punctuation("?")]),
                     rest: &[Token::Punctuation("?")],
                 }),
             ),
@@ -258,7 +267,7 @@ mod tests {
                     command: &Who,
                     command_aliases: &[Token::Word("кто")],
                     control_item: None,
-                    split_values: false,
+                    argument: Argument::Empty,
                     rest: &[],
                 }),
             ),
@@ -276,7 +285,7 @@ mod tests {
                     command: &AnswerChance,
                     command_aliases: &[Token::Word("процент"), Token::Word("срабатывания")],
                     control_item: Some(&ControlItem::MorphWord),
-                    split_values: false,
+                    argument: Argument::Empty,
                     rest: &[],
                 }),
             ),
@@ -286,7 +295,7 @@ mod tests {
                     command: &AnswerChance,
                     command_aliases: &[Token::Word("процент")],
                     control_item: Some(&ControlItem::Substring),
-                    split_values: false,
+                    argument: Argument::Empty,
                     rest: &[],
                 }),
             ),
@@ -296,8 +305,8 @@ mod tests {
                     command: &AnswerChance,
                     command_aliases: &[Token::Word("процент")],
                     control_item: Some(&ControlItem::Substring),
-                    split_values: false,
-                    rest: &[Token::Word("20")],
+                    argument: Argument::Integer(20),
+                    rest: &[Token::Number("20")],
                 }),
             ),
         ]
@@ -311,7 +320,7 @@ mod tests {
             (
                 "хлеб проверь",
                 Err(ProcessError::Feedback {
-                    message: "Необходимо указать значения",
+                    message: Phrase::NeedValues,
                 }),
             ),
             (
@@ -320,17 +329,27 @@ mod tests {
                     command: &Check,
                     command_aliases: &[Token::Word("проверь")],
                     control_item: Some(&ControlItem::Substring),
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("нога")]),
                     rest: &[Token::Word("нога")],
                 }),
             ),
+            (
+                "bread check trigger leg",
+                Ok(CommandContainer {
+                    command: &Check,
+                    command_aliases: &[Token::Word("check")],
+                    control_item: Some(&ControlItem::Trigger),
+                    argument: Argument::Text(&[Token::Word("leg")]),
+                    rest: &[Token::Word("leg")],
+                }),
+            ),
             (
                 "хлеб проверь триггер нога",
                 Ok(CommandContainer {
                     command: &Check,
                     command_aliases: &[Token::Word("проверь")],
                     control_item: Some(&ControlItem::Trigger),
-                    split_values: false,
+                    argument: Argument::Text(&[Token::Word("нога")]),
                     rest: &[Token::Word("нога")],
                 }),
             ),
@@ -338,4 +357,115 @@ mod tests {
         .iter()
         .for_each(|(input, output)| assert_eq!(parse_command(&tokenize(input), false), *output))
     }
+
+    #[test]
+    fn test_parse_invalid_argument() {
+        [
+            (
+                "хлеб процент 101",
+                "Указано неверное значение. Должно быть целое число от 0 до 100",
+                "Invalid value. Expected an integer from 0 to 100",
+            ),
+            (
+                "bread percent сто",
+                "Указано неверное значение. Должно быть целое число от 0 до 100",
+                "Invalid value. Expected an integer from 0 to 100",
+            ),
+            (
+                "хлеб добавь бред , ,",
+                "Указано неверное значение. \
+                Должно быть одно или несколько значений через \",\"",
+                "Invalid value. Expected one or several values separated by \",\"",
+            ),
+        ]
+        .iter()
+        .for_each(
+            |(input, ru, en)| match parse_command(&tokenize(input), false) {
+                Err(ProcessError::Feedback { message }) => {
+                    assert_eq!(translate(&Language::Ru, message), *ru);
+                    assert_eq!(translate(&Language::En, message), *en);
+                }
+                result => panic!("Unexpected result: {result:?}"),
+            },
+        )
+    }
+
+    #[test]
+    fn test_argument_schema_parse() {
+        [
+            (ArgumentSchema::Text, "", Some(Argument::Empty)),
+            (
+                ArgumentSchema::List,
+                "раз, два три",
+                Some(Argument::List(vec![
+                    "раз".to_string(),
+                    "два три".to_string(),
+                ])),
+            ),
+            (
+                ArgumentSchema::Integer { min: -5, max: 5 },
+                "-5",
+                Some(Argument::Integer(-5)),
+            ),
+            (ArgumentSchema::Integer { min: -5, max: 5 }, "6", None),
+            (ArgumentSchema::Integer { min: -5, max: 5 }, "1 2", None),
+            (
+                ArgumentSchema::EnumWord(vec!["ru", "en"]),
+                "EN",
+                Some(Argument::EnumWord("en")),
+            ),
+            (ArgumentSchema::EnumWord(vec!["ru", "en"]), "de", None),
+            (
+                ArgumentSchema::EnumWordWithList(vec!["цикл", "история"]),
+                "Цикл привет, пока",
+                Some(Argument::EnumWordWithList(
+                    Some("цикл"),
+                    vec!["привет".to_string(), "пока".to_string()],
+                )),
+            ),
+            (
+                ArgumentSchema::EnumWordWithList(vec!["цикл", "история"]),
+                "история, привет",
+                Some(Argument::EnumWordWithList(
+                    None,
+                    vec!["история".to_string(), "привет".to_string()],
+                )),
+            ),
+            (
+                ArgumentSchema::EnumWordWithList(vec!["цикл", "история"]),
+                "цикл",
+                Some(Argument::EnumWordWithList(Some("цикл"), vec![])),
+            ),
+            (ArgumentSchema::EnumWordWithList(vec!["цикл"]), ", ,", None),
+            (
+                ArgumentSchema::Member,
+                "@username",
+                Some(Argument::Member(MemberArgument::Username("username"))),
+            ),
+            (ArgumentSchema::Member, "username", None),
+            (ArgumentSchema::Member, "@", None),
+            (
+                ArgumentSchema::Duration,
+                "30с",
+                Some(Argument::Duration(Duration::from_secs(30))),
+            ),
+            (
+                ArgumentSchema::Duration,
+                "10m",
+                Some(Argument::Duration(Duration::from_secs(600))),
+            ),
+            (
+                ArgumentSchema::Duration,
+                "2 часа",
+                Some(Argument::Duration(Duration::from_secs(7200))),
+            ),
+            (ArgumentSchema::Duration, "0д", None),
+            (ArgumentSchema::Duration, "10", None),
+            (ArgumentSchema::Duration, "10 лет", None),
+            (ArgumentSchema::Duration, "99999999999999999 дней", None),
+            (ArgumentSchema::Duration, "99999999999999999д", None),
+        ]
+        .iter()
+        .for_each(|(schema, input, output)| assert_eq!(schema.parse(&tokenize(input)), *output))
+    }
 }
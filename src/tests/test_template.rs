@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::common::locale::Phrase;
+    use crate::common::template::{
+        parse_template, render_parts, Placeholder, TemplateContext, TemplatePart::*,
+    };
+
+    #[test]
+    fn test_parse_template() {
+        [
+            ("привет", Ok(vec![Literal("привет")])),
+            (
+                "привет, {author}!",
+                Ok(vec![
+                    Literal("привет, "),
+                    Placeholder(Placeholder::Author),
+                    Literal("!"),
+                ]),
+            ),
+            (
+                "{rand:1-100}%",
+                Ok(vec![
+                    Placeholder(Placeholder::Random { min: 1, max: 100 }),
+                    Literal("%"),
+                ]),
+            ),
+            (
+                "{{author}} { x }",
+                Ok(vec![
+                    Literal("{"),
+                    Literal("author"),
+                    Literal("}"),
+                    Literal(" "),
+                    Literal("{"),
+                    Literal(" x "),
+                    Literal("}"),
+                ]),
+            ),
+            (
+                "{автор}",
+                Ok(vec![Literal("{"), Literal("автор"), Literal("}")]),
+            ),
+            ("{name}", Err(Phrase::UnknownPlaceholder)),
+            ("{rand:100-1}", Err(Phrase::InvalidTemplateRange)),
+            ("{rand:a-b}", Err(Phrase::InvalidTemplateRange)),
+        ]
+        .iter()
+        .for_each(|(input, output)| assert_eq!(parse_template(input), *output));
+    }
+
+    #[test]
+    fn test_render_parts() {
+        let context = TemplateContext {
+            author: String::from("Вася"),
+            random_member: Some(String::from("Петя")),
+            text: String::from("как дела?"),
+            now: Utc.with_ymd_and_hms(2024, 4, 1, 9, 5, 0).unwrap(),
+        };
+        [
+            (
+                "{author} спросил у {random_member} \"{text}\"",
+                "Вася спросил у Петя \"как дела?\"",
+            ),
+            ("{date} {time}", "01.04.2024 09:05"),
+            ("{rand:7-7}, {{text}}", "7, {text}"),
+        ]
+        .iter()
+        .for_each(|(input, output)| {
+            assert_eq!(
+                render_parts(&parse_template(input).unwrap(), &context),
+                *output
+            )
+        });
+    }
+}
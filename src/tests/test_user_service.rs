@@ -1,11 +1,19 @@
 #[cfg(test)]
 mod tests {
     use axum::http::StatusCode;
+    use serde_json::json;
     use sqlx::{query, PgPool, Row};
 
+    use crate::common::db::{
+        AnswerEntity, AuditAction, AuditLogEntry, Chat as ChatDB, ChatWelcome, CommandPermission,
+        DictionaryEntity, EntityContentType, Language, Member, Role,
+    };
+    use crate::common::request::{ChatMemberUpdated, MessageExt, RequestPayload};
+    use crate::common::user_service::process_my_chat_member;
+
     use crate::tests::helpers::fixtures::{
-        default_chat, default_origin_direct_text_message, default_user, EXISTED_CHAT_ID,
-        EXISTED_USER_ID,
+        db_existed_chat_member, default_chat, default_origin_direct_text_message, default_user,
+        EXISTED_CHAT_ID, EXISTED_USER_ID,
     };
     use crate::tests::helpers::functions::api_telegram_request;
 
@@ -229,4 +237,291 @@ mod tests {
         .map(|x| x.get::<bool, _>("exists"))
         .unwrap())
     }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_unknown_message_kind(pool: PgPool) {
+        let message = serde_json::from_value::<RequestPayload>(json!({
+            "update_id": 959895781,
+            "message": {
+                "message_id": 104041,
+                "from": {"id": 211382612, "is_bot": false, "first_name": "FirstName"},
+                "chat": {"id": -211382612, "title": "StoryChat", "type": "group"},
+                "date": 1699397266,
+                "story": {"chat": {"id": 211382612, "type": "private"}, "id": 1}
+            }
+        }))
+        .unwrap();
+        assert_eq!(message.any_message().direct().ext, MessageExt::Unknown {});
+        let response = api_telegram_request(pool.clone(), &message).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(query(
+            "SELECT EXISTS (SELECT chats_to_members.* FROM chats_to_members \
+            JOIN members ON members.id = chats_to_members.member_id \
+            JOIN chats ON chats.id = chats_to_members.chat_id \
+            WHERE members.member_id = 211382612 AND chats.chat_id = -211382612);"
+        )
+        .fetch_one(&pool)
+        .await
+        .map(|x| x.get::<bool, _>("exists"))
+        .unwrap())
+    }
+
+    fn chat_member_update(old_status: &str, new_status: &str) -> serde_json::Value {
+        json!({
+            "update_id": 959895782,
+            "my_chat_member": {
+                "chat": {"id": -211382613, "title": "NewChat", "type": "group"},
+                "from": {"id": 211382613, "is_bot": false, "first_name": "Inviter"},
+                "date": 1699397266,
+                "old_chat_member": {
+                    "user": {"id": 111, "is_bot": true, "first_name": "Bot"},
+                    "status": old_status
+                },
+                "new_chat_member": {
+                    "user": {"id": 111, "is_bot": true, "first_name": "Bot"},
+                    "status": new_status
+                }
+            }
+        })
+    }
+
+    async fn chat_is_active(pool: &PgPool, chat_id: i64) -> Option<bool> {
+        query("SELECT is_active FROM chats WHERE chat_id = $1")
+            .bind(chat_id)
+            .fetch_one(pool)
+            .await
+            .ok()
+            .map(|x| x.get::<bool, _>("is_active"))
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_my_chat_member(pool: PgPool) {
+        let added = serde_json::from_value::<ChatMemberUpdated>(
+            chat_member_update("left", "member")["my_chat_member"].clone(),
+        )
+        .unwrap();
+        let (_, greeting) = process_my_chat_member(&pool, &added).await.unwrap();
+        assert_eq!(
+            json!(greeting),
+            json!({
+                "chat_id": -211382613,
+                "reply_to_message_id": null,
+                "text": "Привет! Я отвечаю на сообщения чата запомненными ответами. \
+                Список команд: хлеб хелп",
+                "link_preview_options": {"is_disabled": false},
+            })
+        );
+        assert_eq!(chat_is_active(&pool, -211382613).await, Some(true));
+        let chat_db_id = ChatDB::id_and_name(&pool, -211382613).await.unwrap().0;
+        assert_eq!(Member::chat_members(&pool, &chat_db_id).await.len(), 1);
+        for (old_status, new_status, is_active) in [
+            ("member", "kicked", false),
+            ("kicked", "administrator", true),
+            ("administrator", "left", false),
+        ] {
+            let response =
+                api_telegram_request(pool.clone(), &chat_member_update(old_status, new_status))
+                    .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(chat_is_active(&pool, -211382613).await, Some(is_active));
+        }
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_members_join_and_leave(pool: PgPool) {
+        let chat_db_id = ChatDB::id_and_name(&pool, EXISTED_CHAT_ID).await.unwrap().0;
+        let chat = json!({"id": EXISTED_CHAT_ID, "title": "SomeChat", "type": "group"});
+        let user = json!({"id": EXISTED_USER_ID, "is_bot": false, "first_name": "FirstName"});
+        let joined = json!({"id": 211382614, "is_bot": false, "first_name": "Joined"});
+        let response = api_telegram_request(
+            pool.clone(),
+            &json!({
+                "update_id": 959895783,
+                "message": {
+                    "message_id": 104042,
+                    "from": user,
+                    "chat": chat,
+                    "date": 1699397266,
+                    "new_chat_members": [joined, {"id": 112, "is_bot": true, "first_name": "Bot"}]
+                }
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(Member::chat_members(&pool, &chat_db_id).await.len(), 2);
+        let response = api_telegram_request(
+            pool.clone(),
+            &json!({
+                "update_id": 959895784,
+                "message": {
+                    "message_id": 104043,
+                    "from": joined,
+                    "chat": chat,
+                    "date": 1699397266,
+                    "left_chat_member": joined
+                }
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let members = Member::chat_members(&pool, &chat_db_id).await;
+        let member = Member::one_by_member_id(&pool, EXISTED_USER_ID).await.unwrap();
+        assert_eq!(members, vec![member.id]);
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_chat_migration(pool: PgPool) {
+        let chat_db_id = ChatDB::id_and_name(&pool, EXISTED_CHAT_ID).await.unwrap().0;
+        let user = json!({"id": EXISTED_USER_ID, "is_bot": false, "first_name": "FirstName"});
+        let message = |chat_id: i64, migration: serde_json::Value| {
+            let mut message = json!({
+                "update_id": 959895785,
+                "message": {
+                    "message_id": 104044,
+                    "from": user,
+                    "chat": {"id": chat_id, "title": "SomeChat", "type": "supergroup"},
+                    "date": 1699397266
+                }
+            });
+            for (key, value) in migration.as_object().unwrap() {
+                message["message"][key] = value.clone();
+            }
+            message
+        };
+        let new_chat_id = -1001333322221112;
+        // The new chat gets its message first, then both chats send messages about migration
+        for request in [
+            message(new_chat_id, json!({"text": "some_text"})),
+            message(new_chat_id, json!({"migrate_from_chat_id": EXISTED_CHAT_ID})),
+            message(EXISTED_CHAT_ID, json!({"migrate_to_chat_id": new_chat_id})),
+        ] {
+            let response = api_telegram_request(pool.clone(), &request).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert_eq!(chat_is_active(&pool, EXISTED_CHAT_ID).await, None);
+        assert_eq!(
+            ChatDB::id_and_name(&pool, new_chat_id).await,
+            Some((chat_db_id.clone(), String::from("SomeChat")))
+        );
+        assert_eq!(ChatDB::morph_answer_chance(&pool, &chat_db_id).await, Some(11));
+        assert_eq!(Member::chat_members(&pool, &chat_db_id).await.len(), 1);
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_chat_migration_merge(pool: PgPool) {
+        let (member_db_id, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        let new_chat_id = -1001333322221112;
+        let new_chat_db_id = ChatDB::create_chat(&pool, new_chat_id, "SomeChat").await.unwrap();
+        for (chat_db_id, welcome, command, words) in [
+            (&chat_db_id, "старое приветствие", "rename", ["старое", "общее"]),
+            (&new_chat_db_id, "новое приветствие", "move", ["новое", "общее"]),
+        ] {
+            let welcome = welcome.to_string();
+            ChatWelcome::save(
+                &pool,
+                chat_db_id,
+                (&welcome, &None, &None),
+                &EntityContentType::Text,
+                &member_db_id,
+            )
+            .await
+            .unwrap();
+            for command in [command, "delete"] {
+                CommandPermission::update_role(&pool, chat_db_id, command, &Role::Admin)
+                    .await
+                    .unwrap();
+            }
+            let words = words.map(String::from);
+            DictionaryEntity::bulk_add_items(
+                &pool,
+                words.iter().collect(),
+                chat_db_id,
+                &member_db_id,
+            )
+            .await
+            .unwrap();
+        }
+        CommandPermission::update_role(&pool, &new_chat_db_id, "delete", &Role::Member)
+            .await
+            .unwrap();
+        // Settings changed in the new chat are taken, the rest ones of the old chat are kept
+        ChatDB::update_language(&pool, &new_chat_db_id, &Language::En).await.unwrap();
+        assert_eq!(
+            ChatDB::migrate(&pool, EXISTED_CHAT_ID, new_chat_id).await.unwrap(),
+            Some((chat_db_id.clone(), Some(new_chat_db_id)))
+        );
+        assert_eq!(
+            ChatWelcome::by_chat(&pool, &chat_db_id).await.unwrap().value,
+            "новое приветствие"
+        );
+        let mut roles = CommandPermission::roles(&pool, &chat_db_id).await;
+        roles.sort();
+        assert_eq!(
+            roles,
+            [("delete", Role::Member), ("move", Role::Admin), ("rename", Role::Admin)]
+                .map(|(command, role)| (command.to_string(), role))
+        );
+        assert_eq!(
+            DictionaryEntity::all_values(&pool, &chat_db_id).await,
+            ["новое", "общее", "старое"]
+        );
+        assert_eq!(ChatDB::language(&pool, &chat_db_id).await, Some(Language::En));
+        assert_eq!(ChatDB::morph_answer_chance(&pool, &chat_db_id).await, Some(11));
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(path = "sqlx_fixtures", scripts("default_chat", "default_user"))
+    )]
+    async fn test_chat_migration_keeps_settings(pool: PgPool) {
+        let (member_db_id, chat_db_id, _) = db_existed_chat_member(&pool).await;
+        ChatDB::update_language(&pool, &chat_db_id, &Language::En).await.unwrap();
+        let new_chat_id = -1001333322221112;
+        let new_chat_db_id = ChatDB::create_chat(&pool, new_chat_id, "SomeChat").await.unwrap();
+        query(
+            "INSERT INTO answer_entities (is_active, created_at, updated_at, key, value, \
+            reaction_type, content_type, chat_id) \
+            VALUES (true, now(), now(), 'ключ', 'ответ', 'TRIGGER', 'TEXT', $1)",
+        )
+        .bind(&new_chat_db_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        // Weight change is logged as a setting, but it doesn't change settings of the chat
+        AnswerEntity::update_weight(
+            &pool,
+            &new_chat_db_id,
+            &"ответ".to_string(),
+            &None,
+            &EntityContentType::Text,
+            5,
+        )
+        .await
+        .unwrap();
+        AuditLogEntry::add(&pool, &new_chat_db_id, &member_db_id, &AuditAction::Setting, "вес")
+            .await;
+        ChatDB::migrate(&pool, EXISTED_CHAT_ID, new_chat_id).await.unwrap();
+        assert_eq!(ChatDB::language(&pool, &chat_db_id).await, Some(Language::En));
+        assert_eq!(ChatDB::morph_answer_chance(&pool, &chat_db_id).await, Some(11));
+        assert_eq!(
+            AnswerEntity::weights(
+                &pool,
+                &chat_db_id,
+                &"ответ".to_string(),
+                &None,
+                &EntityContentType::Text,
+            )
+            .await,
+            vec![("ключ".to_string(), 5)]
+        );
+    }
 }